@@ -1,7 +1,4 @@
-use std::{
-    io::{IsTerminal, stdout},
-    path::PathBuf,
-};
+use std::path::PathBuf;
 
 use clap::{CommandFactory, Parser};
 use clap_complete::{generate, generate_to, shells};
@@ -10,9 +7,10 @@ use color_eyre::{
     eyre::{WrapErr, bail},
 };
 use pueue::client::{
-    cli::{CliArguments, ColorChoice, Shell, SubCommand},
-    handle_command,
-    style::OutputStyle,
+    cli::{CliArguments, Shell, SubCommand},
+    connection::override_connection,
+    follow_offline_command, handle_command, is_broken_pipe, print_log_file_command,
+    style::{OutputStyle, style_enabled},
 };
 use pueue_lib::{
     Client, network::socket::ConnectionSettings, secret::read_shared_secret, settings::Settings,
@@ -54,12 +52,8 @@ async fn main() -> Result<()> {
     if let Some(profile) = &opt.profile {
         settings.load_profile(profile)?;
     }
-
-    // Error if no configuration file can be found, as this is an indicator, that the daemon hasn't
-    // been started yet.
-    if !config_found {
-        bail!("Couldn't find a configuration file. Did you start the daemon yet?");
-    }
+    // Connect to another daemon endpoint for this invocation, if requested.
+    override_connection(&mut settings, &opt);
 
     // Determine the subcommand that has been called by the user.
     // If no subcommand is given, we default to the `status` subcommand without any arguments.
@@ -71,13 +65,29 @@ async fn main() -> Result<()> {
 
     // Determine whether we should color/style our output or not.
     // The user can explicitly disable/enable this, otherwise we check whether we are on a TTY.
-    let style_enabled = match opt.color {
-        ColorChoice::Auto => stdout().is_terminal(),
-        ColorChoice::Always => true,
-        ColorChoice::Never => false,
-    };
+    let style_enabled = style_enabled(&opt.color);
+    // Crossterm removes colors by itself if `NO_COLOR` is set, while other styling is kept.
+    // All styling thereby follows the state that has been determined above.
+    crossterm::style::force_color_output(style_enabled);
     let style = OutputStyle::new(&settings, style_enabled);
 
+    // Log files can be printed and followed without a running daemon.
+    match &subcommand {
+        SubCommand::Log { file: Some(_), .. } => {
+            return ignore_broken_pipe(print_log_file_command(&settings, &style, subcommand));
+        }
+        SubCommand::Follow { offline: true, .. } => {
+            return ignore_broken_pipe(follow_offline_command(settings, &style, subcommand).await);
+        }
+        _ => (),
+    }
+
+    // Error if no configuration file can be found, as this is an indicator, that the daemon hasn't
+    // been started yet.
+    if !config_found {
+        bail!("Couldn't find a configuration file. Did you start the daemon yet?");
+    }
+
     // Only show version incompatibility warnings if we aren't supposed to output json.
     let show_version_warning = match subcommand {
         SubCommand::Status { json, .. } => !json,
@@ -93,9 +103,16 @@ async fn main() -> Result<()> {
         .await
         .context("Failed to initialize client.")?;
 
-    handle_command(&mut client, settings, &style, subcommand).await?;
+    ignore_broken_pipe(handle_command(&mut client, settings, &style, subcommand).await)
+}
 
-    Ok(())
+/// The reader of our output went away, e.g. because it's piped into `head`.
+/// Just like other Unix tools, we silently stop in that case instead of reporting an error.
+fn ignore_broken_pipe(result: Result<()>) -> Result<()> {
+    match result {
+        Err(err) if is_broken_pipe(&err) => Ok(()),
+        result => result,
+    }
 }
 
 /// [clap] is capable of creating auto-generated shell completion files.
@@ -1,12 +1,17 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use chrono::{TimeDelta, prelude::*};
-use clap::{ArgAction, Parser, ValueEnum, ValueHint};
+use clap::{ArgAction, ArgGroup, Parser, ValueEnum, ValueHint};
 use interim::*;
 use pueue_lib::message::Signal;
 
-use crate::client::commands::WaitTargetStatus;
+use crate::client::{
+    commands::{JsonGroupBy, LineAmount, LogStatus, OnFinish, TimestampFormat, WaitTargetStatus},
+    connection::HostAddress,
+};
 
+// The subcommand is only parsed once, so the size of its largest variant doesn't matter.
+#[allow(clippy::large_enum_variant)]
 #[derive(Parser, Debug, Clone)]
 pub enum SubCommand {
     /// Enqueue a task for execution.
@@ -396,6 +401,8 @@ https://github.com/Nukesor/pueue/issues/350#issue-1359083118"
     ///
     /// Only the last few lines will be shown by default.
     /// If you want to follow the output of a task, please use the \"follow\" subcommand.
+    #[command(group(ArgGroup::new("json_output").args(["json", "json_lines"])))]
+    #[command(group(ArgGroup::new("line_regex").args(["filter", "search"])))]
     Log {
         /// View the task output of these specific tasks.
         task_ids: Vec<usize>,
@@ -408,46 +415,778 @@ https://github.com/Nukesor/pueue/issues/350#issue-1359083118"
         #[arg(short, long)]
         all: bool,
 
+        /// Show the logs of tasks whose label matches this pattern, e.g. `build-*`.
+        ///
+        /// Patterns with `*` or `?` wildcards need to match the whole label, all other patterns
+        /// match labels that contain them. Tasks of all groups are shown, unless --group is given.
+        #[arg(long, conflicts_with_all = ["task_ids", "all"])]
+        label: Option<String>,
+
+        /// Only show the logs of this many of the most recently started tasks.
+        ///
+        /// This avoids huge amounts of output for large histories, e.g. together with --all.
+        /// The amount of omitted tasks is mentioned at the end.
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+            conflicts_with_all = ["watch", "file"],
+        )]
+        max_tasks: Option<usize>,
+
+        /// Also show the task information of tasks with these statuses, even though they don't
+        /// have any output yet, e.g. `--include-status queued,stashed`.
+        ///
+        /// Possible values are `queued`, `stashed` and `locked`.
+        /// By default, only tasks that are running, paused or done are shown.
+        #[arg(
+            long,
+            value_name = "STATUSES",
+            value_delimiter = ',',
+            conflicts_with_all = [
+                "json", "json_lines", "output", "archive", "search", "count", "oneline",
+                "watch", "file",
+            ],
+        )]
+        include_status: Vec<LogStatus>,
+
         /// Print the resulting tasks and output as json.
         ///
         /// By default only the last lines will be returned unless --full is provided.
-        /// Take care, as the json cannot be streamed!
-        /// If your logs are really huge, using --full can use all of your machine's RAM.
+        /// The json is written one task at a time, so only the output of a single task is kept
+        /// in memory, unless its lines need to be processed.
+        ///
+        /// Each task's object contains a `schema` version, which is increased whenever the
+        /// format changes. The same goes for the records of --json-lines.
         #[arg(short, long)]
         json: bool,
 
+        /// Indent the json of --json, which makes it easier to read.
+        ///
+        /// The output is still written one task at a time.
+        #[arg(long, requires = "json", conflicts_with_all = ["oneline", "count"])]
+        json_pretty: bool,
+
+        /// The keys of the top-level object of --json, either `id`, `group` or `label`.
+        ///
+        /// `id` maps each task's id to its object: `{"<id>": {...}}`.
+        /// `group` nests the tasks by their group: `{"<group>": {"<id>": {...}}}`.
+        /// `label` nests the tasks by their label: `{"<label>": {"<id>": {...}}}`. Tasks without
+        /// a label are nested under an empty label. Defaults to `id`.
+        #[arg(
+            long,
+            value_name = "id|group|label",
+            requires = "json",
+            conflicts_with_all = ["oneline", "count"]
+        )]
+        json_group_by: Option<JsonGroupBy>,
+
+        /// Print each task and its output as a separate json record on its own line.
+        ///
+        /// Contrary to --json, each record is a complete json object, which can be parsed on
+        /// its own.
+        /// Just like --json, only the last lines are returned unless --full is provided.
+        #[arg(long, conflicts_with = "json")]
+        json_lines: bool,
+
+        /// Include the tasks' environment variables in the json output.
+        ///
+        /// The values of variables whose names match the `client.env_secret_patterns` setting
+        /// are redacted.
+        #[arg(long, requires = "json_output")]
+        with_env: bool,
+
+        /// Don't redact any environment variables when using --with-env.
+        #[arg(long, requires = "with_env")]
+        with_env_raw: bool,
+
         /// Only print the last X lines of each task's output.
         ///
-        /// This is done by default if you're looking at multiple tasks.
-        #[arg(short, long, conflicts_with = "full")]
-        lines: Option<usize>,
+        /// By default, the last 15 lines are shown, which can be changed via the
+        /// `client.default_log_lines` setting. `0` only shows the task information.
+        /// Percentages such as `50%` show that part of each task's output, e.g. its last half.
+        #[arg(short, long, visible_alias = "tail", conflicts_with = "full")]
+        lines: Option<LineAmount>,
+
+        /// Only print the first X lines of each task's output.
+        ///
+        /// If combined with --lines, the last lines are printed after the first lines and
+        /// the omitted lines in between are marked by `...`.
+        #[arg(long, conflicts_with = "full")]
+        head: Option<usize>,
+
+        /// Only print roughly the last X bytes of each task's output.
+        ///
+        /// Only complete lines are shown, unless a single line exceeds the limit.
+        /// Accepts human-readable sizes, such as `512K` or `2M`.
+        #[arg(long, value_parser = parse_byte_size, conflicts_with_all = ["full", "head"])]
+        max_bytes: Option<u64>,
 
         /// Show the whole output.
         #[arg(short, long)]
         full: bool,
 
+        /// Follow the output of running tasks, once the logs of all other tasks are printed.
+        ///
+        /// The last lines of each running task are shown first, just like `tail -f`.
+        /// Multiple running tasks are followed at once, their lines are prefixed with the task id.
+        #[arg(
+            short = 'F',
+            long,
+            conflicts_with_all = [
+                "json", "json_lines", "output", "archive", "search", "pager", "head", "since",
+                "until",
+            ],
+        )]
+        follow: bool,
+
+        /// Continuously show the last lines of each task on a full screen view, until Ctrl-C is
+        /// pressed.
+        ///
+        /// The height of the terminal is split between the tasks, unless --lines is given.
+        /// Only works if stdout is a terminal.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "json", "json_lines", "output", "archive", "search", "count", "pager", "follow",
+                "head", "max_bytes", "since", "until", "filter", "full",
+            ],
+        )]
+        watch: bool,
+
+        /// Print this log file instead of the logs of tasks, e.g. one that has been copied off
+        /// another machine. `-` reads the log from stdin.
+        ///
+        /// The file is formatted just like the logs of tasks, but the daemon isn't contacted.
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with_all = [
+                "task_ids", "group", "all", "label", "json", "json_lines", "with_env", "search",
+                "count", "since", "until", "stdout_only", "stderr_only", "interleaved", "output",
+                "archive", "follow", "watch",
+            ],
+        )]
+        file: Option<PathBuf>,
+
+        /// The interval in milliseconds at which the view of --watch is refreshed.
+        #[arg(
+            long,
+            value_name = "MS",
+            default_value_t = 2000,
+            requires = "watch",
+            value_parser = clap::value_parser!(u64).range(1..),
+        )]
+        interval: u64,
+
         /// Add timestamps to each line of the log output.
         #[arg(short, long)]
         timestamps: bool,
+
+        /// The format of the timestamps.
+        ///
+        /// Either a `strftime` format string, `rfc3339` or `unix`.
+        /// Defaults to the `client.log_timestamp_format` setting.
+        #[arg(long, requires = "timestamps")]
+        timestamp_format: Option<TimestampFormat>,
+
+        /// Show the timestamps in UTC instead of the local timezone.
+        #[arg(long, requires = "timestamps")]
+        timestamps_utc: bool,
+
+        /// Merge the timestamp of each line into lines that are JSON objects, e.g. of tasks that
+        /// write ndjson logs.
+        ///
+        /// The timestamp is added as a `_pueue_ts` field, or as a field with the given key via
+        /// `--parse-json=KEY`, unless the object already has such a field. All other lines are
+        /// passed through unchanged, apart from the prefix of --timestamps, which is implied.
+        #[arg(
+            long,
+            value_name = "KEY",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "_pueue_ts",
+            conflicts_with_all = ["archive", "search", "count", "oneline", "since_last"],
+        )]
+        parse_json: Option<String>,
+
+        /// Only show lines that match this regular expression.
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Only show lines that don't match the `--filter` regular expression.
+        #[arg(long, requires = "filter")]
+        invert_match: bool,
+
+        /// Match the `--filter` or `--search` regular expression case-insensitively.
+        #[arg(long, requires = "line_regex")]
+        ignore_case: bool,
+
+        /// Show this many lines around each line that matches `--filter` or `--search`, just
+        /// like `grep --context`.
+        ///
+        /// Groups of lines that aren't adjacent are separated by `--`.
+        #[arg(
+            short = 'C',
+            long,
+            value_name = "N",
+            requires = "line_regex",
+            conflicts_with_all = ["json", "json_lines", "count", "follow"],
+        )]
+        context: Option<usize>,
+
+        /// Show this many lines after each matching line. Overrides `--context`.
+        #[arg(
+            short = 'A',
+            long,
+            value_name = "N",
+            requires = "line_regex",
+            conflicts_with_all = ["json", "json_lines", "count", "follow"],
+        )]
+        after_context: Option<usize>,
+
+        /// Show this many lines in front of each matching line. Overrides `--context`.
+        #[arg(
+            short = 'B',
+            long,
+            value_name = "N",
+            requires = "line_regex",
+            conflicts_with_all = ["json", "json_lines", "count", "follow"],
+        )]
+        before_context: Option<usize>,
+
+        /// Search the full logs of all selected finished tasks for this regular expression.
+        ///
+        /// Only the tasks with matching lines are shown, followed by their matching lines.
+        /// A summary of how many tasks matched is printed at the end.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "json", "json_lines", "output", "archive", "lines", "head", "max_bytes", "full",
+                "timestamps", "since", "until", "stdout_only", "stderr_only", "interleaved",
+                "number", "strip_ansi", "pager", "wrap",
+            ],
+        )]
+        search: Option<String>,
+
+        /// Only print the amount of lines and bytes of each task's output, without its content.
+        ///
+        /// With --search, only the amount of matching lines of each task is printed instead.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "json_lines", "output", "archive", "lines", "head", "max_bytes", "full",
+                "timestamps", "since", "until", "interleaved", "number", "strip_ansi", "pager",
+                "wrap", "follow",
+            ],
+        )]
+        count: bool,
+
+        /// Print a single line per task with its id, its status and the last line of its output.
+        ///
+        /// Lines are cut off at the terminal width. With --json, the status and last line of
+        /// each task are printed as a JSON object instead.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "json_lines", "output", "archive", "search", "count", "lines", "head",
+                "max_bytes", "full", "timestamps", "filter", "since", "until", "interleaved",
+                "number", "pager", "wrap", "follow", "watch", "file",
+            ],
+        )]
+        oneline: bool,
+
+        /// Only show the output that has been written since the previous `log --since-last`.
+        ///
+        /// The printed part of each task's log is remembered in the pueue directory, so
+        /// repeated calls behave like an incremental tail. If a log shrank in the meantime, e.g.
+        /// because its task has been restarted, it's shown from the start again.
+        /// This requires `client.read_local_logs`.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "json", "json_lines", "output", "archive", "search", "count", "oneline", "lines",
+                "head", "max_bytes", "full", "timestamps", "filter", "since", "until",
+                "stdout_only", "stderr_only", "interleaved", "number", "pager", "wrap", "follow",
+                "watch", "file", "include_status",
+            ],
+        )]
+        since_last: bool,
+
+        /// Dump the log of a single task exactly as it's stored, without decompressing or
+        /// decoding it.
+        ///
+        /// This is a last resort for debugging corrupted logs or mismatching compression codecs.
+        /// Local logs are copied from the file on disk, which is compressed if the daemon
+        /// compressed the log. Otherwise, the compressed output of the daemon is dumped.
+        /// The output may be binary.
+        #[arg(
+            long,
+            requires = "task_ids",
+            conflicts_with_all = [
+                "group", "all", "label", "json", "json_lines", "output", "archive", "search",
+                "count", "oneline", "since_last", "lines", "head", "max_bytes", "full",
+                "timestamps", "filter", "since", "until", "stdout_only", "stderr_only",
+                "interleaved", "number", "pager", "wrap", "follow", "watch", "file",
+                "include_status", "strip_ansi",
+            ],
+        )]
+        raw: bool,
+
+        /// Only show output that has been written at or after this time.
+        ///
+        /// Either an RFC 3339 timestamp or a time relative to now, such as `10m` or `2h`.
+        /// Lines are only filtered individually, if logs are read locally and the daemon recorded
+        /// their timestamps via `daemon.record_log_timestamps`.
+        /// Otherwise, only tasks that didn't run during this time are hidden.
+        #[arg(long, value_parser = parse_log_time)]
+        since: Option<DateTime<Local>>,
+
+        /// Only show output that has been written at or before this time.
+        ///
+        /// Accepts the same formats and has the same limitations as `--since`.
+        #[arg(long, value_parser = parse_log_time)]
+        until: Option<DateTime<Local>>,
+
+        /// Only show the output that has been written to stdout.
+        ///
+        /// This requires the `daemon.separate_log_streams` setting. Otherwise, the output of
+        /// both streams is shown.
+        #[arg(long, conflicts_with_all = ["stderr_only", "interleaved", "json", "json_lines", "archive"])]
+        stdout_only: bool,
+
+        /// Only show the output that has been written to stderr.
+        ///
+        /// This requires the `daemon.separate_log_streams` setting. Otherwise, the output of
+        /// both streams is shown.
+        #[arg(long, conflicts_with_all = ["interleaved", "json", "json_lines", "archive"])]
+        stderr_only: bool,
+
+        /// Prefix each line with the stream it has been written to, `O: ` for stdout and
+        /// `E: ` for stderr.
+        ///
+        /// This requires the `daemon.separate_log_streams` setting and only works for logs that
+        /// are read locally.
+        #[arg(long, conflicts_with_all = ["json", "json_lines", "archive"])]
+        interleaved: bool,
+
+        /// Prefix each line with its line number, like `cat -n`.
+        ///
+        /// Lines are numbered by their position in the full output, even if only some of them
+        /// are shown. The line numbers are shown in front of any other prefix, such as timestamps.
+        #[arg(short, long, conflicts_with_all = ["json", "json_lines", "archive"])]
+        number: bool,
+
+        /// Collapse consecutive identical lines into a single line, followed by a
+        /// `(repeated N times)` marker.
+        #[arg(long, conflicts_with_all = ["json", "json_lines", "archive", "search"])]
+        squash_repeats: bool,
+
+        /// Remove ANSI escape sequences, such as colors, from the tasks' output.
+        #[arg(long, conflicts_with_all = ["json", "json_lines", "archive"])]
+        strip_ansi: bool,
+
+        /// Print the task information and output headers to stdout instead of stderr.
+        ///
+        /// This allows to capture the headers together with the output, e.g. when redirecting
+        /// the output into a file.
+        #[arg(long, conflicts_with_all = ["json", "json_lines", "output", "archive"])]
+        header_to_stdout: bool,
+
+        /// Only print a single `Task <id> (<status>):` line as task information, followed by
+        /// the raw output.
+        ///
+        /// No tables or output headers are printed, which is useful for scripts and narrow
+        /// terminals.
+        #[arg(long, conflicts_with_all = ["json", "json_lines", "archive"])]
+        plain: bool,
+
+        /// Don't print any task information or output headers, only the output itself.
+        ///
+        /// The output of multiple tasks isn't separated, unless --separator is provided.
+        /// Hints, e.g. if there are no finished tasks, are still printed to stderr.
+        #[arg(
+            long,
+            conflicts_with_all = ["json", "json_lines", "archive", "search", "plain", "header_to_stdout", "with_header"],
+        )]
+        no_task_info: bool,
+
+        /// Print this line between the logs of two tasks, instead of an empty line.
+        ///
+        /// With --no-task-info, the output of multiple tasks is only separated if this is given.
+        #[arg(
+            long,
+            allow_hyphen_values = true,
+            conflicts_with_all = ["json", "json_lines", "archive", "output", "search"],
+        )]
+        separator: Option<String>,
+
+        /// Print a summary line, such as `Showing logs for 5 tasks (group 'ci')`, in front of
+        /// the logs.
+        ///
+        /// The summary is printed to stderr, so it doesn't end up in piped output.
+        #[arg(long, conflicts_with_all = ["json", "json_lines", "archive", "output", "search"])]
+        summary: bool,
+
+        /// Prepend a line of task metadata to each task's output, such as
+        /// `#pueue task=3 group=default label=build status=success exit=0 start=... end=...`.
+        ///
+        /// In contrast to the task information, this line is part of the output itself.
+        /// Concatenated or archived logs thereby stay self-describing, even if the headers are
+        /// lost. Values that contain whitespace are quoted.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "json", "json_lines", "archive", "search", "count", "oneline", "since_last", "raw",
+                "watch", "file", "to_syslog",
+            ],
+        )]
+        annotate: bool,
+
+        /// The prefix of the --annotate metadata lines.
+        /// Defaults to the `client.log_annotation_prefix` setting.
+        #[arg(long, requires = "annotate", allow_hyphen_values = true)]
+        annotate_prefix: Option<String>,
+
+        /// Show output that looks binary, even if it's printed to a terminal.
+        ///
+        /// By default, output that contains NUL bytes within its first 8 KiB isn't printed to
+        /// terminals, as it would garble them. A notice is shown instead.
+        #[arg(long, conflicts_with_all = ["binary", "json", "json_lines", "archive", "raw"])]
+        text: bool,
+
+        /// Never show output that looks binary, even if it isn't printed to a terminal.
+        #[arg(long, conflicts_with_all = ["json", "json_lines", "archive", "raw"])]
+        binary: bool,
+
+        /// Pipe the output through `$PAGER`, or `less` if that isn't set.
+        ///
+        /// The task information is shown in the pager as well. The pager is only used, if stdout
+        /// is a terminal. Defaults to the `client.pager` setting.
+        #[arg(long, conflicts_with_all = ["no_pager", "json", "json_lines", "output", "archive"])]
+        pager: bool,
+
+        /// Don't pipe the output through a pager, even if the `client.pager` setting is enabled.
+        #[arg(long)]
+        no_pager: bool,
+
+        /// Wrap long lines at the width of the terminal.
+        ///
+        /// Wrapped lines are indented, so they're aligned under the content instead of any
+        /// prefixes, such as timestamps. Lines are only wrapped, if stdout is a terminal.
+        /// Defaults to the `client.wrap_logs` setting.
+        #[arg(long, conflicts_with_all = ["no_wrap", "json", "json_lines", "output", "archive"])]
+        wrap: bool,
+
+        /// Don't wrap long lines, even if the `client.wrap_logs` setting is enabled.
+        #[arg(long)]
+        no_wrap: bool,
+
+        /// Highlight lines by their log level, such as `ERROR` or `WARN`.
+        ///
+        /// The log level is only recognized at the start of a line, optionally after a timestamp.
+        /// The colors are configured via the `client.log_level_colors` setting.
+        /// Lines are only highlighted, if colors are enabled.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "strip_ansi", "json", "json_lines", "output", "archive", "search", "count",
+            ],
+        )]
+        color_levels: bool,
+
+        /// Reset the terminal styles wherever lines of the output have been cut off.
+        ///
+        /// Otherwise, a color that has been set by the task before the shown lines would still
+        /// apply to them.
+        #[arg(long, conflicts_with_all = ["json", "json_lines", "archive", "search", "count"])]
+        ansi_safe_truncate: bool,
+
+        /// Write the output into this file instead of printing it.
+        ///
+        /// The full output is written, unless --lines is provided.
+        /// If the path is a directory, each task's output is written to a `<task_id>.log` file
+        /// inside of it. Otherwise, the task id is appended to the path, if the output of
+        /// multiple tasks is written.
+        #[arg(long, value_hint = ValueHint::AnyPath, conflicts_with_all = ["json", "json_lines"])]
+        output: Option<PathBuf>,
+
+        /// Also write the task information and output headers into the output file.
+        #[arg(long, requires = "output")]
+        with_header: bool,
+
+        /// Forward each line of the output to the local syslog daemon instead of printing it.
+        ///
+        /// The full output is forwarded, unless --lines is provided.
+        /// Each line is sent as a single message tagged with `pueue[task=<id>]`, without any
+        /// styling or task information.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "json", "json_lines", "output", "archive", "search", "count", "pager", "follow",
+                "watch", "file",
+            ],
+        )]
+        to_syslog: bool,
+
+        /// Forward each line of the output to the systemd journal instead of printing it.
+        ///
+        /// Just like --to-syslog, but the task's id and label are attached to each message as
+        /// `PUEUE_TASK_ID` and `PUEUE_TASK_LABEL` fields.
+        #[cfg(all(feature = "journald", target_os = "linux"))]
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "json", "json_lines", "output", "archive", "search", "count", "pager", "follow",
+                "watch", "file", "to_syslog",
+            ],
+        )]
+        to_journald: bool,
+
+        /// Bundle the logs of all finished tasks into this gzip compressed tar archive.
+        ///
+        /// This is intended to be used with --group to archive batches of tasks.
+        /// Each task's log is written to a `<id>-<label>.log` entry and a `manifest.json`
+        /// describes the commands, results, start and end times of all archived tasks.
+        #[arg(
+            long,
+            value_hint = ValueHint::FilePath,
+            conflicts_with_all = ["json", "json_lines", "output", "lines", "head", "max_bytes", "timestamps", "filter", "since", "until"],
+        )]
+        archive: Option<PathBuf>,
     },
 
     /// Follow the output of a currently running task.
     /// This command works like "tail -f".
     #[command(alias("fo"))]
     Follow {
-        /// The id of the task you want to watch.
+        /// The ids of the tasks you want to watch.
         ///
         /// If no or multiple tasks are running, you have to specify the id.
         /// If only a single task is running, you can omit the id.
-        task_id: Option<usize>,
+        /// If multiple ids are given, each line is prefixed with the id of its task.
+        task_ids: Vec<usize>,
+
+        /// Follow all running tasks of a group, including tasks that start running later on.
+        /// Stops once there're no running tasks left in the group.
+        #[arg(short, long, conflicts_with = "task_ids")]
+        group: Option<String>,
+
+        /// Follow the task whose label matches this pattern, e.g. `build-*`.
+        ///
+        /// If there's no such task yet, we wait until it has been added.
+        /// Patterns with `*` or `?` wildcards need to match the whole label, all other patterns
+        /// match labels that contain them. Fails if multiple tasks match.
+        #[arg(long, conflicts_with_all = ["task_ids", "group"])]
+        label: Option<String>,
+
+        /// Wait for the next task of the --group that starts running and only follow that task,
+        /// until it finishes.
+        ///
+        /// Tasks that have already been started are ignored. If multiple tasks start at the
+        /// same time, the one with the lowest id is followed.
+        #[arg(long, requires = "group")]
+        next: bool,
 
-        /// Only print the last X lines of the output before following
+        /// Only print the last X lines of the output before following.
+        ///
+        /// `0` doesn't print any previous output, only output that's written from now on.
         #[arg(short, long)]
         lines: Option<usize>,
 
+        /// Only print roughly the last X bytes of the output before following.
+        ///
+        /// The log is searched from its end by bytes instead of lines, which is much faster for
+        /// huge logs. Only complete lines are shown, unless a single line exceeds the limit.
+        /// Accepts human-readable sizes, such as `512K` or `2M`.
+        #[arg(long, visible_alias = "tail-bytes", value_parser = parse_byte_size)]
+        max_bytes: Option<u64>,
+
+        /// The interval in milliseconds at which local log files are checked for new output.
+        ///
+        /// Lower values show new output sooner, at the cost of more CPU usage.
+        /// Defaults to the `client.follow_interval_ms` setting.
+        #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..))]
+        interval: Option<u64>,
+
+        /// Reconnect to the daemon if the connection is lost, e.g. due to a daemon restart.
+        ///
+        /// Following is resumed where it left off. Log files that temporarily disappear are
+        /// waited for, as long as their task is still running.
+        #[arg(long)]
+        retry: bool,
+
+        /// Don't print the result and runtime of the followed tasks once they finished.
+        #[arg(long)]
+        no_summary: bool,
+
+        /// Don't show a spinner while waiting for the first output.
+        ///
+        /// The spinner is only shown if stderr is a terminal.
+        #[arg(long)]
+        quiet_wait: bool,
+
         /// Add timestamps to each line of the log output.
         #[arg(short, long)]
         timestamps: bool,
+
+        /// The format of the timestamps.
+        ///
+        /// Either a `strftime` format string, `rfc3339` or `unix`.
+        /// Defaults to the `client.log_timestamp_format` setting.
+        #[arg(long, requires = "timestamps")]
+        timestamp_format: Option<TimestampFormat>,
+
+        /// Show the timestamps in UTC instead of the local timezone.
+        #[arg(long, requires = "timestamps")]
+        timestamps_utc: bool,
+
+        /// Only show lines that match this regular expression.
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Only show lines that don't match the `--filter` regular expression.
+        #[arg(long, requires = "filter")]
+        invert_match: bool,
+
+        /// Match the `--filter` regular expression case-insensitively.
+        #[arg(long, requires = "filter")]
+        ignore_case: bool,
+
+        /// Don't prefix lines with the task id when following multiple tasks.
+        #[arg(long)]
+        no_prefix: bool,
+
+        /// Only follow the output that's written to stdout.
+        ///
+        /// This requires the `daemon.separate_log_streams` setting. Otherwise, the output of
+        /// both streams is followed.
+        #[arg(long, conflicts_with_all = ["stderr_only", "interleaved"])]
+        stdout_only: bool,
+
+        /// Only follow the output that's written to stderr.
+        ///
+        /// This requires the `daemon.separate_log_streams` setting. Otherwise, the output of
+        /// both streams is followed.
+        #[arg(long, conflicts_with = "interleaved")]
+        stderr_only: bool,
+
+        /// Prefix each line with the stream it has been written to, `O: ` for stdout and
+        /// `E: ` for stderr.
+        ///
+        /// This requires the `daemon.separate_log_streams` setting and only works for logs that
+        /// are read locally.
+        #[arg(long)]
+        interleaved: bool,
+
+        /// Print each chunk of output as a json object on its own line.
+        ///
+        /// Each object contains the `task_id`, the `content` and the `timestamp` at which the
+        /// content has been read. Only complete lines are included, unless the task finished.
+        #[arg(long, conflicts_with_all = ["timestamps", "filter", "interleaved"])]
+        json: bool,
+
+        /// Always read the log files from the local pueue directory.
+        ///
+        /// By default, tasks are followed locally if their log file exists locally and the
+        /// `client.read_local_logs` setting is enabled. All other tasks are streamed by the daemon.
+        #[arg(long, conflicts_with = "remote")]
+        local: bool,
+
+        /// Always let the daemon stream the output, even if the log files exist locally.
+        #[arg(long)]
+        remote: bool,
+
+        /// Stop following after this duration, such as `30s`, `10m` or `2h`, even if the tasks
+        /// are still running.
+        ///
+        /// The client exits with code 124 in that case. The tasks themselves aren't affected.
+        #[arg(long, value_parser = parse_duration)]
+        timeout: Option<Duration>,
+
+        /// Print the amount of followed output and its average throughput once following stops.
+        #[arg(long)]
+        stats: bool,
+
+        /// Collapse consecutive identical lines into a single line, followed by a
+        /// `(repeated N times)` marker.
+        ///
+        /// The marker is printed once a different line arrives or the task finished.
+        #[arg(long, conflicts_with = "json")]
+        squash_repeats: bool,
+
+        /// Print at most this amount of lines per second of each task.
+        ///
+        /// Excess lines are skipped, which is pointed out via a `(throttled, skipped N lines)`
+        /// notice at most once per second. Defaults to the `client.follow_throttle` setting.
+        #[arg(
+            long,
+            value_name = "LINES_PER_SEC",
+            value_parser = clap::value_parser!(u32).range(1..),
+            conflicts_with = "json"
+        )]
+        throttle: Option<u32>,
+
+        /// Follow the local log files without contacting the daemon, e.g. while it's down.
+        ///
+        /// As the daemon isn't asked about the tasks, it can't be detected whether they finished.
+        /// The log files are followed until they disappear or until Ctrl-C is pressed.
+        #[arg(
+            long,
+            requires = "task_ids",
+            conflicts_with_all = [
+                "group", "label", "max_bytes", "retry", "no_summary", "quiet_wait", "local",
+                "remote", "timeout", "stats", "follow_restarts", "on_finish",
+            ],
+        )]
+        offline: bool,
+
+        /// Keep following tasks that are restarted in place.
+        ///
+        /// Once a task finished, it's still followed for a grace period of 10 seconds.
+        /// If it's restarted with `pueue restart --in-place` in the meantime, the output of the
+        /// new run is followed as well.
+        #[arg(long)]
+        follow_restarts: bool,
+
+        /// What to do once the followed tasks finished, either `exit` or `wait`.
+        ///
+        /// `exit` stops following them. `wait` keeps following them, in case they're restarted in
+        /// place, until --timeout is reached or Ctrl-C is pressed. Defaults to `exit`.
+        #[arg(long, value_name = "exit|wait", conflicts_with = "follow_restarts")]
+        on_finish: Option<OnFinish>,
+
+        /// Append the followed output to this file as well, e.g. to review a live session later.
+        ///
+        /// The file receives exactly what's printed, including any prefixes or timestamps.
+        /// If the file can't keep up, some output is dropped from it instead of holding up the
+        /// output to the terminal.
+        #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+        tee: Option<PathBuf>,
+
+        /// Clear the terminal before each new burst of output, so it replaces the previous one.
+        ///
+        /// This emulates in-place progress for a single task that redraws its output, e.g. a
+        /// progress bar. Contrary to `log --watch`, the terminal is only cleared once new output
+        /// arrives. Nothing is cleared if stdout isn't a terminal.
+        #[arg(long, conflicts_with = "json")]
+        clear: bool,
+
+        /// Exit with the exit code of the followed task, once it finished.
+        ///
+        /// Tasks that succeeded exit with `0` and failed tasks with their own exit code.
+        /// Tasks that failed to spawn, have been killed, errored or whose dependency failed
+        /// exit with `1`. If multiple tasks are followed, the first task that didn't succeed
+        /// determines the exit code.
+        #[arg(long, conflicts_with = "offline")]
+        exit_code: bool,
+
+        /// Don't print the information of each task on stderr once it started.
+        ///
+        /// By default, the id, command and status of each followed task are shown on top of its
+        /// output, even if it hasn't written any output yet. There's no header with --json.
+        #[arg(long)]
+        no_header: bool,
     },
 
     /// Wait until tasks are finished.
@@ -623,6 +1362,23 @@ pub struct CliArguments {
     #[arg(short, long)]
     pub profile: Option<String>,
 
+    /// Connect to the daemon via this unix socket instead of the configured endpoint.
+    ///
+    /// Logs are sent by the daemon, unless this is the configured socket, as the logs of
+    /// other daemons aren't in the configured pueue directory.
+    #[cfg(not(target_os = "windows"))]
+    #[arg(long, global = true, value_hint = ValueHint::FilePath, conflicts_with = "host")]
+    pub socket: Option<PathBuf>,
+
+    /// Connect to the daemon at this address via TCP instead of the configured endpoint,
+    /// e.g. `server`, `server:6924` or `[::1]:6924`.
+    ///
+    /// The configured port is used, if none is given. Logs are sent by the daemon, unless this
+    /// is the configured address. The shared secret and TLS certificate are still taken from
+    /// the configuration.
+    #[arg(long, global = true, value_hint = ValueHint::Hostname)]
+    pub host: Option<HostAddress>,
+
     #[command(subcommand)]
     pub cmd: Option<SubCommand>,
 }
@@ -643,3 +1399,61 @@ fn parse_delay_until(src: &str) -> Result<DateTime<Local>, String> {
         "could not parse as seconds or date expression",
     ))
 }
+
+/// Parse a human-readable byte size for the `--max-bytes` options, such as `512K` or `2M`.
+/// The units are powers of 1024 and a trailing `B` is optional.
+fn parse_byte_size(src: &str) -> Result<u64, String> {
+    let size = src.strip_suffix(['B', 'b']).unwrap_or(src);
+    let amount = size.trim_end_matches(|char: char| char.is_ascii_alphabetic());
+    let unit = &size[amount.len()..];
+    let factor = match unit.to_ascii_uppercase().as_str() {
+        "" => Some(1),
+        "K" => Some(1024),
+        "M" => Some(1024 * 1024),
+        "G" => Some(1024 * 1024 * 1024),
+        _ => None,
+    };
+
+    amount
+        .parse::<u64>()
+        .ok()
+        .zip(factor)
+        .and_then(|(amount, factor)| amount.checked_mul(factor))
+        .ok_or_else(|| String::from("could not parse as byte size, such as '512K' or '2M'"))
+}
+
+/// Parse a point in time for the `--since` and `--until` log options.
+/// This is either an RFC 3339 timestamp or a duration relative to now, such as `10m` or `2h`.
+fn parse_log_time(src: &str) -> Result<DateTime<Local>, String> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(src) {
+        return Ok(date_time.with_timezone(&Local));
+    }
+
+    match parse_time_delta(src) {
+        Some(delta) => Ok(Local::now() - delta),
+        None => Err(String::from(
+            "could not parse as RFC 3339 timestamp or relative time, such as '10m' or '2h'",
+        )),
+    }
+}
+
+/// Parse a positive duration, such as `30s` or `2h`, for the `follow --timeout` option.
+fn parse_duration(src: &str) -> Result<Duration, String> {
+    parse_time_delta(src)
+        .and_then(|delta| delta.to_std().ok())
+        .filter(|duration| !duration.is_zero())
+        .ok_or_else(|| String::from("could not parse as duration, such as '30s', '10m' or '2h'"))
+}
+
+/// Parse an amount of seconds, minutes, hours or days, such as `10m` or `2h`.
+fn parse_time_delta(src: &str) -> Option<TimeDelta> {
+    let amount = src.trim_end_matches(|char: char| char.is_ascii_alphabetic());
+    let unit = &src[amount.len()..];
+    amount.parse::<i64>().ok().and_then(|amount| match unit {
+        "s" => TimeDelta::try_seconds(amount),
+        "m" => TimeDelta::try_minutes(amount),
+        "h" => TimeDelta::try_hours(amount),
+        "d" => TimeDelta::try_days(amount),
+        _ => None,
+    })
+}
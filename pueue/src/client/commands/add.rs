@@ -8,10 +8,10 @@ use std::{
 use chrono::{DateTime, Local};
 use pueue_lib::{
     Client, Request, Response, Settings,
-    message::{AddRequest, AddedTaskResponse},
+    message::{AddRequest, AddedTaskResponse, TaskSelection},
 };
 
-use super::{follow as follow_cmd, group_or_default, handle_response};
+use super::{FollowOptions, follow as follow_cmd, group_or_default, handle_response};
 use crate::{client::style::OutputStyle, format::format_datetime, internal_prelude::*};
 
 #[allow(clippy::too_many_arguments)]
@@ -103,7 +103,15 @@ pub async fn add_task(
     }
 
     if follow {
-        follow_cmd(client, settings, style, Some(task_id), None, false).await?;
+        // The task information has already been printed above.
+        follow_cmd(
+            client,
+            settings,
+            style,
+            TaskSelection::TaskIds(vec![task_id]),
+            FollowOptions::default().summary(true),
+        )
+        .await?;
     }
 
     Ok(())
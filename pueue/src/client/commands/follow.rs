@@ -1,294 +1,2446 @@
 use std::{
-    io::{self, Write},
-    time::Duration,
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    fs::{File, OpenOptions},
+    io::{self, IsTerminal, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{SyncSender, TrySendError, sync_channel},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
-use chrono::Local;
+use chrono::{DateTime, Local, TimeDelta};
+use crossterm::{
+    cursor::{Hide, MoveTo, MoveToColumn, Show},
+    execute, queue,
+    style::Attribute,
+    terminal::{Clear, ClearType},
+};
 use pueue_lib::{
     Client, Response, Settings,
-    log::{get_log_file_handle, get_log_path, seek_to_last_lines},
+    log::{
+        OutputStream, decode_line, get_compressed_log_path, get_log_path,
+        get_stream_log_file_handle, get_stream_log_path, log_file_exists, seek_to_last_bytes,
+        seek_to_last_lines,
+    },
     message::{StreamRequest, TaskSelection},
+    network::socket::ConnectionSettings,
+    secret::read_shared_secret,
+    task::{Task, TaskResult, TaskStatus},
 };
+use serde::Serialize;
+use strum::EnumString;
 use tokio::time::sleep;
 
 use crate::{
     client::{
-        commands::{get_state, get_task},
-        display_helper::print_error,
+        commands::{
+            get_state,
+            log::{
+                LabelPattern, LineFilter, LineStreams, LineTimestamps, SquashRepeats,
+                TimestampOptions, filter_line, last_bytes_start, log_file_error, task_info,
+                task_status, warn_missing_stream,
+            },
+        },
+        display_helper::{format_runtime, print_error},
         style::OutputStyle,
     },
     internal_prelude::*,
 };
 
+/// All options of the `follow` command.
+///
+/// Use [FollowOptions::default] and the builder methods to construct the options, so newly added
+/// options don't break existing callers.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct FollowOptions {
+    /// Only show the last X lines of the output that has been written before we started following.
+    pub lines: Option<usize>,
+    /// Only show roughly the last X bytes of the output that has been written before we started
+    /// following.
+    pub max_bytes: Option<u64>,
+    /// Add timestamps to each line of the output.
+    pub timestamps: Option<TimestampOptions>,
+    /// Don't prefix each line with the id of its task when following multiple tasks.
+    pub no_prefix: bool,
+    /// Only show lines that match this filter.
+    pub filter: Option<LineFilter>,
+    /// Only follow the output of this stream.
+    pub stream: Option<OutputStream>,
+    /// Prefix each line with the stream it has been written to.
+    pub interleaved: bool,
+    /// Print each chunk of output as a json record, see [FollowRecord].
+    pub json: bool,
+    /// Reconnect to the daemon if the connection is lost while following, e.g. due to a restart
+    /// of the daemon.
+    pub retry: bool,
+    /// Print the result and runtime of each followed task that finished at the end.
+    pub summary: bool,
+    /// Don't show a spinner on stderr until the first output arrives.
+    pub quiet_wait: bool,
+    /// Force the logs to be read from the local log files or to be streamed by the daemon.
+    /// Otherwise, see [FollowSources::new].
+    pub source: Option<LogSource>,
+    /// Stop following once this timeout has been reached and exit with
+    /// [FOLLOW_TIMEOUT_EXIT_CODE], even if the tasks are still running.
+    pub timeout: Option<Duration>,
+    /// Print the amount of followed output and its throughput at the end, see [FollowStats].
+    pub stats: bool,
+    /// Only print consecutive identical lines of a task once, followed by a marker with the
+    /// amount of repeats, see [SquashRepeats].
+    pub squash_repeats: bool,
+    /// Whether tasks that finished are still followed, see [OnFinish].
+    /// If they're restarted in place in the meantime, the output of the new run is followed
+    /// as well.
+    pub on_finish: OnFinish,
+    /// Append all output to this file as well, see [TeeFile].
+    pub tee: Option<PathBuf>,
+    /// Clear the terminal before each new burst of output, see [ClearScreen].
+    pub clear: bool,
+    /// Exit with the exit code of the followed tasks once they finished, see [task_exit_code].
+    pub exit_code: bool,
+    /// Print the information of each task on stderr once it started, before its output is
+    /// streamed, see [print_follow_header].
+    pub header: bool,
+}
+
+impl FollowOptions {
+    pub fn lines(mut self, lines: impl Into<Option<usize>>) -> Self {
+        self.lines = lines.into();
+        self
+    }
+
+    pub fn max_bytes(mut self, max_bytes: impl Into<Option<u64>>) -> Self {
+        self.max_bytes = max_bytes.into();
+        self
+    }
+
+    pub fn timestamps(mut self, timestamps: impl Into<Option<TimestampOptions>>) -> Self {
+        self.timestamps = timestamps.into();
+        self
+    }
+
+    pub fn no_prefix(mut self, no_prefix: bool) -> Self {
+        self.no_prefix = no_prefix;
+        self
+    }
+
+    pub fn filter(mut self, filter: impl Into<Option<LineFilter>>) -> Self {
+        self.filter = filter.into();
+        self
+    }
+
+    pub fn stream(mut self, stream: impl Into<Option<OutputStream>>) -> Self {
+        self.stream = stream.into();
+        self
+    }
+
+    pub fn interleaved(mut self, interleaved: bool) -> Self {
+        self.interleaved = interleaved;
+        self
+    }
+
+    pub fn json(mut self, json: bool) -> Self {
+        self.json = json;
+        self
+    }
+
+    pub fn retry(mut self, retry: bool) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn summary(mut self, summary: bool) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    pub fn quiet_wait(mut self, quiet_wait: bool) -> Self {
+        self.quiet_wait = quiet_wait;
+        self
+    }
+
+    pub fn source(mut self, source: impl Into<Option<LogSource>>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.timeout = timeout.into();
+        self
+    }
+
+    pub fn stats(mut self, stats: bool) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    pub fn squash_repeats(mut self, squash_repeats: bool) -> Self {
+        self.squash_repeats = squash_repeats;
+        self
+    }
+
+    pub fn on_finish(mut self, on_finish: OnFinish) -> Self {
+        self.on_finish = on_finish;
+        self
+    }
+
+    pub fn tee(mut self, tee: impl Into<Option<PathBuf>>) -> Self {
+        self.tee = tee.into();
+        self
+    }
+
+    pub fn clear(mut self, clear: bool) -> Self {
+        self.clear = clear;
+        self
+    }
+
+    pub fn exit_code(mut self, exit_code: bool) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+}
+
 /// Wrapper around following logic.
 ///
 /// Log files may be read directly on the local machine, but they may also be streamed via the
 /// daemon in case they're somewhere inaccessible or on a remote machine.
+///
+/// When following multiple tasks, each line is prefixed with the id of its task,
+/// unless [FollowOptions::no_prefix] is set.
+///
+/// When following a group, all of its running tasks are followed, including those that start
+/// running while following. We stop once there're no running tasks left in that group.
 pub async fn follow(
     client: &mut Client,
     settings: Settings,
     style: &OutputStyle,
-    task_id: Option<usize>,
-    lines: Option<usize>,
-    timestamps: bool,
+    selection: TaskSelection,
+    options: FollowOptions,
 ) -> Result<()> {
+    let prefix_ids = match &selection {
+        TaskSelection::TaskIds(task_ids) => task_ids.len() > 1,
+        TaskSelection::Group(_) | TaskSelection::All => true,
+    } && !options.no_prefix;
+    let group = match &selection {
+        TaskSelection::Group(group) => Some(group.clone()),
+        _ => None,
+    };
+
     // If we're supposed to read the log files from the local system, we don't have to
     // do any communication with the daemon.
     // Thereby we handle this in a separate function.
-    if settings.client.read_local_logs {
-        local_follow(client, settings, task_id, lines, timestamps).await?;
+    let tee = options.tee.as_deref().map(TeeFile::open).transpose()?;
+    // All sources write to the same sink, so their lines never get mixed up.
+    let sink = LogSink::new(TeeWriter::new(
+        ClearScreen::new(io::stdout(), options.clear),
+        tee.as_ref(),
+    ));
+    let spinner =
+        (!options.quiet_wait && io::stderr().is_terminal()).then(|| WaitSpinner::start(style));
+    let mut stats = FollowStats::new();
+    let interrupt = Interrupt::new(options.clear);
+    // All tasks are followed until they finished, unless the timeout is reached first.
+    let following = async {
+        let sources = FollowSources::new(client, &settings, selection, options.source).await?;
+        let followed_ids = match sources {
+            FollowSources::Local(selection) => {
+                let context = FollowContext {
+                    options: &options,
+                    prefix_ids,
+                    sink: sink.clone(),
+                    spinner,
+                    stats: &mut stats,
+                };
+                local_follow(client, settings.clone(), style, selection, context).await?
+            }
+            FollowSources::Remote(selection) => {
+                let context = FollowContext {
+                    options: &options,
+                    prefix_ids,
+                    sink: sink.clone(),
+                    spinner,
+                    stats: &mut stats,
+                };
+                remote_follow(client, &settings, style, selection, context).await?
+            }
+            FollowSources::Both { local, remote } => {
+                // The remote tasks are streamed via a separate connection, while the local tasks
+                // are followed at the same time. The spinner would get in the way of either one.
+                drop(spinner);
+                let mut remote_client = reconnect(&settings).await?;
+                let mut remote_stats = FollowStats::new();
+                let local_context = FollowContext {
+                    options: &options,
+                    prefix_ids,
+                    sink: sink.clone(),
+                    spinner: None,
+                    stats: &mut stats,
+                };
+                let remote_context = FollowContext {
+                    options: &options,
+                    prefix_ids,
+                    sink: sink.clone(),
+                    spinner: None,
+                    stats: &mut remote_stats,
+                };
+                let (mut followed_ids, remote_ids) = tokio::try_join!(
+                    local_follow(
+                        client,
+                        settings.clone(),
+                        style,
+                        TaskSelection::TaskIds(local),
+                        local_context,
+                    ),
+                    remote_follow(
+                        &mut remote_client,
+                        &settings,
+                        style,
+                        TaskSelection::TaskIds(remote),
+                        remote_context,
+                    ),
+                )?;
+                followed_ids.extend(remote_ids);
+                stats.merge(remote_stats);
+                followed_ids
+            }
+        };
+        Result::<BTreeSet<usize>>::Ok(followed_ids)
+    };
+    // Dropping the follow loop on Ctrl-C restores the terminal, see [ClearScreen].
+    let following = async {
+        tokio::select! {
+            followed_ids = following => followed_ids.map(Some),
+            () = interrupt.wait() => Ok(None),
+        }
+    };
+    let result = match options.timeout {
+        Some(timeout) => tokio::time::timeout(timeout, following)
+            .await
+            .map_err(|_| timeout),
+        None => Ok(following.await),
+    };
+    // The tee file can only be finished once all writers to it are gone.
+    drop(sink);
+    let followed_ids = match result {
+        Ok(followed_ids) => followed_ids?,
+        Err(timeout) => {
+            let timeout = format_runtime(TimeDelta::from_std(timeout).unwrap_or(TimeDelta::MAX));
+            let message = format!("Pueue: follow timed out after {timeout}");
+            stop_following(tee, Some(&message), &stats, options.stats);
+            std::process::exit(FOLLOW_TIMEOUT_EXIT_CODE);
+        }
+    };
+    let Some(followed_ids) = followed_ids else {
+        stop_following(tee, None, &stats, options.stats);
+        std::process::exit(INTERRUPT_EXIT_CODE);
+    };
+    if let Some(tee) = tee {
+        tee.finish();
+    }
+
+    if options.stats {
+        eprintln!("{}", stats.summary());
+    }
+
+    if let Some(group) = group {
+        eprintln!("Pueue: There are no running tasks left in group '{group}'.");
+    }
+
+    if !options.summary && !options.exit_code {
         return Ok(());
     }
+    let state = get_state(client).await?;
+    if options.summary {
+        for task_id in &followed_ids {
+            if let Some(task) = state.tasks.get(task_id).filter(|task| task.is_done()) {
+                eprintln!("{}", task_summary(task, style));
+            }
+        }
+    }
+
+    if options.exit_code {
+        let code = followed_ids
+            .iter()
+            .map(|task_id| state.tasks.get(task_id).map_or(1, task_exit_code))
+            .find(|code| *code != 0)
+            .unwrap_or(0);
+        if code != 0 {
+            io::stdout().flush()?;
+            std::process::exit(code);
+        }
+    }
 
-    remote_follow(client, style, task_id, lines, timestamps).await
+    Ok(())
 }
 
-/// Request the daemon to stream log files for some tasks.
+/// Print what has been followed so far, once following stopped before all tasks finished.
+/// The tasks themselves aren't affected.
+fn stop_following(tee: Option<TeeFile>, message: Option<&str>, stats: &FollowStats, show: bool) {
+    let _ = io::stdout().flush();
+    if let Some(tee) = tee {
+        tee.finish();
+    }
+    if let Some(message) = message {
+        eprintln!("{message}");
+    }
+    if show {
+        eprintln!("{}", stats.summary());
+    }
+}
+
+/// Everything that's needed to follow tasks from a single source, besides the tasks themselves.
+pub struct FollowContext<'a> {
+    pub options: &'a FollowOptions,
+    /// Prefix each line with the id of its task, as multiple tasks are followed.
+    pub prefix_ids: bool,
+    /// Where the output goes. It's shared with all other sources that are followed at the same time.
+    pub sink: FollowSink,
+    /// The spinner that's shown until the first output arrives.
+    pub spinner: Option<WaitSpinner>,
+    pub stats: &'a mut FollowStats,
+}
+
+/// The exit code of `follow --exit-code` for a followed task.
 ///
-/// This receives log output until the connection goes away or is explicitly closed by the daemon
-/// once the task finishes.
-pub async fn remote_follow(
-    client: &mut Client,
-    style: &OutputStyle,
-    task_id: Option<usize>,
-    lines: Option<usize>,
-    timestamps: bool,
-) -> Result<()> {
-    let task_ids = task_id.map(|id| vec![id]).unwrap_or_default();
+/// Tasks that failed exit with their own exit code. All other failures, such as tasks that
+/// failed to spawn, have been killed, errored or whose dependency failed, exit with `1`.
+/// The same goes for tasks that didn't finish, e.g. because they have been removed meanwhile.
+fn task_exit_code(task: &Task) -> i32 {
+    match &task.status {
+        TaskStatus::Done { result, .. } => match result {
+            TaskResult::Success => 0,
+            TaskResult::Failed(code) => *code,
+            TaskResult::FailedToSpawn(_)
+            | TaskResult::Killed
+            | TaskResult::Errored
+            | TaskResult::DependencyFailed => 1,
+        },
+        _ => 1,
+    }
+}
 
-    // Request the log stream.
-    client
-        .send_request(StreamRequest {
-            tasks: TaskSelection::TaskIds(task_ids),
-            lines,
-        })
-        .await?;
+/// The amount of output that has been followed.
+///
+/// The bytes and lines of the tasks' output are counted, without any prefixes that have been
+/// added, such as task ids or timestamps. Lines that have been filtered are counted as well.
+pub struct FollowStats {
+    bytes: u64,
+    lines: u64,
+    start: Instant,
+}
 
-    // Receive the stream until the connection is closed, breaks or another failure appears.
-    loop {
-        let response = client.receive_response().await?;
-        match response {
-            Response::Stream(response) => {
-                for (_, text) in response.logs {
-                    if timestamps {
-                        // Split text into lines and add timestamp to each line
-                        for line in text.lines() {
-                            let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                            println!("[{}] {}", timestamp, line);
-                        }
-                        // Handle the case where text doesn't end with a newline
-                        if !text.ends_with('\n') && !text.is_empty() {
-                            io::stdout().flush().unwrap();
-                        }
-                    } else {
-                        print!("{text}");
-                        io::stdout().flush().unwrap();
-                    }
-                }
-                continue;
-            }
-            Response::Close => break,
-            Response::Failure(text) => {
-                print_error(style, &text);
-                std::process::exit(1);
-            }
-            _ => error!("Received unhandled response message: {response:?}"),
+impl FollowStats {
+    fn new() -> Self {
+        Self {
+            bytes: 0,
+            lines: 0,
+            start: Instant::now(),
         }
     }
 
-    Ok(())
+    /// Count some output of a task.
+    fn record(&mut self, output: &[u8]) {
+        self.bytes += output.len() as u64;
+        self.lines += output.iter().filter(|byte| **byte == b'\n').count() as u64;
+    }
+
+    /// Add the output that has been counted by another follower.
+    fn merge(&mut self, other: FollowStats) {
+        self.bytes += other.bytes;
+        self.lines += other.lines;
+    }
+
+    /// A single line with the amount of followed output and the average throughput.
+    fn summary(&self) -> String {
+        let elapsed = self.start.elapsed();
+        let throughput = self.bytes as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+        let runtime = format_runtime(TimeDelta::from_std(elapsed).unwrap_or(TimeDelta::MAX));
+        format!(
+            "Pueue: Followed {} lines ({}) in {runtime}, {}/s on average",
+            self.lines,
+            format_bytes(self.bytes as f64),
+            format_bytes(throughput),
+        )
+    }
 }
 
-/// This function reads a log file from the filesystem and streams it to `stdout`.
-/// This is the default behavior of `pueue`'s log reading logic, which is only possible
-/// if `pueued` runs on the same environment.
+/// Format an amount of bytes with a binary unit, such as `1.5 KiB`.
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024.0 {
+        return format!("{bytes:.0} B");
+    }
+
+    let mut amount = bytes;
+    let mut unit = UNITS[0];
+    for next_unit in UNITS {
+        amount /= 1024.0;
+        unit = next_unit;
+        if amount < 1024.0 {
+            break;
+        }
+    }
+    format!("{amount:.1} {unit}")
+}
+
+/// Counts all output that's written through it.
+struct StatsWriter<'a, W> {
+    stdout: &'a mut W,
+    stats: &'a mut FollowStats,
+}
+
+impl<W: Write> Write for StatsWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.stdout.write(buf)?;
+        self.stats.record(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdout.flush()
+    }
+}
+
+/// The amount of writes that may be queued for the `--tee` file, before further output is
+/// dropped instead of blocking the output to the terminal.
+const TEE_QUEUE_SIZE: usize = 4096;
+
+/// Appends the followed output to a file via `--tee`.
 ///
-/// `pueue follow` can be called without a `task_id`, in which case we check whether there's a
-/// single running task. If that's the case, we default to it.
-/// If there are multiple tasks, the user has to specify which task they want to follow.
-pub async fn local_follow(
-    client: &mut Client,
-    settings: Settings,
-    task_id: Option<usize>,
-    lines: Option<usize>,
-    timestamps: bool,
-) -> Result<()> {
-    let task_id = match task_id {
-        Some(task_id) => task_id,
-        None => {
-            // The user didn't provide a task id.
-            // Check whether we can find a single running task to follow.
-            let state = get_state(client).await?;
-            let running_ids: Vec<_> = state
-                .tasks
-                .iter()
-                .filter_map(|(&id, t)| if t.is_running() { Some(id) } else { None })
-                .collect();
+/// The file is written by a separate thread. If that thread can't keep up, e.g. because the file
+/// is on a slow disk, output is dropped once [TEE_QUEUE_SIZE] writes are queued.
+/// The output to the terminal is thereby never blocked by the file.
+pub struct TeeFile {
+    sender: SyncSender<Vec<u8>>,
+    /// The amount of bytes that have been dropped since the file last caught up.
+    dropped: Arc<AtomicU64>,
+    thread: JoinHandle<()>,
+}
 
-            match running_ids.len() {
-                0 => {
-                    bail!("There are no running tasks.");
-                }
-                1 => running_ids[0],
-                _ => {
-                    let running_ids = running_ids
-                        .iter()
-                        .map(|id| id.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", ");
-                    bail!(
-                        "Multiple tasks are running, please select one of the following: {running_ids}",
-                    );
+impl TeeFile {
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .wrap_err_with(|| format!("Failed to open tee file {path:?}"))?;
+
+        let (sender, receiver) = sync_channel::<Vec<u8>>(TEE_QUEUE_SIZE);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let thread_dropped = dropped.clone();
+        let path = path.to_path_buf();
+        let thread = thread::spawn(move || {
+            for output in receiver {
+                let dropped = thread_dropped.swap(0, Ordering::SeqCst);
+                let result = match dropped {
+                    0 => file.write_all(&output),
+                    _ => write!(file, "\n(Pueue: {dropped} bytes of output are missing)\n")
+                        .and_then(|()| file.write_all(&output)),
+                };
+                if let Err(err) = result {
+                    eprintln!("Pueue: Failed to write to tee file {path:?}: {err}");
+                    return;
                 }
             }
-        }
-    };
+        });
 
-    follow_local_task_logs(client, settings, task_id, lines, timestamps).await?;
+        Ok(Self {
+            sender,
+            dropped,
+            thread,
+        })
+    }
 
-    Ok(())
+    /// Wait until all queued output has been written to the file.
+    pub fn finish(self) {
+        drop(self.sender);
+        let _ = self.thread.join();
+    }
 }
 
-/// Follow the log output of running task.
+/// Clears the terminal before each new burst of output, so the latest burst replaces the
+/// previous one, e.g. for tasks that redraw their progress.
 ///
-/// If no task is specified, this will check for the following cases:
-///
-/// - No running task: Wait until the task starts running.
-/// - Single running task: Follow the output of that task.
-/// - Multiple running tasks: Print out the list of possible tasks to follow.
-pub async fn follow_local_task_logs(
-    client: &mut Client,
-    settings: Settings,
-    task_id: usize,
-    lines: Option<usize>,
-    timestamps: bool,
-) -> Result<()> {
-    let pueue_directory = &settings.shared.pueue_directory();
-    // It might be that the task is not yet running.
-    // Ensure that it exists and is started.
-    loop {
-        let Some(task) = get_task(client, task_id).await? else {
-            eprintln!("Pueue: The task to be followed doesn't exist.");
-            std::process::exit(1);
-        };
-        // Task started up, we can start to follow.
-        if task.is_running() || task.is_done() {
-            break;
+/// A burst is all output that's written between two flushes. Nothing is cleared if stdout
+/// isn't a terminal. The cursor is hidden meanwhile and shown again once this is dropped,
+/// which is also the case if we're interrupted via Ctrl-C, see [Interrupt].
+pub struct ClearScreen {
+    stdout: io::Stdout,
+    enabled: bool,
+    /// Whether the terminal is cleared before the next write.
+    pending: bool,
+}
+
+impl ClearScreen {
+    fn new(stdout: io::Stdout, clear: bool) -> Self {
+        let enabled = clear && stdout.is_terminal();
+        if enabled {
+            let _ = execute!(io::stdout(), Hide);
+        }
+
+        Self {
+            stdout,
+            enabled,
+            pending: enabled,
         }
-        sleep(Duration::from_millis(1000)).await;
     }
+}
 
-    let mut handle = match get_log_file_handle(task_id, pueue_directory) {
-        Ok(stdout) => stdout,
-        Err(err) => {
-            eprintln!("Failed to get log file handles: {err}");
-            return Ok(());
+impl Write for ClearScreen {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.pending {
+            queue!(self.stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+            self.pending = false;
         }
-    };
-    let path = get_log_path(task_id, pueue_directory);
+        self.stdout.write(buf)
+    }
 
-    // Stdout handle to directly stream log file output to `io::stdout`.
-    // This prevents us from allocating any large amounts of memory.
-    let mut stdout = io::stdout();
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdout.flush()?;
+        self.pending = self.enabled;
+        Ok(())
+    }
+}
 
-    // If `lines` is passed as an option, we only want to show the last `X` lines.
-    // To achieve this, we seek the file handle to the start of the `Xth` line
-    // from the end of the file.
-    // The loop following this section will then only copy those last lines to stdout.
-    if let Some(lines) = lines {
-        if let Err(err) = seek_to_last_lines(&mut handle, lines) {
-            eprintln!("Error seeking to last lines from log: {err}");
+impl Drop for ClearScreen {
+    fn drop(&mut self) {
+        if self.enabled {
+            let _ = execute!(self.stdout, Show);
         }
     }
+}
 
-    // The interval at which the task log is checked and streamed to stdout.
-    let log_check_interval = 250;
+/// Tells when the user pressed Ctrl-C while the terminal is cleared via `--clear`.
+///
+/// The process isn't killed right away in that case. Instead, following is stopped, so the
+/// cursor is shown again by [ClearScreen] and the tee file and statistics are finished as usual.
+struct Interrupt(Option<Arc<AtomicBool>>);
 
-    // We check in regular intervals whether the task finished.
-    // This is something we don't want to do in every loop, as we have to communicate with
-    // the daemon. That's why we only do it now and then.
-    let task_check_interval = log_check_interval * 2;
-    let mut last_check = 0;
+impl Interrupt {
+    fn new(clear: bool) -> Self {
+        if !clear || !io::stdout().is_terminal() {
+            return Self(None);
+        }
+
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let handler_interrupted = interrupted.clone();
+        let result = ctrlc::set_handler(move || handler_interrupted.store(true, Ordering::SeqCst));
+        if let Err(err) = result {
+            eprintln!("Pueue: Failed to handle Ctrl-C, the cursor may stay hidden: {err}");
+            return Self(None);
+        }
 
-    // Store incomplete line buffer for timestamps mode
-    let mut incomplete_line = String::new();
+        Self(Some(interrupted))
+    }
 
-    loop {
-        // Check whether the file still exists. Exit if it doesn't.
-        if !path.exists() {
-            eprintln!("Pueue: Log file has gone away. Has the task been removed?");
-            return Ok(());
+    fn is_set(&self) -> bool {
+        self.0
+            .as_ref()
+            .is_some_and(|interrupted| interrupted.load(Ordering::SeqCst))
+    }
+
+    /// Wait until the user presses Ctrl-C. This never happens, if Ctrl-C isn't handled.
+    async fn wait(&self) {
+        if self.0.is_none() {
+            return std::future::pending().await;
         }
+        while !self.is_set() {
+            sleep(INTERRUPT_CHECK_INTERVAL).await;
+        }
+    }
+}
 
-        // Read and output the next chunk of text
-        if timestamps {
-            // Read new data into a buffer
-            let mut buffer = Vec::new();
-            if let Err(err) = io::copy(&mut handle, &mut buffer) {
-                eprintln!("Pueue: Error while reading file: {err}");
-                return Ok(());
+/// Writes all output to the terminal, and to the [TeeFile] if there's one.
+pub struct TeeWriter<W> {
+    stdout: W,
+    tee: Option<(SyncSender<Vec<u8>>, Arc<AtomicU64>)>,
+}
+
+impl<W: Write> TeeWriter<W> {
+    fn new(stdout: W, tee: Option<&TeeFile>) -> Self {
+        Self {
+            stdout,
+            tee: tee.map(|tee| (tee.sender.clone(), tee.dropped.clone())),
+        }
+    }
+}
+
+impl<W: Write> Write for TeeWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.stdout.write(buf)?;
+        if let Some((sender, dropped)) = &self.tee {
+            // If the file can't be written at all, its thread already reported the failure.
+            if let Err(TrySendError::Full(output)) = sender.try_send(buf[..written].to_vec()) {
+                if dropped.fetch_add(output.len() as u64, Ordering::SeqCst) == 0 {
+                    eprintln!("Pueue: The tee file can't keep up, some output is missing from it.");
+                }
             }
+        }
+        Ok(written)
+    }
 
-            if !buffer.is_empty() {
-                // Convert to string and combine with any incomplete line from previous iteration
-                let new_text = String::from_utf8_lossy(&buffer);
-                let full_text = format!("{}{}", incomplete_line, new_text);
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdout.flush()
+    }
+}
 
-                // Split into lines
-                let mut lines: Vec<&str> = full_text.lines().collect();
+/// The destination of all followed output, see [LogSink].
+pub type FollowSink = LogSink<TeeWriter<ClearScreen>>;
 
-                // Check if the text ends with a newline
-                let ends_with_newline = full_text.ends_with('\n');
+/// A destination for followed output that may be shared by multiple writers, e.g. one per task.
+///
+/// Output is only passed on in whole lines, which are written while holding a lock. Lines of
+/// different writers thereby never get mixed up, even if they're written concurrently.
+/// Partial lines are kept back until they're complete or the writer is flushed.
+///
+/// Each clone writes to the same destination, but keeps back its own partial line.
+pub struct LogSink<W: Write> {
+    out: Arc<Mutex<W>>,
+    /// The partial line that hasn't been passed on yet.
+    pending: Vec<u8>,
+}
 
-                // If it doesn't end with newline, the last line is incomplete
-                if !ends_with_newline && !lines.is_empty() {
-                    incomplete_line = lines.pop().unwrap().to_string();
-                } else {
-                    incomplete_line.clear();
-                }
+impl<W: Write> LogSink<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out: Arc::new(Mutex::new(out)),
+            pending: Vec::new(),
+        }
+    }
 
-                // Print complete lines with timestamps
-                for line in lines {
-                    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                    println!("[{}] {}", timestamp, line);
-                }
+    fn lock(out: &Mutex<W>) -> MutexGuard<'_, W> {
+        // A writer that panicked can't leave a partial line behind, so the output is still fine.
+        out.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
 
-                if let Err(err) = stdout.flush() {
-                    eprintln!("Pueue: Error while flushing stdout: {err}");
-                    return Ok(());
-                }
-            }
+impl<W: Write> Clone for LogSink<W> {
+    fn clone(&self) -> Self {
+        Self {
+            out: self.out.clone(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> Write for LogSink<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(end) = buf.iter().rposition(|byte| *byte == b'\n') else {
+            self.pending.extend_from_slice(buf);
+            return Ok(buf.len());
+        };
+
+        // Complete lines are passed on right away, without copying them first.
+        // A partial line is passed on in a single write together with its end, so it can't be
+        // torn apart if the [TeeFile] drops some output.
+        let (lines, rest) = buf.split_at(end + 1);
+        let mut out = Self::lock(&self.out);
+        if self.pending.is_empty() {
+            out.write_all(lines)?;
         } else {
-            // Original behavior - use io::copy
-            if let Err(err) = io::copy(&mut handle, &mut stdout) {
-                eprintln!("Pueue: Error while reading file: {err}");
-                return Ok(());
-            }
-            // Flush the stdout buffer to actually print the output.
-            if let Err(err) = stdout.flush() {
-                eprintln!("Pueue: Error while flushing stdout: {err}");
-                return Ok(());
-            }
+            self.pending.extend_from_slice(lines);
+            out.write_all(&self.pending)?;
+            self.pending.clear();
         }
+        drop(out);
+        self.pending.extend_from_slice(rest);
 
-        // Check every `task_check_interval` whether the task:
-        // 1. Still exist
-        // 2. Is still running
-        //
-        // In case either is not, exit.
-        if (last_check % task_check_interval) == 0 {
-            let Some(task) = get_task(client, task_id).await? else {
-                eprintln!("Pueue: The followed task has been removed.");
-                std::process::exit(1);
-            };
-            // Task exited by itself. We can stop following.
-            if !task.is_running() {
-                return Ok(());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut out = Self::lock(&self.out);
+        if !self.pending.is_empty() {
+            out.write_all(&self.pending)?;
+            self.pending.clear();
+        }
+        out.flush()
+    }
+}
+
+impl<W: Write> Drop for LogSink<W> {
+    fn drop(&mut self) {
+        if !self.pending.is_empty() {
+            let _ = self.flush();
+        }
+    }
+}
+
+/// Report an error while following the output, unless the reader of stdout went away,
+/// e.g. `pueue follow | head`. In that case, we silently stop following like other Unix tools.
+fn report_output_error(message: &str, err: &io::Error) {
+    if err.kind() != io::ErrorKind::BrokenPipe {
+        eprintln!("Pueue: {message}: {err}");
+    }
+}
+
+/// The exit code if following stopped because the timeout has been reached.
+/// This is the same code that's used by the `timeout` command of coreutils.
+const FOLLOW_TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// The exit code if following has been interrupted via Ctrl-C, as it's used by shells.
+const INTERRUPT_EXIT_CODE: i32 = 130;
+
+/// How often we check whether the user pressed Ctrl-C, see [Interrupt].
+const INTERRUPT_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long finished tasks are still followed with `--follow-restarts`, in case they're
+/// restarted in place.
+const RESTART_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// What `follow` does once a followed task finished.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, EnumString)]
+pub enum OnFinish {
+    /// Stop following the task.
+    #[default]
+    #[strum(serialize = "exit")]
+    Exit,
+    /// Keep following the task for the [RESTART_GRACE_PERIOD], in case it's restarted in place.
+    /// This is done by `--follow-restarts`.
+    #[strum(disabled)]
+    GracePeriod,
+    /// Keep following the task until following is stopped, e.g. by a timeout or Ctrl-C, in
+    /// case it's restarted in place.
+    #[strum(serialize = "wait")]
+    Wait,
+}
+
+impl OnFinish {
+    /// The point in time at which we stop waiting for a task that just finished to be restarted.
+    /// `None` means that we wait forever.
+    fn restart_deadline(self) -> Option<Instant> {
+        match self {
+            OnFinish::Exit => Some(Instant::now()),
+            OnFinish::GracePeriod => Some(Instant::now() + RESTART_GRACE_PERIOD),
+            OnFinish::Wait => None,
+        }
+    }
+}
+
+/// The interval at which the daemon is asked whether remotely followed tasks have been restarted.
+const RESTART_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The interval at which the daemon is asked whether a task with the followed label exists.
+const LABEL_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Wait until a task whose label matches the given pattern exists and return its id.
+///
+/// This allows to follow a task by its label, before it has been added.
+/// Fails if multiple tasks match the label, as it's unclear which one should be followed.
+pub async fn wait_for_label(client: &mut Client, label: &LabelPattern) -> Result<usize> {
+    loop {
+        let state = get_state(client).await?;
+        let task_ids: Vec<usize> = state
+            .tasks
+            .values()
+            .filter(|task| label.matches(task))
+            .map(|task| task.id)
+            .collect();
+
+        match task_ids.as_slice() {
+            [] => sleep(LABEL_CHECK_INTERVAL).await,
+            [task_id] => return Ok(*task_id),
+            _ => {
+                let task_ids = task_ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                bail!("Multiple tasks match label '{label}', please select one of: {task_ids}");
             }
         }
+    }
+}
+
+/// The interval at which the daemon is asked whether the next task of a group has started.
+const NEXT_TASK_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Wait until the next task of a group starts running and return its id.
+///
+/// Tasks that have already been started once we start waiting are ignored, which includes
+/// finished tasks. If multiple tasks start at the same time, the task with the lowest id is
+/// picked and the others are pointed out.
+pub async fn wait_for_next_task(client: &mut Client, group: &str) -> Result<usize> {
+    let state = get_state(client).await?;
+    if !state.groups.contains_key(group) {
+        bail!("Group {group} doesn't exist");
+    }
+    let started_before = started_tasks(&state.tasks, group);
+
+    loop {
+        let state = get_state(client).await?;
+        let started = started_tasks(&state.tasks, group);
+        let mut task_ids = started.difference(&started_before);
+        let Some(task_id) = task_ids.next() else {
+            sleep(NEXT_TASK_CHECK_INTERVAL).await;
+            continue;
+        };
+
+        let others: Vec<String> = task_ids.map(ToString::to_string).collect();
+        if !others.is_empty() {
+            eprintln!(
+                "Pueue: Tasks {} started at the same time, following task {task_id}.",
+                others.join(", ")
+            );
+        }
+        return Ok(*task_id);
+    }
+}
+
+/// The ids of all tasks of a group that have been started, including finished tasks.
+fn started_tasks(tasks: &BTreeMap<usize, Task>, group: &str) -> BTreeSet<usize> {
+    tasks
+        .values()
+        .filter(|task| task.group == group && (task.is_running() || task.is_done()))
+        .map(|task| task.id)
+        .collect()
+}
+
+/// Where the output of followed tasks is read from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogSource {
+    /// Read the log files from the local pueue directory.
+    Local,
+    /// Let the daemon stream the output.
+    Remote,
+}
+
+/// The tasks that are followed locally and the tasks that are streamed by the daemon.
+enum FollowSources {
+    Local(TaskSelection),
+    Remote(TaskSelection),
+    Both {
+        local: Vec<usize>,
+        remote: Vec<usize>,
+    },
+}
+
+impl FollowSources {
+    /// Decide where the output of the selected tasks is read from.
+    ///
+    /// Unless a `source` is forced or `client.read_local_logs` is disabled, this is decided per
+    /// task: Tasks whose log file exists locally are followed locally, while all other tasks are
+    /// streamed by the daemon, e.g. if the daemon runs on another machine.
+    /// Tasks that haven't been started yet don't have a log file. Just like groups, they're
+    /// followed locally if the task log directory exists locally.
+    async fn new(
+        client: &mut Client,
+        settings: &Settings,
+        selection: TaskSelection,
+        source: Option<LogSource>,
+    ) -> Result<Self> {
+        match source {
+            Some(LogSource::Local) => return Ok(Self::Local(selection)),
+            Some(LogSource::Remote) => return Ok(Self::Remote(selection)),
+            None if !settings.client.read_local_logs => return Ok(Self::Remote(selection)),
+            None => (),
+        }
+
+        let pueue_directory = settings.shared.pueue_directory();
+        let local_directory = pueue_directory.join("task_logs").exists();
+        let task_ids = match selection {
+            TaskSelection::TaskIds(task_ids) if !task_ids.is_empty() => task_ids,
+            TaskSelection::Group(_) if local_directory => return Ok(Self::Local(selection)),
+            TaskSelection::Group(_) => return Ok(Self::Remote(selection)),
+            _ => find_single_running_task(client).await?,
+        };
+
+        let (mut local, missing): (Vec<usize>, Vec<usize>) = task_ids
+            .into_iter()
+            .partition(|task_id| log_file_exists(*task_id, &pueue_directory));
+        let remote = if !missing.is_empty() && local_directory {
+            // Unknown tasks are reported when following them locally.
+            let state = get_state(client).await?;
+            let (unstarted, started): (Vec<usize>, Vec<usize>) =
+                missing.into_iter().partition(|task_id| {
+                    state
+                        .tasks
+                        .get(task_id)
+                        .is_none_or(|task| !task.is_running() && !task.is_done())
+                });
+            local.extend(unstarted);
+            local.sort_unstable();
+            started
+        } else {
+            missing
+        };
+
+        Ok(match (local.is_empty(), remote.is_empty()) {
+            (false, false) => Self::Both { local, remote },
+            (true, false) => Self::Remote(TaskSelection::TaskIds(remote)),
+            _ => Self::Local(TaskSelection::TaskIds(local)),
+        })
+    }
+}
+
+/// A single line describing the result of a finished task and how long it ran.
+fn task_summary(task: &Task, style: &OutputStyle) -> String {
+    let task_id = style.style_text(format!("Task {}:", task.id), None, Some(Attribute::Bold));
+    let (status, color) = task_status(task);
+    let status = style.style_text(status, Some(color), None);
+
+    match task.start_and_end() {
+        (Some(start), Some(end)) => {
+            let runtime = format_runtime(end - start);
+            format!("{task_id} {status} (took {runtime})")
+        }
+        _ => format!("{task_id} {status}"),
+    }
+}
+
+/// The difference between the daemon's and the client's clock, if it's larger than
+/// `threshold_secs`. Positive values mean that the daemon's clock is ahead.
+fn clock_skew(
+    daemon_time: DateTime<Local>,
+    client_time: DateTime<Local>,
+    threshold_secs: u64,
+) -> Option<TimeDelta> {
+    let skew = daemon_time - client_time;
+    let threshold = i64::try_from(threshold_secs)
+        .ok()
+        .and_then(TimeDelta::try_seconds)
+        .unwrap_or(TimeDelta::MAX);
+    (skew.abs() > threshold).then_some(skew)
+}
+
+fn print_clock_skew_warning(skew: TimeDelta) {
+    let direction = if skew > TimeDelta::zero() {
+        "ahead of"
+    } else {
+        "behind"
+    };
+    eprintln!(
+        "Pueue: The daemon's clock is {} {direction} this machine's clock. \
+        The timestamps of lines may be off. \
+        This check can be configured via `client.clock_skew_warning_secs`.",
+        format_runtime(skew.abs())
+    );
+}
+
+/// Request the daemon to stream log files for some tasks.
+///
+/// This receives log output until the connection goes away or is explicitly closed by the daemon
+/// once all tasks finished.
+///
+/// If `retry` is set and the connection goes away or is closed while tasks are still running,
+/// we reconnect and continue following where we left off.
+///
+/// Returns the ids of all tasks that have been followed.
+pub async fn remote_follow(
+    client: &mut Client,
+    settings: &Settings,
+    style: &OutputStyle,
+    selection: TaskSelection,
+    context: FollowContext<'_>,
+) -> Result<BTreeSet<usize>> {
+    let FollowContext {
+        options,
+        prefix_ids,
+        sink: mut stdout,
+        mut spinner,
+        stats,
+    } = context;
+    let &FollowOptions {
+        lines,
+        max_bytes,
+        ref timestamps,
+        stream,
+        interleaved,
+        retry,
+        on_finish,
+        header,
+        ..
+    } = options;
+    // The headers of tasks that have already been started are printed right away, as they might
+    // not have written any output yet. All other tasks get their header with their first output.
+    let mut headers = BTreeSet::new();
+    if header {
+        let state = get_state(client).await?;
+        for task in state.tasks.values() {
+            let followed = match &selection {
+                TaskSelection::TaskIds(task_ids) => {
+                    task_ids.contains(&task.id) && (task.is_running() || task.is_done())
+                }
+                TaskSelection::Group(group) => task.group == *group && task.is_running(),
+                TaskSelection::All => false,
+            };
+            if followed {
+                headers.insert(task.id);
+                print_follow_header(task, style, settings, &mut spinner);
+            }
+        }
+    }
+
+    // Request the log stream.
+    // The request is kept around, so we can resume following after a reconnect.
+    let mut request = StreamRequest {
+        tasks: selection,
+        lines,
+        offsets: BTreeMap::new(),
+        stream,
+        max_bytes,
+    };
+    client.send_request(request.clone()).await?;
+
+    // The output of each task is handled separately, as lines of different tasks may be
+    // interleaved in a single response.
+    let mut outputs: BTreeMap<usize, TaskOutput> = BTreeMap::new();
+    // Timestamps are taken by the client, so they're off if the daemon's clock is skewed.
+    let mut check_clock = timestamps.is_some() && settings.client.clock_skew_warning_secs > 0;
+
+    // Receive the stream until the connection is closed, breaks or another failure appears.
+    loop {
+        let response = match client.receive_response().await {
+            Ok(response) => response,
+            Err(err) if retry => {
+                eprintln!("Pueue: Lost connection to the daemon: {err}");
+                resume_remote_follow(client, settings, &mut request, false).await?;
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+        match response {
+            Response::Stream(response) => {
+                // Remove the spinner before printing the first output.
+                spinner.take();
+                // Older daemons don't send their time.
+                if let (true, Some(daemon_time)) = (check_clock, response.daemon_time) {
+                    check_clock = false;
+                    let threshold = settings.client.clock_skew_warning_secs;
+                    if let Some(skew) = clock_skew(daemon_time, Local::now(), threshold) {
+                        print_clock_skew_warning(skew);
+                    }
+                }
+                for (task_id, text) in response.logs {
+                    // The first output of each task contains everything that has been written
+                    // before. The daemon doesn't know about the byte limit, so it's applied here.
+                    // Older daemons ignore the byte limit and send the whole output.
+                    let mut start = 0;
+                    if let (Some(max_bytes), false) = (max_bytes, outputs.contains_key(&task_id)) {
+                        start = last_bytes_start(text.as_bytes(), max_bytes);
+                        while !text.is_char_boundary(start) {
+                            start += 1;
+                        }
+                    }
+                    // Older daemons don't tell where the output starts.
+                    if let (Some(file_start), Some(previous_end)) =
+                        (response.starts.get(&task_id), request.offsets.get(&task_id))
+                    {
+                        start =
+                            start.max(already_printed(task_id, *file_start, *previous_end, &text));
+                    }
+
+                    let output = outputs.entry(task_id).or_insert_with(|| {
+                        let line_timestamps = timestamps.as_ref().map(LineTimestamps::live);
+                        TaskOutput::new(
+                            task_id,
+                            options,
+                            prefix_ids,
+                            line_timestamps,
+                            interleaved.then(LineStreams::remote),
+                            settings.client.follow_throttle,
+                            0,
+                        )
+                    });
+                    if header && headers.insert(task_id) {
+                        if let Some(task) = fetch_streamed_task(settings, task_id).await {
+                            print_follow_header(&task, style, settings, &mut spinner);
+                        }
+                    }
+                    output.write(&mut stdout, &text.as_bytes()[start..], stats)?;
+
+                    // Remember how far we got, in case we have to resume following.
+                    if let Some(offset) = response.offsets.get(&task_id) {
+                        request.offsets.insert(task_id, *offset);
+                    }
+                }
+                stdout.flush()?;
+                for task_id in response.paused {
+                    print_pause_notice(task_id);
+                }
+                continue;
+            }
+            Response::Close => {
+                // The stream might have been closed while tasks are still running,
+                // e.g. if the daemon is shutting down.
+                if retry && resume_remote_follow(client, settings, &mut request, true).await? {
+                    continue;
+                }
+                if on_finish != OnFinish::Exit
+                    && resume_restarted_tasks(
+                        client,
+                        &mut request,
+                        &mut outputs,
+                        &mut stdout,
+                        on_finish,
+                    )
+                    .await?
+                {
+                    continue;
+                }
+                break;
+            }
+            Response::Failure(text) => {
+                print_error(style, &text);
+                std::process::exit(1);
+            }
+            _ => error!("Received unhandled response message: {response:?}"),
+        }
+    }
+
+    // Print any remaining lines that didn't end with a newline.
+    for output in outputs.values_mut() {
+        output.finish(&mut stdout)?;
+    }
+    stdout.flush()?;
+
+    // Tasks that didn't produce any output have still been followed, if they were requested.
+    let mut followed_ids: BTreeSet<usize> = outputs.into_keys().collect();
+    if let TaskSelection::TaskIds(task_ids) = request.tasks {
+        followed_ids.extend(task_ids);
+    }
+
+    Ok(followed_ids)
+}
+
+/// Look up a task whose output is streamed by the daemon, e.g. to print its header.
+///
+/// The streaming connection is busy, which is why a separate connection is used.
+/// Returns `None` if the task can't be looked up, as the stream itself is still fine.
+async fn fetch_streamed_task(settings: &Settings, task_id: usize) -> Option<Task> {
+    let connection_settings = ConnectionSettings::try_from(settings.shared.clone()).ok()?;
+    let secret = read_shared_secret(&settings.shared.shared_secret_path()).ok()?;
+    let mut client = Client::new(connection_settings, &secret, false)
+        .await
+        .ok()?;
+    let mut state = get_state(&mut client).await.ok()?;
+
+    state.tasks.remove(&task_id)
+}
+
+/// Reconnect to the daemon and request the log stream again.
+/// Tasks are followed from where we left off.
+///
+/// If `check_running` is set, the stream is only requested if some of the followed tasks are
+/// still running. Returns whether the stream has been requested.
+async fn resume_remote_follow(
+    client: &mut Client,
+    settings: &Settings,
+    request: &mut StreamRequest,
+    check_running: bool,
+) -> Result<bool> {
+    *client = reconnect(settings).await?;
+
+    // Stick to the task that has been picked by the daemon, if none has been specified.
+    if let TaskSelection::TaskIds(task_ids) = &mut request.tasks {
+        if task_ids.is_empty() {
+            task_ids.extend(request.offsets.keys());
+        }
+    }
+
+    if check_running {
+        let state = get_state(client).await?;
+        let running = match &request.tasks {
+            TaskSelection::Group(group) => state
+                .tasks
+                .values()
+                .any(|task| task.group == *group && task.is_running()),
+            TaskSelection::TaskIds(task_ids) => task_ids
+                .iter()
+                .any(|id| state.tasks.get(id).is_some_and(|task| !task.is_done())),
+            TaskSelection::All => false,
+        };
+        if !running {
+            return Ok(false);
+        }
+    }
+
+    client.send_request(request.clone()).await?;
+
+    Ok(true)
+}
+
+/// Wait whether any of the followed tasks is restarted in place before the deadline of the
+/// `on_finish` policy, once the daemon closed the stream.
+///
+/// If that's the case, the stream of the restarted tasks is requested again, starting at the
+/// beginning of their new output. Returns whether the stream has been requested.
+async fn resume_restarted_tasks(
+    client: &mut Client,
+    request: &mut StreamRequest,
+    outputs: &mut BTreeMap<usize, TaskOutput>,
+    stdout: &mut impl Write,
+    on_finish: OnFinish,
+) -> Result<bool> {
+    let mut task_ids: BTreeSet<usize> = outputs.keys().copied().collect();
+    if let TaskSelection::TaskIds(selected_ids) = &request.tasks {
+        task_ids.extend(selected_ids);
+    }
+
+    // Tasks are restarted, if they're no longer done or have been started again in between.
+    let state = get_state(client).await?;
+    let starts: BTreeMap<usize, Option<DateTime<Local>>> = task_ids
+        .iter()
+        .filter_map(|id| state.tasks.get(id))
+        .map(|task| (task.id, task.start_and_end().0))
+        .collect();
+
+    let deadline = on_finish.restart_deadline();
+    while deadline.is_none_or(|deadline| Instant::now() < deadline) {
+        sleep(RESTART_CHECK_INTERVAL).await;
+        let state = get_state(client).await?;
+        let restarted_ids: Vec<usize> = starts
+            .iter()
+            .filter(|(id, start)| {
+                state
+                    .tasks
+                    .get(id)
+                    .is_some_and(|task| !task.is_done() || task.start_and_end().0 != **start)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        if restarted_ids.is_empty() {
+            continue;
+        }
+
+        for task_id in &restarted_ids {
+            // Any incomplete line still belongs to the previous run.
+            if let Some(output) = outputs.get_mut(task_id) {
+                output.finish(stdout)?;
+            }
+            stdout.flush()?;
+            print_restart_notice(*task_id);
+            request.offsets.insert(*task_id, 0);
+        }
+        // The daemon waits until the restarted tasks are running.
+        request.tasks = TaskSelection::TaskIds(restarted_ids);
+        client.send_request(request.clone()).await?;
+
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+/// The maximum time that's waited between two attempts to reconnect to the daemon.
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Establish a new connection to the daemon.
+///
+/// Failed attempts are retried with an exponential backoff until the daemon can be reached,
+/// e.g. once it has been restarted.
+async fn reconnect(settings: &Settings) -> Result<Client> {
+    let mut backoff = Duration::from_secs(1);
+    loop {
+        let client = async {
+            let connection_settings = ConnectionSettings::try_from(settings.shared.clone())?;
+            let secret = read_shared_secret(&settings.shared.shared_secret_path())?;
+            Client::new(connection_settings, &secret, false).await
+        };
+
+        match client.await {
+            Ok(client) => return Ok(client),
+            Err(err) => {
+                eprintln!(
+                    "Pueue: Failed to reconnect to the daemon, retrying in {}s: {err}",
+                    backoff.as_secs()
+                );
+                sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+            }
+        }
+    }
+}
+
+/// This function reads a log file from the filesystem and streams it to `stdout`.
+/// This is the default behavior of `pueue`'s log reading logic, which is only possible
+/// if `pueued` runs on the same environment.
+///
+/// `pueue follow` can be called without a `task_id`, in which case we check whether there's a
+/// single running task. If that's the case, we default to it.
+/// If there are multiple tasks, the user has to specify which task they want to follow.
+///
+/// If `retry` is set, we reconnect to the daemon if the connection is lost and wait for log files
+/// that temporarily disappear.
+///
+/// Returns the ids of all tasks that have been followed.
+pub async fn local_follow(
+    client: &mut Client,
+    settings: Settings,
+    style: &OutputStyle,
+    selection: TaskSelection,
+    context: FollowContext<'_>,
+) -> Result<BTreeSet<usize>> {
+    let (task_ids, group) = match selection {
+        TaskSelection::TaskIds(task_ids) if !task_ids.is_empty() => (task_ids, None),
+        TaskSelection::Group(group) => (Vec::new(), Some(group)),
+        _ => (find_single_running_task(client).await?, None),
+    };
+
+    follow_local_task_logs(client, settings, style, task_ids, group, context).await
+}
+
+/// The user didn't provide a task id.
+/// Check whether we can find a single running task to follow.
+async fn find_single_running_task(client: &mut Client) -> Result<Vec<usize>> {
+    let state = get_state(client).await?;
+    let running_ids: Vec<_> = state
+        .tasks
+        .iter()
+        .filter_map(|(&id, t)| if t.is_running() { Some(id) } else { None })
+        .collect();
+
+    match running_ids.len() {
+        0 => {
+            bail!("There are no running tasks.");
+        }
+        1 => Ok(vec![running_ids[0]]),
+        _ => {
+            let running_ids = running_ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            bail!("Multiple tasks are running, please select one of the following: {running_ids}",);
+        }
+    }
+}
+
+/// Follow the local log files of tasks without contacting the daemon, e.g. while it's down.
+///
+/// As the daemon isn't asked about the tasks, we can't tell whether they're running or finished.
+/// The log files are thereby followed until they disappear or the user interrupts us.
+pub async fn follow_offline(
+    settings: &Settings,
+    style: &OutputStyle,
+    task_ids: Vec<usize>,
+    options: &FollowOptions,
+) -> Result<()> {
+    let pueue_directory = &settings.shared.pueue_directory();
+    let prefix_ids = !options.no_prefix && task_ids.len() > 1;
+    let &FollowOptions {
+        lines,
+        ref timestamps,
+        stream,
+        interleaved,
+        clear,
+        ..
+    } = options;
+
+    let mut followed_logs = BTreeMap::new();
+    for task_id in task_ids {
+        let (mut handle, found_stream) =
+            get_stream_log_file_handle(task_id, stream, pueue_directory)
+                .map_err(|err| eyre!(log_file_error(task_id, &err)))?;
+        let path = match (stream, found_stream) {
+            (_, Some(found_stream)) => get_stream_log_path(task_id, found_stream, pueue_directory),
+            (Some(stream), None) => {
+                warn_missing_stream(task_id, stream);
+                get_log_path(task_id, pueue_directory)
+            }
+            (None, None) => get_log_path(task_id, pueue_directory),
+        };
+
+        if let Some(lines) = lines {
+            if let Err(err) = seek_to_last_lines(&mut handle, lines) {
+                eprintln!("Error seeking to last lines from log: {err}");
+            }
+        }
+
+        let line_timestamps = timestamps
+            .as_ref()
+            .map(|options| LineTimestamps::local(task_id, settings, options));
+        let line_streams = interleaved.then(|| LineStreams::local(task_id, settings, style));
+        let output = TaskOutput::new(
+            task_id,
+            options,
+            prefix_ids,
+            line_timestamps,
+            line_streams,
+            settings.client.follow_throttle,
+            handle.stream_position().unwrap_or_default(),
+        );
+        followed_logs.insert(task_id, (path, handle, output));
+    }
+
+    let tee = options.tee.as_deref().map(TeeFile::open).transpose()?;
+    let interrupt = Interrupt::new(clear);
+    let mut stdout = LogSink::new(TeeWriter::new(
+        ClearScreen::new(io::stdout(), clear),
+        tee.as_ref(),
+    ));
+    let mut stats = FollowStats::new();
+    let interval = Duration::from_millis(settings.client.follow_interval_ms.max(1));
+    loop {
+        let mut gone_ids = Vec::new();
+        for (task_id, (path, handle, output)) in followed_logs.iter_mut() {
+            // The log file shrank, i.e. it has been truncated or rotated.
+            let position = handle.stream_position().unwrap_or_default();
+            let length = handle
+                .metadata()
+                .map_or(position, |metadata| metadata.len());
+            if length < position {
+                output.rotate(handle, &mut stdout)?;
+            }
+            output.copy(handle, &mut stdout, &mut stats)?;
+
+            // Everything that has been written before the file went away has been read.
+            if !path.exists() {
+                output.finish(&mut stdout)?;
+                eprintln!("Pueue: The log file of task {task_id} has gone away.");
+                gone_ids.push(*task_id);
+            }
+        }
+        stdout.flush()?;
+        followed_logs.retain(|task_id, _| !gone_ids.contains(task_id));
+        let interrupted = interrupt.is_set();
+        if followed_logs.is_empty() || interrupted {
+            // Restore the terminal, before we're done.
+            drop(stdout);
+            if let Some(tee) = tee {
+                tee.finish();
+            }
+            if interrupted {
+                std::process::exit(INTERRUPT_EXIT_CODE);
+            }
+            return Ok(());
+        }
+
+        sleep(interval).await;
+    }
+}
+
+/// Follow the log output of one or more tasks.
+///
+/// Tasks that aren't running yet are waited for. Tasks that finish or are removed are
+/// dropped, while the remaining tasks are followed until they finish as well.
+///
+/// If a `group` is given, tasks of that group that start running are followed as well.
+///
+/// If a `stream` is given, the separate log file of that stream is followed, if there's one.
+///
+/// If `retry` is set, log files that disappear are re-opened once they're back, as long as their
+/// task is still running.
+///
+/// Returns the ids of all tasks that have been followed.
+pub async fn follow_local_task_logs(
+    client: &mut Client,
+    settings: Settings,
+    style: &OutputStyle,
+    task_ids: Vec<usize>,
+    group: Option<String>,
+    context: FollowContext<'_>,
+) -> Result<BTreeSet<usize>> {
+    let FollowContext {
+        options,
+        prefix_ids,
+        sink: mut stdout,
+        mut spinner,
+        stats,
+    } = context;
+    let &FollowOptions {
+        lines,
+        max_bytes,
+        ref timestamps,
+        stream,
+        interleaved,
+        retry,
+        on_finish,
+        header,
+        ..
+    } = options;
+    let pueue_directory = &settings.shared.pueue_directory();
+    // Errors of a single followed task are fatal, as there's nothing else to follow.
+    let single_task = group.is_none() && task_ids.len() == 1;
+
+    // Ensure that all tasks and the group exist.
+    let state = get_state(client).await?;
+    if task_ids.iter().any(|id| !state.tasks.contains_key(id)) {
+        eprintln!("Pueue: The task to be followed doesn't exist.");
+        std::process::exit(1);
+    }
+    if let Some(group) = &group {
+        if !state.groups.contains_key(group) {
+            let group_keys: Vec<&String> = state.groups.keys().collect();
+            bail!("Group {group} doesn't exist. Use one of these: {group_keys:?}");
+        }
+    }
+
+    let mut followed_logs: BTreeMap<usize, FollowedLog> = task_ids
+        .into_iter()
+        .map(|task_id| (task_id, FollowedLog::new(task_id, pueue_directory)))
+        .collect();
+    let mut followed_ids: BTreeSet<usize> = followed_logs.keys().copied().collect();
+    let mut task_removed = false;
+
+    // Tasks of the group that finished before we started following are ignored.
+    let mut seen_tasks: BTreeSet<usize> = match &group {
+        Some(group) => state
+            .tasks
+            .iter()
+            .filter(|(_, task)| task.group == *group && task.is_done())
+            .map(|(task_id, _)| *task_id)
+            .collect(),
+        None => BTreeSet::new(),
+    };
+
+    // The interval at which the task logs are checked and streamed to stdout.
+    let log_check_interval = settings.client.follow_interval_ms.max(1);
+
+    // We check in regular intervals whether the tasks started or finished.
+    // This is something we don't want to do in every loop, as we have to communicate with
+    // the daemon. That's why we only do it roughly every half second, but at least once per
+    // log check if the log interval is longer than that.
+    let task_check_interval =
+        Duration::from_millis(log_check_interval * (500 / log_check_interval).max(1));
+    let mut last_task_check: Option<Instant> = None;
+
+    // If possible, the log files are only read once they changed, instead of polling them.
+    // The tasks are still checked on a timer, which also reads the log files as a fallback,
+    // e.g. for network mounts that don't report changes.
+    #[cfg(feature = "inotify-follow")]
+    let mut watcher = match super::log::LogWatcher::new(pueue_directory) {
+        Ok(watcher) => Some(watcher),
+        Err(err) => {
+            debug!("Falling back to polling the log files: {err:?}");
+            None
+        }
+    };
+
+    loop {
+        // Check every `task_check_interval` whether the tasks:
+        // 1. Still exist
+        // 2. Have been started
+        // 3. Are still running
+        //
+        // Tasks that finished are read one last time and then dropped.
+        if last_task_check.is_none_or(|time| time.elapsed() >= task_check_interval) {
+            last_task_check = Some(Instant::now());
+            let state = match get_state(client).await {
+                Ok(state) => state,
+                Err(err) if retry => {
+                    eprintln!("Pueue: Lost connection to the daemon: {err}");
+                    *client = reconnect(&settings).await?;
+                    get_state(client).await?
+                }
+                Err(err) => return Err(err),
+            };
+
+            // Pick up all tasks of the group that started in the meantime.
+            // Tasks are also picked up if they already finished in between two checks.
+            if let Some(group) = &group {
+                for (task_id, task) in state.tasks.iter() {
+                    let started = task.is_running() || task.is_done();
+                    if task.group == *group && started && seen_tasks.insert(*task_id) {
+                        followed_logs.insert(*task_id, FollowedLog::new(*task_id, pueue_directory));
+                        followed_ids.insert(*task_id);
+                    }
+                }
+            }
+
+            for (task_id, log) in followed_logs.iter_mut() {
+                let Some(task) = state.tasks.get(task_id) else {
+                    if single_task {
+                        eprintln!("Pueue: The followed task has been removed.");
+                        std::process::exit(1);
+                    }
+                    eprintln!("Pueue: The followed task {task_id} has been removed.");
+                    log.finished = true;
+                    task_removed = true;
+                    continue;
+                };
+
+                // The task has been restarted in place, the new output is followed from the start.
+                let started = task.is_running() || task.is_done();
+                let start = task.start_and_end().0;
+                if on_finish != OnFinish::Exit
+                    && log.reader.is_some()
+                    && started
+                    && start != log.start
+                {
+                    if let Some((_, output)) = log.reader.as_mut() {
+                        // Any incomplete line still belongs to the previous run.
+                        if let Err(err) = output.finish(&mut stdout).and_then(|()| stdout.flush()) {
+                            report_output_error("Error while writing to stdout", &err);
+                            return Ok(followed_ids);
+                        }
+                    }
+                    print_restart_notice(*task_id);
+                    log.reader = None;
+                    log.restarted = true;
+                    log.restart_deadline = None;
+                }
+
+                // The task started up, we can start to follow.
+                if log.reader.is_none() && started {
+                    let mut handle =
+                        match get_stream_log_file_handle(*task_id, stream, pueue_directory) {
+                            Ok((handle, found_stream)) => {
+                                match (stream, found_stream) {
+                                    (Some(stream), None) => warn_missing_stream(*task_id, stream),
+                                    (_, Some(found_stream)) => {
+                                        log.path = get_stream_log_path(
+                                            *task_id,
+                                            found_stream,
+                                            pueue_directory,
+                                        )
+                                    }
+                                    _ => (),
+                                }
+                                log.stream = found_stream;
+                                handle
+                            }
+                            Err(err) => {
+                                eprintln!("{}", log_file_error(*task_id, &err));
+                                if single_task {
+                                    return Ok(followed_ids);
+                                }
+                                log.finished = true;
+                                continue;
+                            }
+                        };
+
+                    // If `lines` is passed as an option, we only want to show the last `X` lines.
+                    // To achieve this, we seek the file handle to the start of the `Xth` line
+                    // from the end of the file.
+                    // The loop following this section will then only copy those last lines to
+                    // stdout. With `0` lines, only output that's written from now on is shown.
+                    // The output of restarted tasks is shown in full.
+                    if let Some(lines) = lines.filter(|_| !log.restarted) {
+                        if let Err(err) = seek_to_last_lines(&mut handle, lines) {
+                            eprintln!("Error seeking to last lines from log: {err}");
+                        }
+                    }
+
+                    // The output is further capped to roughly `max_bytes`, if requested.
+                    // If both limits are given, the smaller amount of output is shown.
+                    if let Some(max_bytes) = max_bytes.filter(|_| !log.restarted) {
+                        let lines_start = handle.stream_position().unwrap_or_default();
+                        if let Err(err) = seek_to_last_bytes(&mut handle, max_bytes) {
+                            eprintln!("Error seeking to last bytes from log: {err}");
+                        }
+                        if handle.stream_position().unwrap_or_default() < lines_start {
+                            if let Err(err) = handle.seek(SeekFrom::Start(lines_start)) {
+                                eprintln!("Error seeking to last lines from log: {err}");
+                            }
+                        }
+                    }
+
+                    // The timestamps are looked up by the byte offset at which a line starts.
+                    let line_timestamps = timestamps
+                        .as_ref()
+                        .map(|options| LineTimestamps::local(*task_id, &settings, options));
+                    let line_streams =
+                        interleaved.then(|| LineStreams::local(*task_id, &settings, style));
+                    let offset = handle.stream_position().unwrap_or_default();
+                    let output = TaskOutput::new(
+                        *task_id,
+                        options,
+                        prefix_ids,
+                        line_timestamps,
+                        line_streams,
+                        settings.client.follow_throttle,
+                        offset,
+                    );
+                    log.reader = Some((handle, output));
+                    log.start = start;
+
+                    // Restarted tasks are announced by a notice instead.
+                    if header && !log.restarted {
+                        print_follow_header(task, style, &settings, &mut spinner);
+                    }
+                }
+
+                // The task exited by itself. We can stop following after the last read.
+                // When following restarts, the task may be restarted before the deadline.
+                if log.reader.is_some() && !task.is_running() {
+                    if on_finish == OnFinish::Exit {
+                        log.finished = true;
+                    } else {
+                        if log.restart_deadline.is_none() {
+                            log.restart_deadline = on_finish.restart_deadline();
+                        }
+                        // Restarted tasks might have to wait in the queue, until they run again.
+                        log.finished = task.is_done()
+                            && log
+                                .restart_deadline
+                                .is_some_and(|deadline| Instant::now() >= deadline);
+                    }
+                }
+
+                // Paused tasks are still followed, the user is told once after the next read.
+                log.pause_notice |= task.is_paused() && !log.paused;
+                log.paused = task.is_paused();
+            }
+        }
+
+        // Read and output the next chunk of text of each task.
+        for (task_id, log) in followed_logs.iter_mut() {
+            let Some((handle, output)) = log.reader.as_mut() else {
+                continue;
+            };
+
+            // Check whether the file still exists. Stop following it if it doesn't.
+            // When retrying, we wait for it to come back, as long as the task is running.
+            // The daemon may have compressed the log file of a finished task in the meantime.
+            let exists = log.path.exists()
+                || (log.stream.is_none()
+                    && get_compressed_log_path(*task_id, pueue_directory).exists());
+            if !exists && retry && !log.finished {
+                log.missing = true;
+                continue;
+            }
+            if !exists {
+                eprintln!("Pueue: Log file has gone away. Has the task been removed?");
+                if single_task {
+                    return Ok(followed_ids);
+                }
+                log.finished = true;
+                continue;
+            }
+
+            // The file is back, re-open it and continue where we left off.
+            if log.missing {
+                log.missing = false;
+                let offset = handle.stream_position().unwrap_or_default();
+                match get_stream_log_file_handle(*task_id, log.stream, pueue_directory) {
+                    Ok((new_handle, _)) => *handle = new_handle,
+                    Err(err) => {
+                        eprintln!("{}", log_file_error(*task_id, &err));
+                        continue;
+                    }
+                }
+                if let Err(err) = handle.seek(SeekFrom::Start(offset)) {
+                    eprintln!("Error seeking to previous log offset: {err}");
+                }
+            }
+
+            // The log file shrank, i.e. it has been truncated or rotated.
+            // This happens, for instance, if the task has been restarted.
+            // Continue from the start of the new content.
+            let position = handle.stream_position().unwrap_or_default();
+            let length = handle
+                .metadata()
+                .map_or(position, |metadata| metadata.len());
+            if length < position {
+                if let Err(err) = output.rotate(handle, &mut stdout) {
+                    report_output_error("Error while reading file", &err);
+                    return Ok(followed_ids);
+                }
+            } else if length > position {
+                // Remove the spinner before printing the first output.
+                spinner.take();
+            }
+
+            if let Err(err) = output.copy(handle, &mut stdout, stats) {
+                report_output_error("Error while reading file", &err);
+                return Ok(followed_ids);
+            }
+            if log.finished {
+                if let Err(err) = output.finish(&mut stdout) {
+                    report_output_error("Error while writing to stdout", &err);
+                    return Ok(followed_ids);
+                }
+            }
+            if std::mem::take(&mut log.pause_notice) {
+                // The output that has been written before the task got paused is shown first.
+                if let Err(err) = stdout.flush() {
+                    report_output_error("Error while flushing stdout", &err);
+                    return Ok(followed_ids);
+                }
+                spinner.take();
+                print_pause_notice(*task_id);
+            }
+        }
+
+        // Flush the stdout buffer to actually print the output.
+        if let Err(err) = stdout.flush() {
+            report_output_error("Error while flushing stdout", &err);
+            return Ok(followed_ids);
+        }
+
+        followed_logs.retain(|_, log| !log.finished);
+        if followed_logs.is_empty() {
+            if task_removed {
+                std::process::exit(1);
+            }
+            return Ok(followed_ids);
+        }
+
+        #[cfg(feature = "inotify-follow")]
+        if let Some(watcher) = watcher.as_mut() {
+            let elapsed = last_task_check
+                .map(|time| time.elapsed())
+                .unwrap_or_default();
+            watcher
+                .wait(task_check_interval.saturating_sub(elapsed))
+                .await;
+            continue;
+        }
+
+        sleep(Duration::from_millis(log_check_interval)).await;
+    }
+}
+
+/// The local log file of a task that's being followed.
+struct FollowedLog {
+    /// The log file is only opened once the task has been started.
+    reader: Option<(File, TaskOutput)>,
+    /// We need to check continuously, whether the file still exists.
+    path: PathBuf,
+    /// The task finished or went away. Its output has been read for the last time.
+    finished: bool,
+    /// The log file disappeared and needs to be re-opened once it's back.
+    missing: bool,
+    /// The task is currently paused.
+    paused: bool,
+    /// The task has just been paused, which the user is told about.
+    pause_notice: bool,
+    /// The stream whose separate log file is followed, if any.
+    stream: Option<OutputStream>,
+    /// The time at which the followed run of the task started.
+    start: Option<DateTime<Local>>,
+    /// The task has been restarted in place, thereby its new output is followed from the start.
+    restarted: bool,
+    /// The point in time at which we stop waiting for the finished task to be restarted.
+    restart_deadline: Option<Instant>,
+}
+
+impl FollowedLog {
+    fn new(task_id: usize, pueue_directory: &Path) -> Self {
+        Self {
+            reader: None,
+            path: get_log_path(task_id, pueue_directory),
+            finished: false,
+            missing: false,
+            paused: false,
+            pause_notice: false,
+            stream: None,
+            start: None,
+            restarted: false,
+            restart_deadline: None,
+        }
+    }
+}
+
+/// Print the information of a followed task on stderr, before its output is streamed.
+///
+/// This is the same information that `pueue log` shows on top of the output.
+/// A running spinner is printed below the header again.
+fn print_follow_header(
+    task: &Task,
+    style: &OutputStyle,
+    settings: &Settings,
+    spinner: &mut Option<WaitSpinner>,
+) {
+    let waiting = spinner.take().is_some();
+    eprintln!("{}", task_info(task, style, settings));
+    if waiting {
+        *spinner = Some(WaitSpinner::start(style));
+    }
+}
+
+/// Tell the user that a followed task has been paused.
+/// It's still followed and its output continues once it's resumed.
+fn print_pause_notice(task_id: usize) {
+    eprintln!("Pueue: (task {task_id} is paused)");
+}
+
+/// Determine how many bytes at the start of some received output have already been printed.
+///
+/// The output starts at `file_start` in the task's log file, while all output up to
+/// `previous_end` has been printed before. Output in between has been lost, which is reported.
+/// Invalid UTF-8 has been replaced in the received text, so its length may differ slightly from
+/// the one in the log file.
+fn already_printed(task_id: usize, file_start: u64, previous_end: u64, text: &str) -> usize {
+    if file_start > previous_end {
+        let missing = file_start - previous_end;
+        eprintln!("Pueue: ({missing} bytes of task {task_id}'s output are missing)");
+        return 0;
+    }
+
+    let overlap = usize::try_from(previous_end - file_start).unwrap_or(usize::MAX);
+    let mut start = overlap.min(text.len());
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    start
+}
+
+/// Tell the user that a followed task has been restarted in place.
+/// The output of the new run follows.
+fn print_restart_notice(task_id: usize) {
+    eprintln!("Pueue: (task {task_id} has been restarted)");
+}
+
+/// Shows a spinner on stderr while we're waiting for the first output of the followed tasks.
+///
+/// The spinner runs in a separate thread, as the remote log stream blocks while waiting.
+/// It's removed once the [WaitSpinner] is dropped.
+pub struct WaitSpinner {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WaitSpinner {
+    const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+    fn start(style: &OutputStyle) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let text = style.style_text("Waiting for output…", None, Some(Attribute::Dim));
+
+        let thread = thread::spawn({
+            let stop = stop.clone();
+            move || {
+                let mut stderr = io::stderr();
+                for frame in Self::FRAMES.iter().cycle() {
+                    if stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let _ = write!(stderr, "\r{frame} {text}");
+                    let _ = stderr.flush();
+                    thread::sleep(Duration::from_millis(100));
+                }
+                let _ = execute!(stderr, MoveToColumn(0), Clear(ClearType::CurrentLine));
+            }
+        });
+
+        Self {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for WaitSpinner {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Wait for the spinner to be removed, so it doesn't interfere with any following output.
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The marker that's printed when a followed log file has been truncated or rotated.
+const ROTATION_MARKER: &str = "--- log rotated ---";
+
+/// A chunk of followed output, which is printed as a single line of json by `follow --json`.
+#[derive(Debug, Serialize)]
+struct FollowRecord<'a> {
+    task_id: usize,
+    content: Cow<'a, str>,
+    /// The time at which the content has been read.
+    timestamp: DateTime<Local>,
+}
+
+/// Limits the amount of lines of a task that're printed per second via `--throttle`.
+///
+/// This is a token bucket, which allows bursts of up to a second's worth of lines.
+/// Skipped lines are counted, so they can be pointed out at most once per second.
+struct Throttle {
+    lines_per_sec: f64,
+    tokens: f64,
+    last_refill: Instant,
+    skipped: usize,
+    last_notice: Instant,
+}
+
+impl Throttle {
+    fn new(lines_per_sec: u32, now: Instant) -> Self {
+        let lines_per_sec = f64::from(lines_per_sec);
+        Self {
+            lines_per_sec,
+            tokens: lines_per_sec,
+            last_refill: now,
+            skipped: 0,
+            last_notice: now,
+        }
+    }
+
+    /// Whether the next line may be printed. Otherwise, it's counted as skipped.
+    fn allow(&mut self, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.lines_per_sec).min(self.lines_per_sec);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            self.skipped += 1;
+            false
+        }
+    }
+
+    /// Take the amount of skipped lines, if they should be pointed out.
+    /// Unless `force` is set, this only happens once per second.
+    fn take_skipped(&mut self, now: Instant, force: bool) -> Option<usize> {
+        if self.skipped == 0
+            || (!force && now.saturating_duration_since(self.last_notice) < Duration::from_secs(1))
+        {
+            return None;
+        }
+        self.last_notice = now;
+        Some(std::mem::take(&mut self.skipped))
+    }
+}
+
+fn throttle_notice(skipped: usize) -> String {
+    let noun = if skipped == 1 { "line" } else { "lines" };
+    format!("(throttled, skipped {skipped} {noun})")
+}
+
+/// Writes the output of a single followed task to stdout.
+///
+/// If lines are filtered, squashed or prefixed with the task id, stream or timestamps, incomplete
+/// lines are buffered until they've been fully written. This prevents lines of different tasks from being torn apart
+/// and ensures that filters are only applied to complete lines.
+struct TaskOutput {
+    task_id: usize,
+    /// Print the output as json records instead of plain text.
+    json: bool,
+    /// The prefix that's added to each line, e.g. `[task 3] `.
+    id_prefix: Option<String>,
+    line_timestamps: Option<LineTimestamps>,
+    line_streams: Option<LineStreams>,
+    filter: Option<LineFilter>,
+    squash: Option<SquashRepeats>,
+    throttle: Option<Throttle>,
+    incomplete_line: Vec<u8>,
+    /// The byte offset in the log file at which the next line starts.
+    line_offset: u64,
+}
+
+impl TaskOutput {
+    /// The filter, json and squash settings are taken from the `options`.
+    fn new(
+        task_id: usize,
+        options: &FollowOptions,
+        prefix_id: bool,
+        line_timestamps: Option<LineTimestamps>,
+        line_streams: Option<LineStreams>,
+        throttle: Option<u32>,
+        line_offset: u64,
+    ) -> Self {
+        Self {
+            task_id,
+            json: options.json,
+            id_prefix: prefix_id.then(|| format!("[task {task_id}] ")),
+            line_timestamps,
+            line_streams,
+            filter: options.filter.clone(),
+            squash: options.squash_repeats.then(SquashRepeats::default),
+            throttle: throttle.map(|lines_per_sec| Throttle::new(lines_per_sec, Instant::now())),
+            incomplete_line: Vec::new(),
+            line_offset,
+        }
+    }
+
+    /// Whether the output can be written as it is.
+    fn is_plain(&self) -> bool {
+        !self.json
+            && self.id_prefix.is_none()
+            && self.line_timestamps.is_none()
+            && self.line_streams.is_none()
+            && self.filter.is_none()
+            && self.squash.is_none()
+            && self.throttle.is_none()
+    }
+
+    /// Copy all new content of a log file to stdout.
+    fn copy(
+        &mut self,
+        handle: &mut File,
+        stdout: &mut impl Write,
+        stats: &mut FollowStats,
+    ) -> io::Result<()> {
+        if self.is_plain() {
+            io::copy(handle, &mut StatsWriter { stdout, stats })?;
+            // Stdout is line buffered, even if it isn't a terminal. Incomplete lines are flushed
+            // right away, so they're visible while the task is still writing them.
+            return stdout.flush();
+        }
+
+        let mut buffer = Vec::new();
+        handle.read_to_end(&mut buffer)?;
+        // The stream index is written before the output, so it covers everything that's been read.
+        if !buffer.is_empty() {
+            if let Some(line_streams) = self.line_streams.as_mut() {
+                line_streams.reload();
+            }
+        }
+        self.write(stdout, &buffer, stats)
+    }
+
+    /// Write a new chunk of output. Only complete lines are written, if they need to be prefixed.
+    ///
+    /// The output is split into lines before it's decoded. That way, invalid UTF-8 doesn't
+    /// affect the byte offsets and characters that're split across chunks stay intact.
+    fn write(
+        &mut self,
+        stdout: &mut impl Write,
+        output: &[u8],
+        stats: &mut FollowStats,
+    ) -> io::Result<()> {
+        stats.record(output);
+        if self.is_plain() {
+            return stdout.write_all(output);
+        }
+
+        // Combine the output with any incomplete line from the previous chunk.
+        self.incomplete_line.extend_from_slice(output);
+
+        // All complete lines are written as a single record.
+        if self.json {
+            let Some(end) = self.incomplete_line.iter().rposition(|byte| *byte == b'\n') else {
+                return Ok(());
+            };
+            let incomplete_line = self.incomplete_line.split_off(end + 1);
+            let output = std::mem::replace(&mut self.incomplete_line, incomplete_line);
+            return self.write_record(stdout, &output);
+        }
+
+        let output = std::mem::take(&mut self.incomplete_line);
+
+        // Split into lines, while keeping the newlines to track the byte offsets.
+        for line in output.split_inclusive(|byte| *byte == b'\n') {
+            if !line.ends_with(b"\n") {
+                self.incomplete_line = line.to_vec();
+                break;
+            }
+            self.write_line(stdout, line)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the last line, even if it didn't end with a newline.
+    /// If lines are squashed, the marker of the last line's repeats is written as well.
+    fn finish(&mut self, stdout: &mut impl Write) -> io::Result<()> {
+        if !self.incomplete_line.is_empty() {
+            let line = std::mem::take(&mut self.incomplete_line);
+            if self.json {
+                return self.write_record(stdout, &line);
+            }
+            self.write_line(stdout, &line)?;
+        }
+
+        let id_prefix = self.id_prefix.as_deref().unwrap_or_default();
+        if let Some(squash) = self.squash.as_mut() {
+            squash.finish(stdout, id_prefix)?;
+        }
+        if let Some(skipped) = self
+            .throttle
+            .as_mut()
+            .and_then(|throttle| throttle.take_skipped(Instant::now(), true))
+        {
+            writeln!(stdout, "{id_prefix}{}", throttle_notice(skipped))?;
+        }
+
+        Ok(())
+    }
+
+    /// Start over at the beginning of a log file that has been truncated or rotated.
+    /// Any incomplete line of the previous content is written, followed by a marker.
+    fn rotate(&mut self, handle: &mut File, stdout: &mut impl Write) -> io::Result<()> {
+        self.finish(stdout)?;
+        handle.seek(SeekFrom::Start(0))?;
+        self.line_offset = 0;
+
+        // The timestamp index belongs to the previous content of the file.
+        if let Some(line_timestamps) = self.line_timestamps.as_mut() {
+            line_timestamps.discard_index();
+        }
+
+        // Consumers of json records see the new content, without any marker in between.
+        if self.json {
+            return Ok(());
+        }
+
+        let id_prefix = self.id_prefix.as_deref().unwrap_or_default();
+        writeln!(stdout, "{id_prefix}{ROTATION_MARKER}")
+    }
+
+    /// Write some output as a single json record and flush it, so it can be processed right away.
+    fn write_record(&self, stdout: &mut impl Write, output: &[u8]) -> io::Result<()> {
+        let record = FollowRecord {
+            task_id: self.task_id,
+            content: String::from_utf8_lossy(output),
+            timestamp: Local::now(),
+        };
+        serde_json::to_writer(&mut *stdout, &record)?;
+        writeln!(stdout)?;
+        stdout.flush()
+    }
+
+    fn write_line(&mut self, stdout: &mut impl Write, line: &[u8]) -> io::Result<()> {
+        let line_offset = self.line_offset;
+        self.line_offset += line.len() as u64;
+
+        let line = decode_line(line);
+        let Some(line) = filter_line(self.filter.as_ref(), &line) else {
+            return Ok(());
+        };
+
+        let id_prefix = self.id_prefix.as_deref().unwrap_or_default();
+        if let Some(squash) = self.squash.as_mut() {
+            if squash.skip_line(stdout, &line, id_prefix)? {
+                return Ok(());
+            }
+        }
+        if let Some(throttle) = self.throttle.as_mut() {
+            let now = Instant::now();
+            let allowed = throttle.allow(now);
+            if let Some(skipped) = throttle.take_skipped(now, false) {
+                writeln!(stdout, "{id_prefix}{}", throttle_notice(skipped))?;
+            }
+            if !allowed {
+                return Ok(());
+            }
+        }
+
+        let timestamp = self
+            .line_timestamps
+            .as_ref()
+            .map(|line_timestamps| line_timestamps.prefix(line_offset))
+            .unwrap_or_default();
+        // The stream is looked up by the offset at which the line starts, which is also correct
+        // for lines that have been buffered across multiple reads.
+        match &self.line_streams {
+            Some(line_streams) => {
+                let stream = line_streams.prefix(line_offset);
+                let line = format!("{stream}{timestamp}{line}");
+                let line = line_streams.style_line(line_offset, line);
+                writeln!(stdout, "{id_prefix}{line}")
+            }
+            None => writeln!(stdout, "{id_prefix}{timestamp}{line}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn skip_already_printed_output() {
+        // The output continues right where we left off.
+        assert_eq!(already_printed(0, 10, 10, "new\n"), 0);
+        // The first bytes have been sent twice.
+        assert_eq!(already_printed(0, 8, 10, "ab new\n"), 2);
+        assert_eq!(already_printed(0, 0, 10, "old\n"), 4);
+        // Multi-byte characters are never split.
+        assert_eq!(already_printed(0, 9, 10, "äb\n"), 2);
+        // Some output has been lost, everything that has been received is printed.
+        assert_eq!(already_printed(0, 12, 10, "new\n"), 0);
+    }
+
+    #[test]
+    fn throttle_lines() {
+        let start = Instant::now();
+        let mut throttle = Throttle::new(2, start);
+        // A second's worth of lines may be printed right away.
+        assert!(throttle.allow(start));
+        assert!(throttle.allow(start));
+        assert!(!throttle.allow(start));
+        assert!(!throttle.allow(start));
+        // Skipped lines are only pointed out once per second, unless forced.
+        assert_eq!(throttle.take_skipped(start, false), None);
+
+        // Half a second later, another line may be printed.
+        let later = start + Duration::from_millis(500);
+        assert!(throttle.allow(later));
+        assert!(!throttle.allow(later));
+        assert_eq!(throttle.take_skipped(later, true), Some(3));
+        assert_eq!(throttle.take_skipped(later, true), None);
+
+        // The bucket doesn't fill up beyond a second's worth of lines.
+        let much_later = start + Duration::from_secs(10);
+        assert!(throttle.allow(much_later));
+        assert!(throttle.allow(much_later));
+        assert!(!throttle.allow(much_later));
+        assert_eq!(throttle.take_skipped(much_later, false), Some(1));
+    }
+
+    #[test]
+    fn detect_clock_skew() {
+        let now = Local::now();
+        assert_eq!(clock_skew(now + TimeDelta::seconds(3), now, 5), None);
+        assert_eq!(
+            clock_skew(now + TimeDelta::seconds(10), now, 5),
+            Some(TimeDelta::seconds(10))
+        );
+        assert_eq!(
+            clock_skew(now - TimeDelta::seconds(10), now, 5),
+            Some(TimeDelta::seconds(-10))
+        );
+        // Huge thresholds don't overflow.
+        assert_eq!(clock_skew(now - TimeDelta::days(1), now, u64::MAX), None);
+    }
+
+    #[test]
+    fn concurrent_sink_lines() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tee_path = dir.path().join("tee.log");
+        let tee = TeeFile::open(&tee_path)?;
+        // Local and remote tasks are followed at the same time and share a single sink, just like
+        // the tasks of a single source do.
+        let sink = LogSink::new(TeeWriter::new(Vec::new(), Some(&tee)));
+        thread::scope(|scope| {
+            for (source, writer) in ["local", "remote"]
+                .into_iter()
+                .flat_map(|source| (0..4).map(move |writer| (source, writer)))
+            {
+                let mut sink = sink.clone();
+                scope.spawn(move || {
+                    for line in 0..500 {
+                        // Lines are split across several writes, which may happen concurrently.
+                        write!(sink, "{source} writer {writer} ").unwrap();
+                        write!(sink, "line {line}\n{source} writer {writer} ").unwrap();
+                        sink.write_all(b"continued\n").unwrap();
+                    }
+                    sink.flush().unwrap();
+                });
+            }
+        });
+
+        let output = String::from_utf8(LogSink::lock(&sink.out).stdout.clone())?;
+        drop(sink);
+        tee.finish();
+        assert_eq!(output.lines().count(), 8 * 500 * 2);
+        // The tee file may miss some output if it can't keep up, but never has mixed up lines.
+        let tee_output = std::fs::read_to_string(&tee_path)?;
+        let tee_lines = tee_output
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with("(Pueue:"));
+        for line in output.lines().chain(tee_lines) {
+            let words: Vec<&str> = line.split(' ').collect();
+            assert!(
+                matches!(
+                    words.as_slice(),
+                    ["local" | "remote", "writer", _, "line", _]
+                        | ["local" | "remote", "writer", _, "continued"]
+                ),
+                "Lines have been mixed up: {line}"
+            );
+        }
 
-        last_check += log_check_interval;
-        let timeout = Duration::from_millis(log_check_interval);
-        sleep(timeout).await;
+        Ok(())
     }
 }
@@ -0,0 +1,106 @@
+use std::borrow::Cow;
+
+use chrono::SecondsFormat;
+use pueue_lib::task::{Task, TaskResult, TaskStatus};
+
+/// A single line of machine-parseable task metadata, which is prepended to a task's output via
+/// `--annotate`, e.g. `#pueue task=3 group=default label=build status=success exit=0 start=...`.
+///
+/// In contrast to the task information, this line is part of the output itself. Concatenated or
+/// archived logs thereby stay self-describing, even if the headers are lost.
+/// Values are `key=value` pairs, whose values are quoted if they contain whitespace.
+pub fn annotation(prefix: &str, task: &Task) -> String {
+    let mut fields = vec![
+        format!("task={}", task.id),
+        format!("group={}", annotation_value(&task.group)),
+    ];
+    if let Some(label) = &task.label {
+        fields.push(format!("label={}", annotation_value(label)));
+    }
+
+    let (status, exit_code) = match &task.status {
+        TaskStatus::Running { .. } => ("running", None),
+        TaskStatus::Paused { .. } => ("paused", None),
+        TaskStatus::Done { result, .. } => match result {
+            TaskResult::Success => ("success", Some(0)),
+            TaskResult::Failed(exit_code) => ("failed", Some(*exit_code)),
+            TaskResult::FailedToSpawn(_) => ("failed_to_spawn", None),
+            TaskResult::Killed => ("killed", None),
+            TaskResult::Errored => ("errored", None),
+            TaskResult::DependencyFailed => ("dependency_failed", None),
+        },
+        // Only tasks with some output are annotated.
+        _ => ("not_started", None),
+    };
+    fields.push(format!("status={status}"));
+    if let Some(exit_code) = exit_code {
+        fields.push(format!("exit={exit_code}"));
+    }
+
+    let (start, end) = task.start_and_end();
+    if let Some(start) = start {
+        fields.push(format!(
+            "start={}",
+            start.to_rfc3339_opts(SecondsFormat::Secs, false)
+        ));
+    }
+    if let Some(end) = end {
+        fields.push(format!(
+            "end={}",
+            end.to_rfc3339_opts(SecondsFormat::Secs, false)
+        ));
+    }
+
+    format!("{prefix} {}", fields.join(" "))
+}
+
+/// Quote values that would otherwise be split into multiple fields.
+fn annotation_value(value: &str) -> Cow<'_, str> {
+    if value.is_empty() || value.contains(|char: char| char.is_whitespace() || char == '"') {
+        Cow::Owned(format!("{value:?}"))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::{Local, TimeZone};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn annotate_task() {
+        let start = Local.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let end = Local.with_ymd_and_hms(2024, 5, 1, 12, 5, 30).unwrap();
+        let mut task = Task::new(
+            "make".into(),
+            PathBuf::from("/"),
+            Default::default(),
+            "default".into(),
+            TaskStatus::Done {
+                enqueued_at: start,
+                start,
+                end,
+                result: TaskResult::Failed(2),
+            },
+            Vec::new(),
+            0,
+            Some("nightly build".into()),
+        );
+        task.id = 3;
+
+        let start = start.to_rfc3339_opts(SecondsFormat::Secs, false);
+        let end = end.to_rfc3339_opts(SecondsFormat::Secs, false);
+        assert_eq!(
+            annotation("#pueue", &task),
+            format!(
+                "#pueue task=3 group=default label=\"nightly build\" status=failed exit=2 \
+                start={start} end={end}"
+            )
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    io::{self, Write},
+    ops::Range,
+};
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+/// The position inside of an ANSI escape sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EscapeState {
+    /// Regular text.
+    Text,
+    /// Right after an `ESC`.
+    Escape,
+    /// Within a sequence that's ended by a single final byte, such as `ESC ( B`.
+    Intermediate,
+    /// Within a control sequence, such as `ESC [ 31 m`.
+    Csi,
+    /// Within an operating system command, which is terminated by `BEL` or `ESC \`.
+    Osc,
+    /// Right after an `ESC` within an operating system command.
+    OscEscape,
+}
+
+impl EscapeState {
+    /// Advance the state by a single byte.
+    /// Returns the new state and whether the byte is part of an escape sequence.
+    fn next(self, byte: u8) -> (Self, bool) {
+        match (self, byte) {
+            (EscapeState::Text, ESC) => (EscapeState::Escape, true),
+            (EscapeState::Text, _) => (EscapeState::Text, false),
+            (EscapeState::Escape, b'[') => (EscapeState::Csi, true),
+            (EscapeState::Escape, b']') => (EscapeState::Osc, true),
+            (EscapeState::Escape | EscapeState::Intermediate, 0x20..=0x2f) => {
+                (EscapeState::Intermediate, true)
+            }
+            (EscapeState::Escape | EscapeState::Intermediate, _) => (EscapeState::Text, true),
+            // Parameter and intermediate bytes, the sequence ends with a byte in `0x40..=0x7e`.
+            (EscapeState::Csi, 0x20..=0x3f) => (EscapeState::Csi, true),
+            (EscapeState::Csi, 0x40..=0x7e) => (EscapeState::Text, true),
+            // Anything else is invalid and aborts the sequence.
+            (EscapeState::Csi, _) => (EscapeState::Text, false),
+            (EscapeState::Osc, BEL) => (EscapeState::Text, true),
+            (EscapeState::Osc, ESC) => (EscapeState::OscEscape, true),
+            (EscapeState::Osc, _) => (EscapeState::Osc, true),
+            (EscapeState::OscEscape, b'\\') => (EscapeState::Text, true),
+            (EscapeState::OscEscape, _) => (EscapeState::Osc, true),
+        }
+    }
+}
+
+/// A writer that removes all ANSI escape sequences, such as colors, from the written output.
+///
+/// Nothing is buffered, escape sequences that are split across multiple writes are still removed.
+pub struct StripAnsi<W: Write> {
+    inner: W,
+    state: EscapeState,
+}
+
+impl<W: Write> StripAnsi<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            state: EscapeState::Text,
+        }
+    }
+}
+
+impl<W: Write> Write for StripAnsi<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Text is written in consecutive runs between the escape sequences.
+        let mut text_start = None;
+        for (index, byte) in buf.iter().enumerate() {
+            let (state, escaped) = self.state.next(*byte);
+            self.state = state;
+
+            match (escaped, text_start) {
+                (false, None) => text_start = Some(index),
+                (true, Some(start)) => {
+                    self.inner.write_all(&buf[start..index])?;
+                    text_start = None;
+                }
+                _ => (),
+            }
+        }
+        if let Some(start) = text_start {
+            self.inner.write_all(&buf[start..])?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Return the byte ranges of all ANSI escape sequences in a line.
+pub fn escape_sequences(line: &str) -> Vec<Range<usize>> {
+    let mut sequences = Vec::new();
+    let mut state = EscapeState::Text;
+    let mut sequence_start = None;
+    for (index, byte) in line.bytes().enumerate() {
+        let (next_state, escaped) = state.next(byte);
+        state = next_state;
+
+        match (escaped, sequence_start) {
+            (true, None) => sequence_start = Some(index),
+            (false, Some(start)) => {
+                sequences.push(start..index);
+                sequence_start = None;
+            }
+            _ => (),
+        }
+        // Sequences may directly follow each other.
+        if escaped && state == EscapeState::Text {
+            if let Some(start) = sequence_start.take() {
+                sequences.push(start..index + 1);
+            }
+        }
+    }
+    if let Some(start) = sequence_start {
+        sequences.push(start..line.len());
+    }
+
+    sequences
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn strip_colors() {
+        let mut output = Vec::new();
+        let mut writer = StripAnsi::new(&mut output);
+        writer
+            .write_all(b"\x1b[1;31merror\x1b[0m: \x1b]8;;http://x\x1b\\link\x1b]8;;\x07 done\n")
+            .unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&output), "error: link done\n");
+    }
+
+    #[test]
+    fn strip_split_sequences() {
+        let mut output = Vec::new();
+        let mut writer = StripAnsi::new(&mut output);
+        writer.write_all(b"first\x1b").unwrap();
+        writer.write_all(b"[3").unwrap();
+        writer.write_all(b"2msecond\n").unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&output), "firstsecond\n");
+    }
+
+    #[test]
+    fn find_escape_sequences() {
+        let line = "\x1b[31m\x1b[1mred\x1b[0m text";
+        let sequences = escape_sequences(line);
+
+        assert_eq!(sequences, vec![0..5, 5..9, 12..16]);
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use flate2::{Compression, write::GzEncoder};
+use pueue_lib::{
+    log::get_log_file_handle,
+    message::TaskLogResponse,
+    settings::Settings,
+    task::{Task, TaskResult, TaskStatus},
+};
+use serde::Serialize;
+
+use super::LogChunks;
+use crate::internal_prelude::*;
+
+/// Describes a single task in the `manifest.json` of a log archive.
+#[derive(Debug, Serialize)]
+struct ManifestEntry {
+    id: usize,
+    /// The name of the task's log file inside the archive.
+    log: String,
+    command: String,
+    path: PathBuf,
+    label: Option<String>,
+    group: String,
+    result: Option<TaskResult>,
+    exit_code: Option<i32>,
+    start: Option<DateTime<Local>>,
+    end: Option<DateTime<Local>>,
+}
+
+impl ManifestEntry {
+    fn new(task: &Task) -> Self {
+        let result = match &task.status {
+            TaskStatus::Done { result, .. } => Some(result.clone()),
+            _ => None,
+        };
+        let exit_code = match result {
+            Some(TaskResult::Success) => Some(0),
+            Some(TaskResult::Failed(exit_code)) => Some(exit_code),
+            _ => None,
+        };
+        let (start, end) = task.start_and_end();
+
+        ManifestEntry {
+            id: task.id,
+            log: archive_log_name(task),
+            command: task.command.clone(),
+            path: task.path.clone(),
+            label: task.label.clone(),
+            group: task.group.clone(),
+            result,
+            exit_code,
+            start,
+            end,
+        }
+    }
+}
+
+/// Write the logs of all finished tasks into a gzip compressed tar archive.
+///
+/// Each task's log is a separate `<id>-<label>.log` entry and a `manifest.json` describes all
+/// archived tasks. The archive is streamed to disk, so only a single chunk of output is kept
+/// in memory at any time.
+pub async fn write_log_archive(
+    path: &Path,
+    task_logs: &BTreeMap<usize, TaskLogResponse>,
+    settings: &Settings,
+    chunks: &mut LogChunks<'_>,
+) -> Result<usize> {
+    let tasks: Vec<&TaskLogResponse> = task_logs
+        .values()
+        .filter(|task_log| task_log.task.is_done())
+        .collect();
+
+    let file = File::create(path).wrap_err_with(|| format!("Failed to create archive {path:?}"))?;
+    let encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+
+    // Add the manifest first, so it can be found without unpacking the whole archive.
+    let manifest: Vec<ManifestEntry> = tasks
+        .iter()
+        .map(|task_log| ManifestEntry::new(&task_log.task))
+        .collect();
+    let manifest = serde_json::to_vec_pretty(&manifest)?;
+    let mut header = archive_header(manifest.len() as u64, Local::now());
+    builder
+        .append_data(&mut header, "manifest.json", manifest.as_slice())
+        .context("Failed to add manifest to archive")?;
+
+    for task_log in &tasks {
+        let task = &task_log.task;
+        let mut log = if settings.client.read_local_logs {
+            get_log_file_handle(task.id, &settings.shared.pueue_directory())?
+        } else {
+            receive_remote_log(task_log, chunks).await?
+        };
+
+        let size = log.metadata()?.len();
+        let (start, end) = task.start_and_end();
+        let time = end.or(start).unwrap_or_else(Local::now);
+        let mut header = archive_header(size, time);
+        builder
+            .append_data(&mut header, archive_log_name(task), &mut log)
+            .wrap_err_with(|| format!("Failed to add log of task {} to archive", task.id))?;
+    }
+
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .and_then(|mut file| file.flush())
+        .wrap_err_with(|| format!("Failed to write archive {path:?}"))?;
+
+    Ok(tasks.len())
+}
+
+/// The name of a task's log file inside the archive.
+/// Path separators in labels are replaced, so each log is a top-level entry.
+fn archive_log_name(task: &Task) -> String {
+    match &task.label {
+        Some(label) => {
+            let label = label.replace(['/', '\\'], "_");
+            format!("{}-{label}.log", task.id)
+        }
+        None => format!("{}.log", task.id),
+    }
+}
+
+/// Create the header for a regular file entry.
+fn archive_header(size: u64, time: DateTime<Local>) -> tar::Header {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(size);
+    header.set_mode(0o644);
+    header.set_mtime(time.timestamp().try_into().unwrap_or_default());
+    header.set_cksum();
+
+    header
+}
+
+/// Decompress a task's log output that's received from the daemon into a temporary file.
+///
+/// Archive entries need to know their size upfront, which is why the output cannot be directly
+/// streamed into the archive.
+async fn receive_remote_log(
+    task_log: &TaskLogResponse,
+    chunks: &mut LogChunks<'_>,
+) -> Result<File> {
+    let mut file = tempfile::tempfile().context("Failed to create temporary file")?;
+
+    if let Some(bytes) = &task_log.output {
+        // Older daemons send the output as a single payload.
+        let mut decoder = task_log.compression.decompressor(bytes.as_slice())?;
+        io::copy(&mut decoder, &mut file).context("Failed to decompress remote log output")?;
+    } else {
+        while let Some(bytes) = chunks.next(task_log.task.id).await? {
+            let mut decoder = task_log.compression.decompressor(bytes.as_slice())?;
+            io::copy(&mut decoder, &mut file).context("Failed to decompress remote log output")?;
+        }
+        if let Some(error) = chunks.take_error(task_log.task.id) {
+            bail!(error);
+        }
+    }
+
+    file.seek(SeekFrom::Start(0))?;
+
+    Ok(file)
+}
@@ -0,0 +1,136 @@
+use std::collections::{VecDeque, vec_deque::Drain};
+
+/// The separator between groups of lines that aren't adjacent, just like `grep --context`.
+pub const CONTEXT_SEPARATOR: &str = "--";
+
+/// Shows some lines around the lines that match a filter, just like `grep --context`.
+///
+/// Lines are passed in order, together with whether they matched. Lines that don't match are
+/// held back, until it's clear whether they precede a match.
+#[derive(Clone, Debug)]
+pub struct LineContext<T> {
+    before: usize,
+    after: usize,
+    /// The last lines that didn't match, which are shown in front of the next match.
+    preceding: VecDeque<T>,
+    /// The amount of lines after the last match that are still shown.
+    remaining_after: usize,
+    /// Whether any line has been shown yet.
+    shown: bool,
+    /// Whether any line has been hidden since the last shown line.
+    gap: bool,
+}
+
+/// The lines that should be shown, after a line has been passed to a [LineContext].
+pub struct ContextLines<'a, T> {
+    /// Lines have been hidden since the last shown line, so a [CONTEXT_SEPARATOR] has to be
+    /// printed in front of these lines.
+    pub separator: bool,
+    pub lines: Drain<'a, T>,
+}
+
+impl<T> LineContext<T> {
+    pub fn new(before: usize, after: usize) -> Self {
+        Self {
+            before,
+            after,
+            preceding: VecDeque::with_capacity(before + 1),
+            remaining_after: 0,
+            shown: false,
+            gap: false,
+        }
+    }
+
+    /// Pass the next line and return the lines that should be shown now.
+    pub fn next(&mut self, line: T, matched: bool) -> ContextLines<'_, T> {
+        self.preceding.push_back(line);
+        if matched {
+            self.remaining_after = self.after;
+        } else if self.remaining_after > 0 {
+            self.remaining_after -= 1;
+        } else {
+            if self.preceding.len() > self.before {
+                self.preceding.pop_front();
+                self.gap = true;
+            }
+            return ContextLines {
+                separator: false,
+                lines: self.preceding.drain(..0),
+            };
+        }
+
+        let separator = self.shown && self.gap;
+        self.shown = true;
+        self.gap = false;
+        ContextLines {
+            separator,
+            lines: self.preceding.drain(..),
+        }
+    }
+
+    /// Forget about all previous lines, e.g. because lines have been omitted in between.
+    /// The next shown line isn't preceded by a separator.
+    pub fn reset(&mut self) {
+        self.preceding.clear();
+        self.remaining_after = 0;
+        self.shown = false;
+        self.gap = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Run lines through the context and return the shown lines, including separators.
+    fn shown_lines(before: usize, after: usize, lines: &[&str], pattern: &str) -> Vec<String> {
+        let mut context = LineContext::new(before, after);
+        let mut shown = Vec::new();
+        for line in lines {
+            let lines = context.next(line.to_string(), line.contains(pattern));
+            if lines.separator {
+                shown.push(CONTEXT_SEPARATOR.to_string());
+            }
+            shown.extend(lines.lines);
+        }
+
+        shown
+    }
+
+    #[test]
+    fn context_around_matches() {
+        let lines = ["a", "b", "match 1", "c", "d", "e", "f", "match 2", "g"];
+        assert_eq!(
+            shown_lines(1, 1, &lines, "match"),
+            vec!["b", "match 1", "c", "--", "f", "match 2", "g"]
+        );
+        assert_eq!(
+            shown_lines(0, 2, &lines, "match"),
+            vec!["match 1", "c", "d", "--", "match 2", "g"]
+        );
+        assert_eq!(
+            shown_lines(3, 0, &lines, "match"),
+            vec!["a", "b", "match 1", "--", "d", "e", "f", "match 2"]
+        );
+        // Overlapping and adjacent groups are merged.
+        assert_eq!(
+            shown_lines(2, 2, &lines, "match"),
+            vec!["a", "b", "match 1", "c", "d", "e", "f", "match 2", "g"]
+        );
+    }
+
+    #[test]
+    fn reset_context() {
+        let mut context = LineContext::new(1, 1);
+        assert_eq!(context.next("a", true).lines.collect::<Vec<_>>(), vec!["a"]);
+        context.reset();
+        // Neither the trailing context nor the separator carry over a reset.
+        assert_eq!(context.next("b", false).lines.count(), 0);
+        assert!(!context.next("c", false).separator);
+        let shown = context.next("d", true);
+        assert!(!shown.separator);
+        assert_eq!(shown.lines.collect::<Vec<_>>(), vec!["c", "d"]);
+    }
+}
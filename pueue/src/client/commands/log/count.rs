@@ -0,0 +1,147 @@
+use std::{collections::BTreeMap, io::Read};
+
+use pueue_lib::{
+    Client,
+    log::{OutputStream, get_log_size},
+    message::{LogRequest, Response, TaskLogResponse, TaskSelection},
+    settings::Settings,
+};
+use serde::Serialize;
+
+use super::{has_log, print_missing_logs_hint, remote::count_output_lines};
+use crate::{
+    client::{commands::handle_response, style::OutputStyle},
+    internal_prelude::*,
+};
+
+/// The size of a task's output.
+#[derive(Debug, Serialize)]
+struct LogSize {
+    lines: usize,
+    bytes: u64,
+}
+
+/// Print the amount of lines and bytes of each selected task's output, without its content.
+///
+/// Local logs are counted without reading them into memory. Otherwise, the daemon counts them
+/// for us. Older daemons don't know how to, in which case their output is sent and counted here.
+///
+/// If a single task is selected, only its size is printed as `lines=X bytes=Y`.
+/// Otherwise, each line is prefixed with `task=ID`.
+pub async fn print_log_sizes(
+    client: &mut Client,
+    settings: &Settings,
+    style: &OutputStyle,
+    selection: TaskSelection,
+    stream: Option<OutputStream>,
+    json: bool,
+) -> Result<()> {
+    let read_local_logs = settings.client.read_local_logs;
+    let Some(task_logs) =
+        request_logs(client, style, &selection, stream, false, !read_local_logs).await?
+    else {
+        return Ok(());
+    };
+    if !json {
+        print_missing_logs_hint(&task_logs, &selection);
+    }
+
+    let mut sizes = BTreeMap::new();
+    let mut uncounted = Vec::new();
+    for (task_id, task_log) in task_logs.iter() {
+        if !has_log(&task_log.task) {
+            continue;
+        }
+
+        if read_local_logs {
+            match get_log_size(*task_id, stream, &settings.shared.pueue_directory()) {
+                Ok((lines, bytes)) => {
+                    sizes.insert(*task_id, LogSize { lines, bytes });
+                }
+                Err(err) => eprintln!("Failed to count the output of task {task_id}: {err}"),
+            }
+        } else if let (Some(lines), Some(bytes)) = (task_log.total_lines, task_log.total_bytes) {
+            sizes.insert(*task_id, LogSize { lines, bytes });
+        } else {
+            uncounted.push(*task_id);
+        }
+    }
+
+    // Older daemons don't send the size of the output, which is why we count it ourselves.
+    if !uncounted.is_empty() {
+        let selection = TaskSelection::TaskIds(uncounted);
+        let Some(task_logs) = request_logs(client, style, &selection, stream, true, false).await?
+        else {
+            return Ok(());
+        };
+        for (task_id, task_log) in task_logs.iter() {
+            let Some(bytes) = task_log.output.as_ref() else {
+                continue;
+            };
+            let mut output = Vec::new();
+            task_log
+                .compression
+                .decompressor(bytes.as_slice())?
+                .read_to_end(&mut output)
+                .context("Failed to decompress remote log output")?;
+            let size = LogSize {
+                lines: count_output_lines(&output),
+                bytes: output.len() as u64,
+            };
+            sizes.insert(*task_id, size);
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string(&sizes)?);
+        return Ok(());
+    }
+
+    let single_task = matches!(&selection, TaskSelection::TaskIds(ids) if ids.len() == 1);
+    for (task_id, size) in sizes {
+        let LogSize { lines, bytes } = size;
+        if single_task {
+            println!("lines={lines} bytes={bytes}");
+        } else {
+            println!("task={task_id} lines={lines} bytes={bytes}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Request the selected tasks, either with their full output or only with its size.
+/// Returns `None` if the daemon responded with a failure, which has already been printed.
+async fn request_logs(
+    client: &mut Client,
+    style: &OutputStyle,
+    selection: &TaskSelection,
+    stream: Option<OutputStream>,
+    send_logs: bool,
+    count: bool,
+) -> Result<Option<BTreeMap<usize, TaskLogResponse>>> {
+    client
+        .send_request(LogRequest {
+            tasks: selection.clone(),
+            send_logs,
+            lines: None,
+            head: None,
+            tail: None,
+            stream,
+            line_numbers: false,
+            chunked: false,
+            count,
+            compression: Default::default(),
+            compression_level: None,
+            task_lines: BTreeMap::new(),
+        })
+        .await?;
+
+    let response = client.receive_response().await?;
+    let Response::Log(task_logs) = response else {
+        handle_response(style, response)?;
+        return Ok(None);
+    };
+
+    Ok(Some(task_logs))
+}
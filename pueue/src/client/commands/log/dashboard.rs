@@ -0,0 +1,261 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, IsTerminal, Read, Write},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    execute, queue,
+    style::Attribute,
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use pueue_lib::{
+    Client,
+    log::{OutputStream, get_stream_log_file_handle, seek_to_last_lines},
+    message::{LogRequest, Response, TaskLogResponse, TaskSelection},
+    settings::Settings,
+};
+use tokio::time::sleep;
+
+use super::{
+    LineAmount, LogOptions, ansi::escape_sequences, has_log, task_status, wrap::advance_char,
+};
+use crate::{
+    client::{commands::handle_response, style::OutputStyle},
+    internal_prelude::*,
+};
+
+/// How often the terminal size is checked, so the view is re-rendered right after a resize.
+const RESIZE_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Continuously show the last lines of the selected tasks on an alternate screen, until the user
+/// presses Ctrl-C.
+///
+/// Each task gets a fixed window with a header and its last lines, which is re-rendered every
+/// `interval`. If no amount of `lines` is given, the height of the terminal is split between the
+/// tasks.
+pub async fn watch_logs(
+    client: &mut Client,
+    settings: &Settings,
+    style: &OutputStyle,
+    selection: TaskSelection,
+    interval: Duration,
+    options: &LogOptions,
+) -> Result<()> {
+    if !io::stdout().is_terminal() {
+        bail!("Watching logs requires stdout to be a terminal");
+    }
+    // The amount of lines is only fixed if it's explicitly requested.
+    let lines = options.lines.and_then(LineAmount::fixed);
+    let stream = options.stream;
+
+    // The screen has to be restored on Ctrl-C, which is why the process mustn't be killed.
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_running = running.clone();
+    ctrlc::set_handler(move || handler_running.store(false, Ordering::SeqCst))
+        .context("Failed to set Ctrl-C handler")?;
+
+    let screen = AlternateScreen::enter()?;
+    let mut size = terminal::size()?;
+    let mut next_render = Instant::now();
+    while running.load(Ordering::SeqCst) {
+        let current_size = terminal::size()?;
+        if Instant::now() >= next_render || current_size != size {
+            size = current_size;
+            let response = request_logs(client, settings, &selection, stream, size.1).await?;
+            let Response::Log(task_logs) = response else {
+                // Leave the alternate screen first, so the failure stays visible.
+                drop(screen);
+                handle_response(style, response)?;
+                return Ok(());
+            };
+            render(&task_logs, settings, style, lines, stream, size.0, size.1)?;
+            next_render = Instant::now() + interval;
+        }
+
+        sleep(RESIZE_CHECK_INTERVAL).await;
+    }
+
+    Ok(())
+}
+
+/// Request the selected tasks and their output from the daemon.
+///
+/// If logs aren't read locally, the daemon sends enough lines to fill the whole terminal.
+async fn request_logs(
+    client: &mut Client,
+    settings: &Settings,
+    selection: &TaskSelection,
+    stream: Option<OutputStream>,
+    rows: u16,
+) -> Result<Response> {
+    client
+        .send_request(LogRequest {
+            tasks: selection.clone(),
+            send_logs: !settings.client.read_local_logs,
+            lines: Some(usize::from(rows)),
+            head: None,
+            tail: None,
+            stream,
+            line_numbers: false,
+            chunked: false,
+            count: false,
+            compression: settings.shared.log_compression,
+            compression_level: settings.shared.log_compression_level,
+            task_lines: BTreeMap::new(),
+        })
+        .await?;
+
+    Ok(client.receive_response().await?)
+}
+
+/// Render a single frame, which shows a window with the last lines of each task.
+fn render(
+    task_logs: &BTreeMap<usize, TaskLogResponse>,
+    settings: &Settings,
+    style: &OutputStyle,
+    lines: Option<usize>,
+    stream: Option<OutputStream>,
+    columns: u16,
+    rows: u16,
+) -> Result<()> {
+    let width = usize::from(columns);
+    let rows = usize::from(rows);
+    // Each task takes up one row for its header.
+    let window = lines.unwrap_or_else(|| {
+        let tasks = task_logs.len().max(1);
+        (rows.saturating_sub(tasks) / tasks).max(1)
+    });
+
+    let mut frame = Vec::new();
+    if task_logs.is_empty() {
+        frame.push("There are no tasks to watch".to_string());
+    }
+    for task_log in task_logs.values() {
+        let task = &task_log.task;
+        let (status, color) = task_status(task);
+        let header = fit_line(
+            &format!("Task {} ({status}): {}", task.id, task.command),
+            width,
+        );
+        frame.push(style.style_text(header, Some(color), Some(Attribute::Bold)));
+
+        if !has_log(task) {
+            continue;
+        }
+        let output = match task_output(task_log, settings, stream, window) {
+            Ok(output) => output,
+            Err(err) => format!("(Pueue error) Failed to read log output: {err}"),
+        };
+        let output_lines: Vec<&str> = output.lines().collect();
+        let start = output_lines.len().saturating_sub(window);
+        frame.extend(
+            output_lines[start..]
+                .iter()
+                .map(|line| fit_line(line, width)),
+        );
+    }
+    // Lines that don't fit on the screen would scroll the view.
+    frame.truncate(rows);
+
+    let mut stdout = io::stdout().lock();
+    queue!(stdout, MoveTo(0, 0), Clear(ClearType::All))?;
+    stdout.write_all(frame.join("\n").as_bytes())?;
+    stdout.flush()?;
+
+    Ok(())
+}
+
+/// Get the last `lines` of a task's output, either from its local log file or from the output
+/// that has been sent by the daemon.
+fn task_output(
+    task_log: &TaskLogResponse,
+    settings: &Settings,
+    stream: Option<OutputStream>,
+    lines: usize,
+) -> Result<String> {
+    let mut output = Vec::new();
+    if settings.client.read_local_logs {
+        let (mut file, _) = get_stream_log_file_handle(
+            task_log.task.id,
+            stream,
+            &settings.shared.pueue_directory(),
+        )?;
+        seek_to_last_lines(&mut file, lines)?;
+        file.read_to_end(&mut output)?;
+    } else if let Some(bytes) = task_log.output.as_ref() {
+        task_log
+            .compression
+            .decompressor(bytes.as_slice())?
+            .read_to_end(&mut output)?;
+    }
+
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+/// Make a line of output fit into a single row of the terminal.
+///
+/// Escape sequences are removed, as they could move the cursor or leave the terminal in a
+/// different style. Only the text after the last carriage return is kept, just like it's shown
+/// by the terminal, and tabs are expanded so the line can be cut at the terminal's width.
+fn fit_line(line: &str, width: usize) -> String {
+    let line = line.rsplit('\r').next().unwrap_or_default();
+    let sequences = escape_sequences(line);
+
+    let mut fitted = String::new();
+    let mut column = 0;
+    for (index, character) in line.char_indices() {
+        if sequences.iter().any(|sequence| sequence.contains(&index)) {
+            continue;
+        }
+        let next_column = advance_char(column, character);
+        if next_column > width {
+            break;
+        }
+        match character {
+            '\t' => fitted.extend(std::iter::repeat_n(' ', next_column - column)),
+            _ => fitted.push(character),
+        }
+        column = next_column;
+    }
+
+    fitted
+}
+
+/// Shows the alternate screen of the terminal, until it's dropped.
+/// The previous content of the terminal is restored afterwards, even if an error occurred.
+struct AlternateScreen;
+
+impl AlternateScreen {
+    fn enter() -> Result<Self> {
+        execute!(io::stdout(), EnterAlternateScreen, Hide)
+            .context("Failed to enter the alternate screen")?;
+
+        Ok(Self)
+    }
+}
+
+impl Drop for AlternateScreen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn fit_lines() {
+        assert_eq!(fit_line("\x1b[31mred\x1b[0m text", 6), "red te");
+        assert_eq!(fit_line("a\tb", 20), "a       b");
+        assert_eq!(fit_line("progress 10%\rprogress 20%", 20), "progress 20%");
+    }
+}
@@ -0,0 +1,99 @@
+use std::io::Read;
+
+use pueue_lib::{
+    Client,
+    log::{get_log_file_handle, seek_to_last_lines},
+    message::{LogRequest, Response, TaskSelection},
+    settings::Settings,
+    task::Task,
+};
+
+use super::{has_log, log_file_error};
+use crate::internal_prelude::*;
+
+/// The log output of a single task, together with the task itself.
+#[derive(Clone, Debug)]
+pub struct TaskLog {
+    pub task: Task,
+    /// The decompressed output of the task, without any styling or task information.
+    pub output: Vec<u8>,
+    /// Whether this is the whole output or only its last lines.
+    pub output_complete: bool,
+}
+
+/// Fetch the log output of a single task, without printing anything.
+///
+/// This is intended for frontends that are built on top of the client and want to display
+/// the output by themselves.
+/// The local log file is read, if `client.read_local_logs` is set. Otherwise, the daemon sends
+/// the output. Only the last `lines` lines are fetched, unless `lines` is `None`.
+/// Tasks that haven't been started yet don't have any output.
+pub async fn fetch_task_log(
+    client: &mut Client,
+    settings: &Settings,
+    task_id: usize,
+    lines: Option<usize>,
+) -> Result<TaskLog> {
+    let read_local_logs = settings.client.read_local_logs;
+    client
+        .send_request(LogRequest {
+            tasks: TaskSelection::TaskIds(vec![task_id]),
+            send_logs: !read_local_logs,
+            lines,
+            head: None,
+            tail: None,
+            stream: None,
+            line_numbers: false,
+            chunked: false,
+            count: false,
+            compression: settings.shared.log_compression,
+            compression_level: settings.shared.log_compression_level,
+            task_lines: Default::default(),
+        })
+        .await?;
+
+    let mut task_logs = match client.receive_response().await? {
+        Response::Log(task_logs) => task_logs,
+        Response::Failure(message) => bail!(message),
+        response => bail!("Received unexpected response: {response:?}"),
+    };
+    let Some(task_log) = task_logs.remove(&task_id) else {
+        bail!("There's no task with id {task_id}");
+    };
+
+    let mut output = Vec::new();
+    if !has_log(&task_log.task) {
+        return Ok(TaskLog {
+            task: task_log.task,
+            output,
+            output_complete: true,
+        });
+    }
+
+    let output_complete = if read_local_logs {
+        let mut file = get_log_file_handle(task_id, &settings.shared.pueue_directory())
+            .map_err(|err| eyre!(log_file_error(task_id, &err)))?;
+        let output_complete = match lines {
+            Some(lines) => seek_to_last_lines(&mut file, lines)?,
+            None => true,
+        };
+        file.read_to_end(&mut output)
+            .context("Failed to read local log file")?;
+        output_complete
+    } else {
+        if let Some(bytes) = &task_log.output {
+            task_log
+                .compression
+                .decompressor(bytes.as_slice())?
+                .read_to_end(&mut output)
+                .context("Failed to decompress remote log output")?;
+        }
+        task_log.output_complete
+    };
+
+    Ok(TaskLog {
+        task: task_log.task,
+        output,
+        output_complete,
+    })
+}
@@ -0,0 +1,139 @@
+use std::{
+    fs::File,
+    io::{self, IsTerminal, Write},
+    path::Path,
+};
+
+use crossterm::style::{Attribute, Color};
+use pueue_lib::{log::count_total_lines, settings::Settings};
+
+use super::{
+    HeaderTarget, LineAmount, LineLevels, LineTimestamps, LineWrap, LogOptions, StripAnsi,
+    TaskRender, determine_log_line_amount,
+    local::{LocalLines, print_local_file},
+    pager::Pager,
+};
+use crate::{client::style::OutputStyle, internal_prelude::*};
+
+/// Print an arbitrary log file, e.g. one that has been copied off another machine.
+///
+/// The file is formatted just like the local log of a task, but there's no task whose
+/// information could be shown. `-` reads the log from stdin.
+pub fn print_log_file(
+    settings: &Settings,
+    style: &OutputStyle,
+    path: &Path,
+    options: LogOptions,
+) -> Result<()> {
+    let LogOptions {
+        lines,
+        head,
+        max_bytes,
+        full,
+        ref timestamps,
+        wrap,
+        color_levels,
+        strip_ansi,
+        header_to_stdout,
+        plain,
+        no_task_info,
+        binary,
+        pager,
+        ..
+    } = options;
+
+    let stdin = path == Path::new("-");
+    let mut file = if stdin {
+        // The output is read multiple times, which isn't possible with stdin.
+        let mut file = tempfile::tempfile().context("Failed to create temporary file")?;
+        io::copy(&mut io::stdin().lock(), &mut file).context("Failed to read log from stdin")?;
+        file
+    } else {
+        File::open(path).wrap_err_with(|| format!("Failed to open log file {path:?}"))?
+    };
+
+    // Percentages refer to the length of the whole file.
+    let lines = match lines {
+        Some(LineAmount::Percent(_)) => {
+            let total_lines = count_total_lines(&mut file).context("Failed to count lines")?;
+            lines.map(|lines| lines.of(total_lines))
+        }
+        lines => lines.and_then(LineAmount::fixed),
+    };
+    let lines = determine_log_line_amount(
+        full,
+        &lines,
+        head.is_some() || max_bytes.is_some(),
+        settings.client.default_log_lines,
+    );
+    let timestamps = timestamps.as_ref().map(LineTimestamps::file);
+
+    let mut pager = if pager {
+        Pager::start(style.enabled)
+    } else {
+        None
+    };
+    let wrap = if wrap { LineWrap::detect() } else { None };
+    let levels = color_levels
+        .then(|| LineLevels::new(settings, style))
+        .flatten();
+    let header_target = if no_task_info || plain {
+        HeaderTarget::Hidden
+    } else if header_to_stdout || pager.is_some() {
+        HeaderTarget::Output
+    } else {
+        HeaderTarget::Stderr
+    };
+    // The file takes the place of the task information.
+    let name = if stdin {
+        "stdin".into()
+    } else {
+        path.display().to_string()
+    };
+    let header = style.style_text(
+        format!("{name}:"),
+        Some(Color::Green),
+        Some(Attribute::Bold),
+    );
+
+    let mut stdout = io::stdout();
+    let out: &mut dyn Write = match pager.as_mut() {
+        Some(pager) => pager,
+        None => &mut stdout,
+    };
+    let mut stripped;
+    let out: &mut dyn Write = if strip_ansi {
+        stripped = StripAnsi::new(out);
+        &mut stripped
+    } else {
+        out
+    };
+
+    let render = TaskRender {
+        lines,
+        wrap,
+        levels: levels.as_ref(),
+        hide_binary: binary.hide(io::stdout().is_terminal()),
+        ..Default::default()
+    };
+    let local_lines = LocalLines {
+        timestamps,
+        ..Default::default()
+    };
+    print_local_file(
+        out,
+        &mut file,
+        &options,
+        &render,
+        &local_lines,
+        header,
+        header_target,
+    );
+    out.flush()?;
+
+    if let Some(pager) = pager {
+        pager.finish()?;
+    }
+
+    Ok(())
+}
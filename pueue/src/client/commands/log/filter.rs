@@ -0,0 +1,129 @@
+use std::{borrow::Cow, ops::Range};
+
+use crossterm::style::{Attribute, Color};
+use regex::{Regex, RegexBuilder};
+
+use super::{LineContext, ansi::escape_sequences};
+use crate::{client::style::OutputStyle, internal_prelude::*};
+
+/// Only show log lines that match a regular expression.
+#[derive(Clone, Debug)]
+pub struct LineFilter {
+    regex: Regex,
+    /// Show the lines that **don't** match instead.
+    invert_match: bool,
+    /// The style that's used to highlight matches.
+    /// This is only set if styling is enabled, as there's nothing to highlight otherwise.
+    highlight_style: Option<OutputStyle>,
+    /// The amount of lines that are shown in front of each match.
+    before_context: usize,
+    /// The amount of lines that are shown after each match.
+    after_context: usize,
+}
+
+impl LineFilter {
+    pub fn new(
+        pattern: &str,
+        invert_match: bool,
+        ignore_case: bool,
+        style: &OutputStyle,
+    ) -> Result<Self> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|err| eyre!("Invalid filter regex: {err}"))?;
+
+        // Inverted matches don't contain anything that could be highlighted.
+        let highlight_style = (style.enabled && !invert_match).then(|| style.clone());
+
+        Ok(Self {
+            regex,
+            invert_match,
+            highlight_style,
+            before_context: 0,
+            after_context: 0,
+        })
+    }
+
+    /// Show some lines in front of and after each match as well.
+    pub fn with_context(mut self, before: usize, after: usize) -> Self {
+        self.before_context = before;
+        self.after_context = after;
+        self
+    }
+
+    /// Create the state that's needed to show the lines around each match.
+    /// Returns `None` if no such lines should be shown.
+    pub fn context<T>(&self) -> Option<LineContext<T>> {
+        (self.before_context > 0 || self.after_context > 0)
+            .then(|| LineContext::new(self.before_context, self.after_context))
+    }
+
+    /// Check whether a line should be shown.
+    /// The line is expected to not contain the trailing newline.
+    pub fn matches(&self, line: &str) -> bool {
+        self.regex.is_match(line) != self.invert_match
+    }
+
+    /// Check whether a line should be shown and highlight all matches in it.
+    /// Returns `None` if the line should be hidden.
+    pub fn apply<'a>(&self, line: &'a str) -> Option<Cow<'a, str>> {
+        if !self.matches(line) {
+            return None;
+        }
+
+        let Some(style) = &self.highlight_style else {
+            return Some(Cow::Borrowed(line));
+        };
+
+        // Matches within the task's own escape sequences, such as colors, aren't highlighted,
+        // as this would break those sequences.
+        let escapes = if line.contains('\x1b') {
+            escape_sequences(line)
+        } else {
+            Vec::new()
+        };
+
+        // Merge adjacent matches, so they're highlighted as a single span.
+        let mut spans: Vec<Range<usize>> = Vec::new();
+        for found in self.regex.find_iter(line) {
+            if found.is_empty() {
+                continue;
+            }
+            if escapes
+                .iter()
+                .any(|escape| escape.start < found.end() && found.start() < escape.end)
+            {
+                continue;
+            }
+            match spans.last_mut() {
+                Some(last) if found.start() <= last.end => last.end = last.end.max(found.end()),
+                _ => spans.push(found.range()),
+            }
+        }
+
+        let mut highlighted = String::with_capacity(line.len());
+        let mut position = 0;
+        for span in spans {
+            highlighted.push_str(&line[position..span.start]);
+            highlighted.push_str(&style.style_text(
+                &line[span.clone()],
+                Some(Color::Red),
+                Some(Attribute::Bold),
+            ));
+            position = span.end;
+        }
+        highlighted.push_str(&line[position..]);
+
+        Some(Cow::Owned(highlighted))
+    }
+}
+
+/// Apply an optional filter to a line.
+/// Returns `None` if the line should be hidden.
+pub fn filter_line<'a>(filter: Option<&LineFilter>, line: &'a str) -> Option<Cow<'a, str>> {
+    match filter {
+        Some(filter) => filter.apply(line),
+        None => Some(Cow::Borrowed(line)),
+    }
+}
@@ -1,121 +1,725 @@
 use std::{
     collections::{BTreeMap, HashMap},
-    io::Read,
+    io::{self, Read, Seek, SeekFrom, Write},
 };
 
-use chrono::Local;
 use pueue_lib::{
-    log::{get_log_file_handle, read_last_lines},
+    log::{LogCompression, decode_line, get_log_file_handle, read_last_lines},
     message::TaskLogResponse,
     settings::Settings,
     task::Task,
 };
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
-use snap::read::FrameDecoder;
+use serde_json::ser::{CharEscape, CompactFormatter, Formatter, PrettyFormatter};
+use strum::EnumString;
 
-/// This is the output struct used for
+use super::{
+    LineFilter, LineTimestamps, LineWindow, LogChunks, LogOptions, OMISSION_MARKER, TaskLines,
+    last_bytes_start, local_log_ranges, log_file_error, trim_head_and_tail,
+};
+use crate::internal_prelude::*;
+
+/// Determines whether the environment variables of tasks are included in the json output.
+#[derive(Clone, Debug, Default)]
+pub enum JsonEnvs {
+    /// The environment variables are removed.
+    #[default]
+    Hidden,
+    /// The values of all variables whose names match the regex are redacted.
+    Redacted(Regex),
+    /// The environment variables are included as they are.
+    Raw,
+}
+
+impl JsonEnvs {
+    /// Include the environment variables and redact any secrets.
+    /// Secrets are determined by the `client.env_secret_patterns` setting.
+    pub fn redacted(settings: &Settings) -> Result<Self> {
+        // Convert the wildcard patterns into a single regex.
+        let patterns: Vec<String> = settings
+            .client
+            .env_secret_patterns
+            .iter()
+            .map(|pattern| {
+                pattern
+                    .split('*')
+                    .map(regex::escape)
+                    .collect::<Vec<_>>()
+                    .join(".*")
+            })
+            .collect();
+        let regex = RegexBuilder::new(&format!("^(?:{})$", patterns.join("|")))
+            .case_insensitive(true)
+            .build()
+            .map_err(|err| eyre!("Invalid client.env_secret_patterns setting: {err}"))?;
+
+        Ok(JsonEnvs::Redacted(regex))
+    }
+
+    /// Adjust the environment variables of a task, before it's serialized.
+    fn apply(&self, task: &mut Task) {
+        match self {
+            JsonEnvs::Hidden => task.envs = HashMap::new(),
+            JsonEnvs::Redacted(secrets) => {
+                for (key, value) in task.envs.iter_mut() {
+                    if secrets.is_match(key) {
+                        *value = "[REDACTED]".to_string();
+                    }
+                }
+            }
+            JsonEnvs::Raw => (),
+        }
+    }
+}
+
+/// Determines the keys of the top-level object that's printed by `--json`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, EnumString)]
+pub enum JsonGroupBy {
+    /// Each task is keyed by its id: `{"<id>": {...}}`.
+    #[default]
+    #[strum(serialize = "id")]
+    Id,
+    /// The tasks are nested by their group: `{"<group>": {"<id>": {...}}}`.
+    #[strum(serialize = "group")]
+    Group,
+    /// The tasks are nested by their label: `{"<label>": {"<id>": {...}}}`.
+    /// Tasks without a label are nested under an empty label.
+    #[strum(serialize = "label")]
+    Label,
+}
+
+impl JsonGroupBy {
+    /// The key of the object that a task is nested in, if tasks are grouped at all.
+    fn key(self, task: &Task) -> Option<String> {
+        match self {
+            JsonGroupBy::Id => None,
+            JsonGroupBy::Group => Some(task.group.clone()),
+            JsonGroupBy::Label => Some(task.label.clone().unwrap_or_default()),
+        }
+    }
+}
+
+/// The version of the records that're printed by `--json` and `--json-lines`.
+///
+/// This needs to be increased whenever fields of the records are changed or removed, so
+/// consumers can tell which format they're dealing with.
+pub const LOG_JSON_SCHEMA: u32 = 1;
+
+/// This is the output struct used for each line of `--json-lines`.
 #[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct TaskLog {
+pub struct TaskLogRecord {
+    /// See [LOG_JSON_SCHEMA].
+    pub schema: u32,
+    pub id: usize,
     pub task: Task,
     pub output: String,
+    /// Why the output couldn't be read, in which case `output` is empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The output of a task, or the reason why it couldn't be read.
+type LogOutput = std::result::Result<String, String>;
+
+/// Writes the objects of `--json` either compactly or indented via `--json-pretty`.
+///
+/// The objects are written piece by piece, so the output can be streamed into them.
+enum JsonFormatter {
+    Compact(CompactFormatter),
+    Pretty(PrettyFormatter<'static>),
+}
+
+impl JsonFormatter {
+    /// Write the key of an object's value, followed by the separator in front of the value.
+    fn write_key(&mut self, out: &mut impl Write, key: &str, first: bool) -> io::Result<()> {
+        self.begin_object_key(out, first)?;
+        serde_json::to_writer(&mut *out, key)?;
+        self.end_object_key(out)?;
+        self.begin_object_value(out)
+    }
+
+    /// Write a complete value, which is nested `depth` objects deep.
+    fn write_nested(
+        &self,
+        out: &mut impl Write,
+        value: &impl Serialize,
+        depth: usize,
+    ) -> io::Result<()> {
+        match self {
+            JsonFormatter::Compact(_) => serde_json::to_writer(out, value)?,
+            JsonFormatter::Pretty(_) => {
+                // Newlines within strings are escaped, so each newline starts a new line of the
+                // value, which needs to be indented to the depth of the value.
+                let value = serde_json::to_string_pretty(value)?;
+                out.write_all(
+                    value
+                        .replace('\n', &format!("\n{}", "  ".repeat(depth)))
+                        .as_bytes(),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Formatter for JsonFormatter {
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        match self {
+            JsonFormatter::Compact(formatter) => formatter.begin_object(writer),
+            JsonFormatter::Pretty(formatter) => formatter.begin_object(writer),
+        }
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        match self {
+            JsonFormatter::Compact(formatter) => formatter.end_object(writer),
+            JsonFormatter::Pretty(formatter) => formatter.end_object(writer),
+        }
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        match self {
+            JsonFormatter::Compact(formatter) => formatter.begin_object_key(writer, first),
+            JsonFormatter::Pretty(formatter) => formatter.begin_object_key(writer, first),
+        }
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        match self {
+            JsonFormatter::Compact(formatter) => formatter.begin_object_value(writer),
+            JsonFormatter::Pretty(formatter) => formatter.begin_object_value(writer),
+        }
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        match self {
+            JsonFormatter::Compact(formatter) => formatter.end_object_value(writer),
+            JsonFormatter::Pretty(formatter) => formatter.end_object_value(writer),
+        }
+    }
 }
 
 /// Print some log output in JSON serialized form.
 ///
-/// If the log isn't read from the disk but rather received from the daemon, we have to
-/// convert the received [TaskLogResponse] into a proper JSON serializable format.
-/// Output in [TaskLogResponse], is usually compressed, so we need to decompress it first.
-pub fn print_log_json(
-    task_log_messages: BTreeMap<usize, TaskLogResponse>,
+/// The JSON object maps the id of each task to an object with the `schema` version, its `task`
+/// and its `output`. If the output couldn't be read, the reason is added as `error`.
+/// It's written incrementally, one task at a time.
+/// Output that's received from the daemon is decompressed straight into the JSON string, unless
+/// its lines need to be processed. That way, the output of all tasks never has to be kept in
+/// memory at once. If such output turns out to be corrupted, whatever has been decompressed
+/// before is kept in the `output`.
+/// If `json_pretty` is set, the JSON is indented, while the output is still written
+/// incrementally.
+///
+/// With `json_group_by`, the tasks are nested in objects that are keyed by their group or label, see
+/// [JsonGroupBy]. The output is then received as a whole, as chunks are sent in order of the
+/// task ids.
+pub async fn print_log_json(
+    task_log_messages: &BTreeMap<usize, TaskLogResponse>,
     settings: &Settings,
-    lines: Option<usize>,
-    timestamps: bool,
-) {
-    let mut tasks: BTreeMap<usize, Task> = BTreeMap::new();
-    let mut task_log: BTreeMap<usize, String> = BTreeMap::new();
-    for (id, message) in task_log_messages {
-        tasks.insert(id, message.task);
+    options: &LogOptions,
+    lines: &TaskLines,
+    chunks: &mut LogChunks<'_>,
+) -> Result<()> {
+    let group_by = options.json_group_by;
+    // The remote output can only be streamed, if it doesn't need to be trimmed or processed.
+    let stream_remote = options.head.is_none()
+        && options.max_bytes.is_none()
+        && options.timestamps.is_none()
+        && options.filter.is_none();
+
+    let mut stdout = io::stdout().lock();
+    let mut formatter = if options.json_pretty {
+        JsonFormatter::Pretty(PrettyFormatter::new())
+    } else {
+        JsonFormatter::Compact(CompactFormatter)
+    };
+    // Tasks of the same group or label are written one after another, in order of their ids.
+    let mut entries: Vec<(Option<String>, usize, &TaskLogResponse)> = task_log_messages
+        .iter()
+        .map(|(id, message)| (group_by.key(&message.task), *id, message))
+        .collect();
+    entries.sort_by(|(key, ..), (other_key, ..)| key.cmp(other_key));
+    let depth = if group_by == JsonGroupBy::Id { 2 } else { 3 };
+
+    formatter.begin_object(&mut stdout)?;
+    let mut current_key: Option<&str> = None;
+    let mut first = true;
+    for (key, id, message) in &entries {
+        if let Some(key) = key.as_deref() {
+            if current_key != Some(key) {
+                if current_key.is_some() {
+                    formatter.end_object(&mut stdout)?;
+                    formatter.end_object_value(&mut stdout)?;
+                }
+                formatter.write_key(&mut stdout, key, current_key.is_none())?;
+                formatter.begin_object(&mut stdout)?;
+                current_key = Some(key);
+                first = true;
+            }
+        }
+        // Map keys are always strings in JSON.
+        formatter.write_key(&mut stdout, &id.to_string(), first)?;
+        first = false;
 
-        if settings.client.read_local_logs {
-            let output = get_local_log(settings, id, lines, timestamps);
-            task_log.insert(id, output);
+        let mut task = message.task.clone();
+        options.envs.apply(&mut task);
+        formatter.begin_object(&mut stdout)?;
+        formatter.write_key(&mut stdout, "schema", true)?;
+        write!(stdout, "{LOG_JSON_SCHEMA}")?;
+        formatter.end_object_value(&mut stdout)?;
+        formatter.write_key(&mut stdout, "task", false)?;
+        formatter.write_nested(&mut stdout, &task, depth)?;
+        formatter.end_object_value(&mut stdout)?;
+        formatter.write_key(&mut stdout, "output", false)?;
+
+        let mut output = JsonStringWriter::new(&mut stdout)?;
+        let log = if settings.client.read_local_logs {
+            get_local_log(settings, *id, lines.get(*id), options)
+        } else if !stream_remote {
+            if message.output.is_some() {
+                // Older daemons send the output as a single payload.
+                get_remote_log(message, lines.get(*id), options)
+            } else {
+                get_remote_log_chunks(message, chunks, lines.get(*id), options).await?
+            }
+        } else if let Some(bytes) = message.output.as_deref() {
+            decompress_into(bytes, message.compression, &mut output)?
         } else {
-            let output = get_remote_log(message.output, timestamps);
-            task_log.insert(id, output);
+            let mut log = Ok(String::new());
+            while let Some(bytes) = chunks.next(message.task.id).await? {
+                log = decompress_into(&bytes, message.compression, &mut output)?;
+                if log.is_err() {
+                    break;
+                }
+            }
+            match chunks.take_error(message.task.id) {
+                Some(error) => Err(error),
+                None => log,
+            }
+        };
+        let error = match log {
+            Ok(log) => {
+                output.write_all(log.as_bytes())?;
+                None
+            }
+            Err(error) => Some(error),
+        };
+        output.finish()?;
+        formatter.end_object_value(&mut stdout)?;
+        if let Some(error) = error {
+            formatter.write_key(&mut stdout, "error", false)?;
+            serde_json::to_writer(&mut stdout, &error)?;
+            formatter.end_object_value(&mut stdout)?;
         }
+
+        formatter.end_object(&mut stdout)?;
+        formatter.end_object_value(&mut stdout)?;
+    }
+    if current_key.is_some() {
+        formatter.end_object(&mut stdout)?;
+        formatter.end_object_value(&mut stdout)?;
     }
+    formatter.end_object(&mut stdout)?;
+    writeln!(stdout)?;
+
+    Ok(())
+}
 
-    // Now assemble the final struct that will be returned
-    let mut json = BTreeMap::new();
-    for (id, mut task) in tasks {
-        let (id, output) = task_log.remove_entry(&id).unwrap();
+/// Print the log output of each task as a separate JSON record on its own line.
+///
+/// Contrary to [print_log_json], only the output of a single task is kept in memory at a time
+/// and each record is printed as soon as it's ready.
+pub async fn print_log_json_lines(
+    task_log_messages: &BTreeMap<usize, TaskLogResponse>,
+    settings: &Settings,
+    options: &LogOptions,
+    lines: &TaskLines,
+    chunks: &mut LogChunks<'_>,
+) -> Result<()> {
+    for (id, message) in task_log_messages {
+        let log = if settings.client.read_local_logs {
+            get_local_log(settings, *id, lines.get(*id), options)
+        } else if message.output.is_some() {
+            // Older daemons send the output as a single payload.
+            get_remote_log(message, lines.get(*id), options)
+        } else {
+            get_remote_log_chunks(message, chunks, lines.get(*id), options).await?
+        };
 
-        task.envs = HashMap::new();
-        json.insert(id, TaskLog { task, output });
+        let mut task = message.task.clone();
+        options.envs.apply(&mut task);
+        let (output, error) = match log {
+            Ok(output) => (output, None),
+            Err(error) => (String::new(), Some(error)),
+        };
+        let record = TaskLogRecord {
+            schema: LOG_JSON_SCHEMA,
+            id: *id,
+            task,
+            output,
+            error,
+        };
+        writeln!(io::stdout(), "{}", serde_json::to_string(&record)?)?;
     }
 
-    println!("{}", serde_json::to_string(&json).unwrap());
+    Ok(())
 }
 
 /// Read logs directly from local files for a specific task.
-fn get_local_log(settings: &Settings, id: usize, lines: Option<usize>, timestamps: bool) -> String {
-    let mut file = match get_log_file_handle(id, &settings.shared.pueue_directory()) {
-        Ok(file) => file,
-        Err(err) => {
-            return format!("(Pueue error) Failed to get log file handle: {err}");
-        }
-    };
+fn get_local_log(
+    settings: &Settings,
+    id: usize,
+    lines: Option<usize>,
+    options: &LogOptions,
+) -> LogOutput {
+    let (head, max_bytes, filter) = (options.head, options.max_bytes, options.filter.as_ref());
+    let mut file = get_log_file_handle(id, &settings.shared.pueue_directory())
+        .map_err(|err| log_file_error(id, &err))?;
+
+    let window = options
+        .window
+        .as_ref()
+        .and_then(|window| LineWindow::local(id, settings, window));
+    let timestamps = options
+        .timestamps
+        .as_ref()
+        .map(|timestamps| LineTimestamps::local(id, settings, timestamps));
+
+    // Lines only need to be processed one by one, if they're filtered or timestamped.
+    let process_lines = timestamps.is_some() || filter.is_some() || window.is_some();
 
     // Only return the last few lines.
-    if let Some(lines) = lines {
-        let content = read_last_lines(&mut file, lines);
-        if timestamps {
-            return add_timestamps_to_string(&content);
-        }
-        return content;
+    if let (None, Some(lines), None, false) = (head, lines, max_bytes, process_lines) {
+        return Ok(read_last_lines(&mut file, lines));
     }
 
-    // Read the whole local log output.
-    let mut output = String::new();
-    if let Err(error) = file.read_to_string(&mut output) {
-        return format!("(Pueue error) Failed to read local log output file: {error:?}");
-    };
+    // Only return the first and/or last few lines.
+    let read_error =
+        |error: &dyn std::fmt::Display| format!("Failed to read local log output file: {error}");
+    let (ranges, _) =
+        local_log_ranges(&mut file, head, lines, max_bytes).map_err(|err| read_error(&err))?;
 
-    if timestamps {
-        add_timestamps_to_string(&output)
-    } else {
-        output
+    let mut parts = Vec::new();
+    for range in ranges {
+        file.seek(SeekFrom::Start(range.start))
+            .map_err(|err| read_error(&err))?;
+
+        let mut output = Vec::new();
+        (&mut file)
+            .take(range.end - range.start)
+            .read_to_end(&mut output)
+            .map_err(|err| read_error(&err))?;
+
+        // The start of the range is needed to look up the timestamps.
+        if process_lines {
+            parts.push(filter_and_timestamp_lines(
+                &output,
+                range.start,
+                timestamps.as_ref(),
+                filter,
+                window.as_ref(),
+            ));
+        } else {
+            parts.push(String::from_utf8_lossy(&output).into_owned());
+        }
     }
+
+    Ok(join_omitted(parts, process_lines))
 }
 
 /// Read logs from from compressed remote logs.
 /// If logs don't exist, an empty string will be returned.
-fn get_remote_log(output_bytes: Option<Vec<u8>>, timestamps: bool) -> String {
-    let Some(bytes) = output_bytes else {
-        return String::new();
+fn get_remote_log(
+    message: &TaskLogResponse,
+    lines: Option<usize>,
+    options: &LogOptions,
+) -> LogOutput {
+    let Some(bytes) = message.output.as_deref() else {
+        return Ok(String::new());
     };
 
-    let mut decoder = FrameDecoder::new(&bytes[..]);
-    let mut output = String::new();
-    if let Err(error) = decoder.read_to_string(&mut output) {
-        return format!("(Pueue error) Failed to decompress remote log output: {error:?}");
+    let mut output = Vec::new();
+    message
+        .compression
+        .decompressor(bytes)
+        .and_then(|mut decoder| decoder.read_to_end(&mut output))
+        .map_err(|err| decompression_error(&err))?;
+
+    Ok(process_remote_log(output, message, lines, options))
+}
+
+/// Decompress a chunk of remote log output into the given writer.
+///
+/// If the output can't be decompressed, the error message is returned as the [LogOutput].
+/// Only errors of the writer itself are returned as errors.
+fn decompress_into(
+    bytes: &[u8],
+    compression: LogCompression,
+    writer: &mut impl Write,
+) -> io::Result<LogOutput> {
+    let mut decoder = match compression.decompressor(bytes) {
+        Ok(decoder) => decoder,
+        Err(error) => return Ok(Err(decompression_error(&error))),
+    };
+    let mut buffer = [0; 8192];
+    loop {
+        let read = match decoder.read(&mut buffer) {
+            Ok(0) => return Ok(Ok(String::new())),
+            Ok(read) => read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Ok(Err(decompression_error(&error))),
+        };
+        writer.write_all(&buffer[..read])?;
     }
+}
 
-    if timestamps {
-        add_timestamps_to_string(&output)
+fn decompression_error(error: &io::Error) -> String {
+    format!("Failed to decompress remote log output: {error}")
+}
+
+/// Read logs that're received from the daemon in a series of compressed chunks.
+async fn get_remote_log_chunks(
+    message: &TaskLogResponse,
+    chunks: &mut LogChunks<'_>,
+    lines: Option<usize>,
+    options: &LogOptions,
+) -> Result<LogOutput> {
+    let mut output = Vec::new();
+    while let Some(bytes) = chunks.next(message.task.id).await? {
+        let result = message
+            .compression
+            .decompressor(bytes.as_slice())
+            .and_then(|mut decoder| decoder.read_to_end(&mut output));
+        if let Err(err) = result {
+            return Ok(Err(decompression_error(&err)));
+        }
+    }
+    if let Some(error) = chunks.take_error(message.task.id) {
+        return Ok(Err(error));
+    }
+
+    Ok(Ok(process_remote_log(output, message, lines, options)))
+}
+
+/// Filter and timestamp the decompressed output of a remote log, if requested.
+///
+/// If the first lines have been requested, they're separated from the last lines.
+/// Older daemons send the full output in that case, which is trimmed in here.
+/// The daemon doesn't know about the byte limit, which is why it's applied in here as well.
+fn process_remote_log(
+    output: Vec<u8>,
+    message: &TaskLogResponse,
+    lines: Option<usize>,
+    options: &LogOptions,
+) -> String {
+    let (head, max_bytes, filter) = (options.head, options.max_bytes, options.filter.as_ref());
+    let (mut output, head_size, output_complete) = match (head, message.head_size) {
+        (Some(head), None) => trim_head_and_tail(&output, head, lines),
+        (_, head_size) => (
+            output,
+            head_size.unwrap_or_default(),
+            message.output_complete,
+        ),
+    };
+    if let (None, Some(max_bytes)) = (head, max_bytes) {
+        output.drain(..last_bytes_start(&output, max_bytes));
+    }
+
+    let parts = if head.is_some() && lines.is_some() && !output_complete {
+        let (head, tail) = output.split_at((head_size as usize).min(output.len()));
+        vec![head, tail]
+    } else {
+        vec![output.as_slice()]
+    };
+
+    let process_lines = options.timestamps.is_some() || filter.is_some();
+    let timestamps = options.timestamps.as_ref().map(LineTimestamps::remote);
+    let parts = parts
+        .into_iter()
+        .map(|part| {
+            if process_lines {
+                filter_and_timestamp_lines(part, 0, timestamps.as_ref(), filter, None)
+            } else {
+                String::from_utf8_lossy(part).into_owned()
+            }
+        })
+        .collect();
+
+    join_omitted(parts, process_lines)
+}
+
+/// Join the first and the last lines of a task's output with the [OMISSION_MARKER].
+///
+/// Processed lines are joined without a trailing newline, which is why the marker needs its own
+/// leading newline in that case.
+fn join_omitted(parts: Vec<String>, process_lines: bool) -> String {
+    if process_lines {
+        parts.join(&format!("\n{OMISSION_MARKER}\n"))
     } else {
-        output
+        parts.join(&format!("{OMISSION_MARKER}\n"))
     }
 }
 
-/// Add timestamps to each line of the given string content.
-fn add_timestamps_to_string(content: &str) -> String {
+/// Filter the lines of the given string content and add timestamps to the remaining lines.
+///
+/// `offset` is the position of the content in the log file.
+/// The lines are split before decoding them, so invalid UTF-8 doesn't shift the offsets.
+fn filter_and_timestamp_lines(
+    content: &[u8],
+    mut offset: u64,
+    timestamps: Option<&LineTimestamps>,
+    filter: Option<&LineFilter>,
+    window: Option<&LineWindow>,
+) -> String {
     content
-        .lines()
-        .map(|line| {
-            let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-            format!("[{}] {}", timestamp, line)
+        .split_inclusive(|byte| *byte == b'\n')
+        .filter_map(|line| {
+            let line_offset = offset;
+            offset += line.len() as u64;
+            if window.is_some_and(|window| !window.contains(line_offset)) {
+                return None;
+            }
+
+            let line = decode_line(line);
+            if filter.is_some_and(|filter| !filter.matches(&line)) {
+                return None;
+            }
+
+            let (prefix, line) = match timestamps {
+                Some(timestamps) => timestamps.apply(line_offset, line),
+                None => (String::new(), line),
+            };
+            Some(format!("{prefix}{line}"))
         })
         .collect::<Vec<_>>()
         .join("\n")
 }
+
+/// A writer that writes its output as the content of a JSON string.
+///
+/// The output is escaped while it's written, so it doesn't need to be kept in memory.
+/// Invalid UTF-8 is replaced, just like [String::from_utf8_lossy] does. Characters that're split
+/// across multiple writes are kept until the rest of them arrives.
+struct JsonStringWriter<W: Write> {
+    inner: W,
+    /// The start of a character, whose remaining bytes haven't been written yet.
+    incomplete: Vec<u8>,
+}
+
+impl<W: Write> JsonStringWriter<W> {
+    /// Start the JSON string by writing its opening quote.
+    fn new(mut inner: W) -> io::Result<Self> {
+        CompactFormatter.begin_string(&mut inner)?;
+        Ok(Self {
+            inner,
+            incomplete: Vec::new(),
+        })
+    }
+
+    /// End the JSON string by writing its closing quote.
+    /// An incomplete character at the end of the output is replaced.
+    fn finish(mut self) -> io::Result<()> {
+        if !self.incomplete.is_empty() {
+            write_escaped(&mut self.inner, "\u{FFFD}")?;
+        }
+        CompactFormatter.end_string(&mut self.inner)
+    }
+}
+
+impl<W: Write> Write for JsonStringWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut bytes = std::mem::take(&mut self.incomplete);
+        bytes.extend_from_slice(buf);
+
+        let mut chunks = bytes.utf8_chunks().peekable();
+        while let Some(chunk) = chunks.next() {
+            write_escaped(&mut self.inner, chunk.valid())?;
+            let invalid = chunk.invalid();
+            if invalid.is_empty() {
+                continue;
+            }
+
+            // Invalid bytes at the very end might just be the start of a split character.
+            let incomplete = chunks.peek().is_none()
+                && std::str::from_utf8(invalid).is_err_and(|error| error.error_len().is_none());
+            if incomplete {
+                self.incomplete = invalid.to_vec();
+            } else {
+                write_escaped(&mut self.inner, "\u{FFFD}")?;
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Write the given text with all characters escaped, that need to be escaped in JSON strings.
+fn write_escaped(writer: &mut impl Write, text: &str) -> io::Result<()> {
+    let mut formatter = CompactFormatter;
+    let mut start = 0;
+    for (index, byte) in text.bytes().enumerate() {
+        let escape = match byte {
+            b'"' => CharEscape::Quote,
+            b'\\' => CharEscape::ReverseSolidus,
+            b'\x08' => CharEscape::Backspace,
+            b'\x0c' => CharEscape::FormFeed,
+            b'\n' => CharEscape::LineFeed,
+            b'\r' => CharEscape::CarriageReturn,
+            b'\t' => CharEscape::Tab,
+            0x00..=0x1f => CharEscape::AsciiControl(byte),
+            _ => continue,
+        };
+        formatter.write_string_fragment(writer, &text[start..index])?;
+        formatter.write_char_escape(writer, escape)?;
+        start = index + 1;
+    }
+
+    formatter.write_string_fragment(writer, &text[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn escape_like_serde() {
+        let text = "quote \" backslash \\ tab \t bell \x07 unicode ä\r\n";
+        let mut output = Vec::new();
+        let mut writer = JsonStringWriter::new(&mut output).unwrap();
+        writer.write_all(text.as_bytes()).unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            serde_json::to_string(text).unwrap()
+        );
+    }
+
+    #[test]
+    fn split_and_invalid_characters() {
+        let mut output = Vec::new();
+        let mut writer = JsonStringWriter::new(&mut output).unwrap();
+        // The `ä` is split across two writes, the `\xff` byte is invalid.
+        writer.write_all(b"a\xc3").unwrap();
+        writer.write_all(b"\xa4b\xffc\xe2\x82").unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            "\"a\u{e4}b\u{FFFD}c\u{FFFD}\""
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+
+use pueue_lib::task::Task;
+use regex::Regex;
+
+use crate::internal_prelude::*;
+
+/// Select tasks by their label.
+///
+/// Patterns with `*` or `?` wildcards are matched against the whole label, like shell globs.
+/// All other patterns match labels that contain them.
+#[derive(Clone, Debug)]
+pub struct LabelPattern {
+    pattern: String,
+    regex: Regex,
+}
+
+impl LabelPattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let escaped = regex::escape(pattern);
+        let regex = if pattern.contains(['*', '?']) {
+            let glob = escaped.replace(r"\*", ".*").replace(r"\?", ".");
+            format!("^{glob}$")
+        } else {
+            escaped
+        };
+        let regex = Regex::new(&regex).map_err(|err| eyre!("Invalid label pattern: {err}"))?;
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            regex,
+        })
+    }
+
+    /// Check whether a task has a label that matches the pattern.
+    pub fn matches(&self, task: &Task) -> bool {
+        task.label
+            .as_deref()
+            .is_some_and(|label| self.regex.is_match(label))
+    }
+}
+
+impl fmt::Display for LabelPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
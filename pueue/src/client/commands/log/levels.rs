@@ -0,0 +1,128 @@
+use std::{borrow::Cow, collections::BTreeMap};
+
+use crossterm::style::{Attribute, Color};
+use pueue_lib::settings::{LogLevelColor, Settings};
+
+use crate::client::style::OutputStyle;
+
+/// Highlights lines of unstructured log output by their log level, such as `ERROR` or `WARN`.
+///
+/// This is only a heuristic. To avoid false positives, the level is only recognized at the
+/// start of a line, optionally after a timestamp and in brackets, e.g. `[2024-05-01 12:00] WARN`.
+pub struct LineLevels {
+    style: OutputStyle,
+    colors: BTreeMap<String, LogLevelColor>,
+}
+
+impl LineLevels {
+    /// Returns `None` if styling is disabled, e.g. because stdout isn't a terminal.
+    pub fn new(settings: &Settings, style: &OutputStyle) -> Option<Self> {
+        if !style.enabled {
+            return None;
+        }
+
+        Some(Self {
+            style: style.clone(),
+            colors: settings.client.log_level_colors.clone(),
+        })
+    }
+
+    /// Highlight a line with the color of its log level.
+    /// Lines without a known log level are returned as they are.
+    pub fn style_line<'a>(&self, line: Cow<'a, str>) -> Cow<'a, str> {
+        let color = level_token(&line).and_then(|token| self.colors.get(token));
+        match color {
+            Some(LogLevelColor::Red) => self.color(line, Color::Red),
+            Some(LogLevelColor::Yellow) => self.color(line, Color::Yellow),
+            Some(LogLevelColor::Green) => self.color(line, Color::Green),
+            Some(LogLevelColor::Blue) => self.color(line, Color::Blue),
+            Some(LogLevelColor::Dim) => {
+                Cow::Owned(self.style.style_text(line, None, Some(Attribute::Dim)))
+            }
+            Some(LogLevelColor::None) | None => line,
+        }
+    }
+
+    fn color<'a>(&self, line: Cow<'a, str>, color: Color) -> Cow<'a, str> {
+        Cow::Owned(self.style.style_text(line, Some(color), None))
+    }
+}
+
+/// Return the word at which a line's log level is expected.
+///
+/// That's the first word of the line, after an optional timestamp. The word may be enclosed in
+/// brackets and has to be followed by a character that isn't part of a word, e.g. `ERROR:`.
+fn level_token(line: &str) -> Option<&str> {
+    let mut rest = line.trim_start();
+    // Skip a timestamp, which may be enclosed in brackets.
+    if let Some(timestamp) = rest.strip_prefix('[') {
+        if timestamp.starts_with(|c: char| c.is_ascii_digit()) {
+            rest = skip_timestamp(timestamp).trim_start_matches(']');
+        }
+    } else {
+        rest = skip_timestamp(rest);
+    }
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix('[').unwrap_or(rest);
+
+    let end = rest
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(rest.len());
+    (end > 0).then(|| &rest[..end])
+}
+
+/// Skip a timestamp at the start of some text, such as `2024-05-01T12:00:00.123Z` or
+/// `12:00:00,123`. Text that doesn't start with a digit is returned as it is.
+fn skip_timestamp(text: &str) -> &str {
+    if !text.starts_with(|c: char| c.is_ascii_digit()) {
+        return text;
+    }
+
+    let mut previous = ' ';
+    let end = text
+        .char_indices()
+        .find(|(_, c)| {
+            let part_of_timestamp = c.is_ascii_digit()
+                || matches!(c, '-' | ':' | '.' | ',' | '/' | '+' | ' ')
+                // The date and time are separated by a `T`, the timezone may be `Z`.
+                || (matches!(c, 'T' | 'Z') && previous.is_ascii_digit());
+            previous = *c;
+            !part_of_timestamp
+        })
+        .map(|(index, _)| index)
+        .unwrap_or(text.len());
+
+    &text[end..]
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn level_tokens() {
+        assert_eq!(level_token("ERROR: failed"), Some("ERROR"));
+        assert_eq!(level_token("  WARN something"), Some("WARN"));
+        assert_eq!(level_token("[DEBUG] value"), Some("DEBUG"));
+        assert_eq!(
+            level_token("2024-05-01T12:00:00.123Z INFO started"),
+            Some("INFO")
+        );
+        assert_eq!(
+            level_token("2024-05-01 12:00:00,123 ERROR failed"),
+            Some("ERROR")
+        );
+        assert_eq!(
+            level_token("[2024-05-01 12:00:00] [WARN] slow"),
+            Some("WARN")
+        );
+        assert_eq!(level_token("12:00:00 DEBUG value"), Some("DEBUG"));
+
+        // Log levels in the middle of a line aren't recognized.
+        assert_eq!(level_token("no ERROR here"), Some("no"));
+        assert_eq!(level_token("ERRORS: 0"), Some("ERRORS"));
+        assert_eq!(level_token(""), None);
+    }
+}
@@ -0,0 +1,170 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use pueue_lib::{
+    Client,
+    log::{OutputStream, get_log_size},
+    message::{LogRequest, Response, TaskSelection},
+    settings::Settings,
+};
+
+use super::has_log;
+use crate::internal_prelude::*;
+
+/// The amount of lines that's shown of each task's output, as given via `--lines`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineAmount {
+    /// A fixed amount of lines.
+    Lines(usize),
+    /// A percentage of each task's output, e.g. `50%` for the last half of it.
+    Percent(u8),
+}
+
+impl FromStr for LineAmount {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let Some(percent) = value.strip_suffix('%') else {
+            return value
+                .parse()
+                .map(LineAmount::Lines)
+                .map_err(|_| format!("'{value}' is neither an amount of lines nor a percentage"));
+        };
+
+        match percent.parse::<u8>() {
+            Ok(percent) if percent <= 100 => Ok(LineAmount::Percent(percent)),
+            Ok(_) => Err("Percentages can't be larger than 100%".to_string()),
+            Err(_) => Err(format!("'{value}' isn't a valid percentage")),
+        }
+    }
+}
+
+impl From<usize> for LineAmount {
+    fn from(lines: usize) -> Self {
+        LineAmount::Lines(lines)
+    }
+}
+
+impl LineAmount {
+    pub fn fixed(self) -> Option<usize> {
+        match self {
+            LineAmount::Lines(lines) => Some(lines),
+            LineAmount::Percent(_) => None,
+        }
+    }
+
+    pub fn percent(self) -> Option<u8> {
+        match self {
+            LineAmount::Lines(_) => None,
+            LineAmount::Percent(percent) => Some(percent),
+        }
+    }
+
+    /// The amount of lines that's shown of a log with `total_lines` lines.
+    /// Percentages are rounded up, so even small percentages show at least a single line.
+    pub fn of(self, total_lines: usize) -> usize {
+        match self {
+            LineAmount::Lines(lines) => lines,
+            LineAmount::Percent(percent) => (total_lines * usize::from(percent)).div_ceil(100),
+        }
+    }
+}
+
+/// The amount of lines that's shown of each task's output.
+///
+/// Percentages of `--lines` result in a different amount for each task, depending on the length
+/// of its log. All other tasks show the `default` amount, where `None` shows all lines.
+#[derive(Clone, Debug, Default)]
+pub struct TaskLines {
+    pub default: Option<usize>,
+    pub tasks: BTreeMap<usize, usize>,
+}
+
+impl TaskLines {
+    pub fn get(&self, task_id: usize) -> Option<usize> {
+        self.tasks.get(&task_id).copied().or(self.default)
+    }
+}
+
+/// Resolve a percentage of `--lines` to the amount of lines of each selected task.
+///
+/// Local logs are counted by us, otherwise the daemon counts them.
+/// Tasks whose logs can't be counted, e.g. because older daemons don't count them, are missing.
+pub async fn resolve_percentage(
+    client: &mut Client,
+    settings: &Settings,
+    selection: &TaskSelection,
+    stream: Option<OutputStream>,
+    percent: u8,
+) -> Result<BTreeMap<usize, usize>> {
+    let read_local_logs = settings.client.read_local_logs;
+    client
+        .send_request(LogRequest {
+            tasks: selection.clone(),
+            send_logs: false,
+            lines: None,
+            head: None,
+            tail: None,
+            stream,
+            line_numbers: false,
+            chunked: false,
+            count: !read_local_logs,
+            compression: settings.shared.log_compression,
+            compression_level: settings.shared.log_compression_level,
+            task_lines: BTreeMap::new(),
+        })
+        .await?;
+
+    let task_logs = match client.receive_response().await? {
+        Response::Log(task_logs) => task_logs,
+        Response::Failure(message) => bail!(message),
+        response => bail!("Received unexpected response: {response:?}"),
+    };
+
+    let mut lines = BTreeMap::new();
+    for (task_id, task_log) in task_logs {
+        if !has_log(&task_log.task) {
+            continue;
+        }
+        let total_lines = if read_local_logs {
+            // Unreadable logs are reported once their output is printed.
+            get_log_size(task_id, stream, &settings.shared.pueue_directory())
+                .ok()
+                .map(|(lines, _)| lines)
+        } else {
+            task_log.total_lines
+        };
+        if let Some(total_lines) = total_lines {
+            lines.insert(task_id, LineAmount::Percent(percent).of(total_lines));
+        }
+    }
+
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn parse_line_amount() {
+        assert_eq!("15".parse(), Ok(LineAmount::Lines(15)));
+        assert_eq!("50%".parse(), Ok(LineAmount::Percent(50)));
+        assert_eq!("100%".parse(), Ok(LineAmount::Percent(100)));
+        assert!("101%".parse::<LineAmount>().is_err());
+        assert!("-5".parse::<LineAmount>().is_err());
+        assert!("half%".parse::<LineAmount>().is_err());
+    }
+
+    #[test]
+    fn percentage_of_lines() {
+        assert_eq!(LineAmount::Percent(50).of(10), 5);
+        // Partial lines are rounded up.
+        assert_eq!(LineAmount::Percent(50).of(3), 2);
+        assert_eq!(LineAmount::Percent(1).of(10), 1);
+        assert_eq!(LineAmount::Percent(0).of(10), 0);
+        assert_eq!(LineAmount::Percent(100).of(0), 0);
+        assert_eq!(LineAmount::Lines(4).of(10), 4);
+    }
+}
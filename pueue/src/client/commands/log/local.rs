@@ -1,63 +1,162 @@
 use std::{
+    borrow::Cow,
     fs::File,
-    io::{self, BufRead, BufReader, Stdout, Write},
+    io::{self, Read, Seek, SeekFrom, Write},
+    ops::Range,
 };
 
-use chrono::Local;
 use crossterm::style::{Attribute, Color};
 use pueue_lib::{
-    log::{get_log_file_handle, seek_to_last_lines},
+    Error,
+    log::{
+        LogReader, count_lines, count_total_lines, get_stream_log_file_handle,
+        head_and_tail_ranges, seek_to_last_bytes,
+    },
     settings::Settings,
 };
 
+use super::{
+    BINARY_CHECK_SIZE, BINARY_NOTICE, CONTEXT_SEPARATOR, HeaderTarget, LineFilter, LineNumbers,
+    LineStreams, LineTimestamps, LineWindow, LogOptions, OMISSION_MARKER, STYLE_RESET,
+    SquashRepeats, TaskRender, filter_line, is_binary, line_info, print_no_output,
+    warn_missing_stream,
+};
 use crate::client::style::OutputStyle;
 
+/// The information about the lines of a local log file, which is looked up in the files next to
+/// it, such as the timestamp and stream indexes.
+#[derive(Default)]
+pub struct LocalLines {
+    pub timestamps: Option<LineTimestamps>,
+    /// Only show the lines that have been written within a time window.
+    pub window: Option<LineWindow>,
+    pub streams: Option<LineStreams>,
+}
+
 /// The daemon didn't send any log output, thereby we didn't request any.
 /// If that's the case, read the log file from the local pueue directory.
 pub fn print_local_log(
     task_id: usize,
     style: &OutputStyle,
     settings: &Settings,
-    lines: Option<usize>,
-    timestamps: bool,
+    options: &LogOptions,
+    render: &TaskRender<'_>,
+    out: &mut dyn Write,
+    header: HeaderTarget,
 ) {
-    let mut file = match get_log_file_handle(task_id, &settings.shared.pueue_directory()) {
-        Ok(file) => file,
+    let pueue_directory = settings.shared.pueue_directory();
+    let stream = options.stream;
+    let mut file = match get_stream_log_file_handle(task_id, stream, &pueue_directory) {
+        Ok((file, found_stream)) => {
+            if let (Some(stream), None) = (stream, found_stream) {
+                warn_missing_stream(task_id, stream);
+            }
+            file
+        }
         Err(err) => {
-            eprintln!("Failed to get log file handle: {err}");
+            eprintln!("{}", log_file_error(task_id, &err));
             return;
         }
     };
-    // Stdout handler to directly write log file output to io::stdout
-    // without having to load anything into memory.
-    let mut stdout = io::stdout();
 
+    let local_lines = LocalLines {
+        timestamps: options
+            .timestamps
+            .as_ref()
+            .map(|timestamps| LineTimestamps::local(task_id, settings, timestamps)),
+        window: options
+            .window
+            .as_ref()
+            .and_then(|window| LineWindow::local(task_id, settings, window)),
+        streams: options
+            .interleaved
+            .then(|| LineStreams::local(task_id, settings, style)),
+    };
+
+    // The log file output is directly written to the output, without loading it into memory.
     print_local_file(
-        &mut stdout,
+        out,
         &mut file,
-        &lines,
+        options,
+        render,
+        &local_lines,
         style.style_text("output:", Some(Color::Green), Some(Attribute::Bold)),
-        timestamps,
+        header,
     );
 }
 
+/// Describe why the log file of a task couldn't be opened.
+///
+/// Missing permissions usually mean that the client runs as a different user than the daemon,
+/// which is pointed out along with how to work around it.
+pub fn log_file_error(task_id: usize, err: &Error) -> String {
+    let io_error = match err {
+        Error::IoPathError(_, _, err) | Error::IoError(_, err) | Error::RawIoError(err) => err,
+        _ => return format!("Failed to get log file handle: {err}"),
+    };
+
+    match io_error.kind() {
+        io::ErrorKind::NotFound => format!("The log of task {task_id} doesn't exist."),
+        io::ErrorKind::PermissionDenied => format!(
+            "Permission denied while reading the log of task {task_id}: {err}\n\
+            The daemon probably runs as a different user than this client. \
+            Either run the client as the daemon's user or set `client.read_local_logs` to \
+            `false`, so the daemon sends the log instead."
+        ),
+        _ => format!("Failed to get log file handle: {err}"),
+    }
+}
+
 /// Print a local log file of a task.
-fn print_local_file(
-    stdout: &mut Stdout,
+///
+/// If `hide_binary` is set and the file looks binary, a notice is printed instead of the output.
+pub fn print_local_file(
+    out: &mut dyn Write,
     file: &mut File,
-    lines: &Option<usize>,
+    options: &LogOptions,
+    render: &TaskRender<'_>,
+    local_lines: &LocalLines,
     header: String,
-    timestamps: bool,
+    header_target: HeaderTarget,
 ) {
+    let (head, lines, max_bytes) = (options.head, render.lines, options.max_bytes);
     if let Ok(metadata) = file.metadata() {
         if metadata.len() != 0 {
-            // Indicates whether the full log output is shown or just the last part of it.
-            let mut output_complete = true;
+            // Only print the first and/or last lines if requested.
+            // Indicates whether the full log output is shown or just some parts of it.
+            let (ranges, output_complete) = match local_log_ranges(file, head, lines, max_bytes) {
+                Ok(ranges) => ranges,
+                Err(err) => {
+                    eprintln!("Failed reading local log file: {err}");
+                    return;
+                }
+            };
+
+            // Add a hint if we should limit the output to X lines **and** there are actually more
+            // lines than that given limit.
+            // The omitted lines are counted, which requires another pass over the file.
+            let total_lines = if output_complete {
+                None
+            } else {
+                count_total_lines(file).ok()
+            };
+            let line_info = line_info(output_complete, head, lines, max_bytes, total_lines);
+
+            // Print a newline between the task information and the first output.
+            if let Err(err) = header_target.print(out, &format!("\n{header}{line_info}")) {
+                report_write_error(&err);
+                return;
+            }
 
-            // Only print the last lines if requested
-            if let Some(lines) = lines {
-                match seek_to_last_lines(file, *lines) {
-                    Ok(complete) => output_complete = complete,
+            if render.hide_binary {
+                match file_is_binary(file) {
+                    Ok(false) => (),
+                    Ok(true) => {
+                        if let Err(err) = writeln!(out, "{BINARY_NOTICE}") {
+                            report_write_error(&err);
+                        }
+                        return;
+                    }
                     Err(err) => {
                         eprintln!("Failed reading local log file: {err}");
                         return;
@@ -65,36 +164,211 @@ fn print_local_file(
                 }
             }
 
-            // Add a hint if we should limit the output to X lines **and** there are actually more
-            // lines than that given limit.
-            let mut line_info = String::new();
-            if !output_complete {
-                line_info = lines.map_or(String::new(), |lines| format!(" (last {lines} lines)"));
+            let mut numbers = options.line_numbers.then(|| LineNumbers::new(1));
+            for (index, range) in ranges.into_iter().enumerate() {
+                // Styles that have been set before the cut mustn't leak into the shown output.
+                if options.ansi_safe_truncate && range.start > 0 {
+                    if let Err(err) = write!(out, "{STYLE_RESET}") {
+                        report_write_error(&err);
+                        return;
+                    }
+                }
+                // Multiple ranges are only returned if lines have been omitted in between.
+                if index > 0 {
+                    if let Err(err) = writeln!(out, "{OMISSION_MARKER}") {
+                        report_write_error(&err);
+                        return;
+                    }
+                }
+
+                // Lines are numbered by their position in the full file, which requires us to
+                // count all lines in front of the shown ones.
+                if let Some(numbers) = numbers.as_mut() {
+                    match count_lines(file, range.start) {
+                        Ok(lines) => numbers.skip_to(lines + 1),
+                        Err(err) => {
+                            eprintln!("Failed reading local log file: {err}");
+                            return;
+                        }
+                    }
+                }
+
+                if let Err(err) = file.seek(SeekFrom::Start(range.start)) {
+                    eprintln!("Failed reading local log file: {err}");
+                    return;
+                }
+                let mut reader = LogReader::new((&mut *file).take(range.end - range.start))
+                    .with_offset(range.start);
+
+                // Lines only need to be processed one by one, if they're filtered, prefixed,
+                // squashed, wrapped or highlighted.
+                if local_lines.timestamps.is_some()
+                    || options.filter.is_some()
+                    || local_lines.window.is_some()
+                    || local_lines.streams.is_some()
+                    || numbers.is_some()
+                    || options.squash_repeats
+                    || render.wrap.is_some()
+                    || render.levels.is_some()
+                {
+                    print_lines(
+                        &mut reader,
+                        out,
+                        options,
+                        render,
+                        local_lines,
+                        numbers.as_mut(),
+                    );
+                } else if let Err(err) = io::copy(&mut reader, out) {
+                    // The reader of the output went away, e.g. because a pager has been closed.
+                    if err.kind() != io::ErrorKind::BrokenPipe {
+                        eprintln!("Failed reading local log file: {err}");
+                    }
+                }
             }
+        } else if let Err(err) = print_no_output(out, header_target) {
+            report_write_error(&err);
+        }
+    }
+}
 
-            // Print a newline between the task information and the first output.
-            eprintln!("\n{header}{line_info}");
+/// Whether a log file looks binary, which is checked at the start of the file.
+fn file_is_binary(file: &mut File) -> io::Result<bool> {
+    let mut start = Vec::new();
+    file.seek(SeekFrom::Start(0))?;
+    (&mut *file)
+        .take(BINARY_CHECK_SIZE as u64)
+        .read_to_end(&mut start)?;
+
+    Ok(is_binary(&start))
+}
 
-            // Print everything with optional timestamps
-            if timestamps {
-                print_with_timestamps(file, stdout);
-            } else if let Err(err) = io::copy(file, stdout) {
-                eprintln!("Failed reading local log file: {err}");
+/// Determine the byte ranges of a local log file that should be shown.
+///
+/// These are the first `head` and last `lines` lines of the file.
+/// The last lines are further capped to roughly `max_bytes`, if requested.
+/// The `bool` return value indicates whether the ranges cover the whole file.
+pub fn local_log_ranges(
+    file: &mut File,
+    head: Option<usize>,
+    lines: Option<usize>,
+    max_bytes: Option<u64>,
+) -> Result<(Vec<Range<u64>>, bool), Error> {
+    let (mut ranges, mut output_complete) = head_and_tail_ranges(file, head, lines)?;
+
+    // The byte limit only applies to the last lines.
+    if let (Some(max_bytes), None, Some(range)) = (max_bytes, head, ranges.last_mut()) {
+        if !seek_to_last_bytes(file, max_bytes)? {
+            let start = file
+                .stream_position()
+                .map_err(|err| Error::IoError("getting byte limit position".to_string(), err))?;
+            if start > range.start {
+                range.start = start.min(range.end);
+                output_complete = false;
             }
         }
     }
+
+    Ok((ranges, output_complete))
 }
 
-/// Print log file content with timestamps for each line.
-fn print_with_timestamps(file: &mut File, stdout: &mut Stdout) {
-    let reader = BufReader::new(file);
-    for line_result in reader.lines() {
-        match line_result {
+/// Print log file content line by line.
+/// Lines are filtered first and the remaining lines are prefixed with their number, stream and
+/// timestamp.
+/// If `squash_repeats` is set, consecutive identical lines are only printed once.
+/// If `wrap` is given, the lines are wrapped at the terminal width.
+/// If `levels` is given, the lines are highlighted by their log level.
+/// If the filter has some context, the lines around each match are shown as well.
+fn print_lines(
+    lines: &mut LogReader<'_>,
+    out: &mut dyn Write,
+    options: &LogOptions,
+    render: &TaskRender<'_>,
+    local_lines: &LocalLines,
+    mut numbers: Option<&mut LineNumbers>,
+) {
+    let filter = options.filter.as_ref();
+    let (wrap, levels) = (render.wrap, render.levels);
+    let timestamps = local_lines.timestamps.as_ref();
+    let window = local_lines.window.as_ref();
+    let streams = local_lines.streams.as_ref();
+    let mut squash = options.squash_repeats.then(SquashRepeats::default);
+    let mut context = filter.and_then(LineFilter::context);
+    let mut print_line =
+        |out: &mut dyn Write, line_offset: u64, number: &str, line: Cow<'_, str>| {
+            if let Some(squash) = squash.as_mut() {
+                if squash.skip_line(out, &line, "")? {
+                    return Ok(());
+                }
+            }
+
+            // Timestamps are merged into JSON lines before they're styled.
+            let (prefix, line) = match timestamps {
+                Some(timestamps) => timestamps.apply(line_offset, line),
+                None => (String::new(), line),
+            };
+            let line = match levels {
+                Some(levels) => levels.style_line(line),
+                None => line,
+            };
+            let stream = streams
+                .map(|streams| streams.prefix(line_offset))
+                .unwrap_or_default();
+            // Wrapped lines are aligned under the content, after all prefixes.
+            let line = match wrap {
+                Some(wrap) => wrap.wrap(&format!("{number}{stream}{prefix}"), line),
+                None => line,
+            };
+            // Lines of stderr are highlighted as a whole, including their prefixes.
+            match streams {
+                Some(streams) => {
+                    let line = format!("{stream}{prefix}{line}");
+                    writeln!(out, "{number}{}", streams.style_line(line_offset, line))
+                }
+                None => writeln!(out, "{number}{prefix}{line}"),
+            }
+        };
+
+    while let Some(line) = lines.next() {
+        match line {
             Ok(line) => {
-                let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                if let Err(err) = writeln!(stdout, "[{}] {}", timestamp, line) {
-                    eprintln!("Failed writing to stdout: {err}");
-                    break;
+                let line_offset = lines.line_offset();
+                // Lines that aren't shown are numbered as well.
+                let number = numbers
+                    .as_deref_mut()
+                    .map(LineNumbers::next_prefix)
+                    .unwrap_or_default();
+                if window.is_some_and(|window| !window.contains(line_offset)) {
+                    continue;
+                }
+
+                let filtered = filter_line(filter, &line);
+                let result = match (context.as_mut(), filtered) {
+                    // Lines around the matches are shown as well, which are held back until
+                    // it's clear whether they're close to a match.
+                    (Some(context), filtered) => {
+                        let matched = filtered.is_some();
+                        let line = filtered.map_or_else(|| line.to_string(), Cow::into_owned);
+                        let shown = context.next((line_offset, number, line), matched);
+                        let mut result = if shown.separator {
+                            writeln!(out, "{CONTEXT_SEPARATOR}")
+                        } else {
+                            Ok(())
+                        };
+                        for (line_offset, number, line) in shown.lines {
+                            if result.is_err() {
+                                break;
+                            }
+                            result = print_line(out, line_offset, &number, Cow::Owned(line));
+                        }
+                        result
+                    }
+                    (None, Some(line)) => print_line(out, line_offset, &number, line),
+                    (None, None) => continue,
+                };
+                if let Err(err) = result {
+                    report_write_error(&err);
+                    return;
                 }
             }
             Err(err) => {
@@ -103,4 +377,48 @@ fn print_with_timestamps(file: &mut File, stdout: &mut Stdout) {
             }
         }
     }
+
+    if let Some(squash) = squash.as_mut() {
+        if let Err(err) = squash.finish(out, "") {
+            report_write_error(&err);
+        }
+    }
+}
+
+/// Report a failure to write the log output.
+fn report_write_error(err: &io::Error) {
+    // The reader of the output went away, e.g. because a pager has been closed.
+    if err.kind() != io::ErrorKind::BrokenPipe {
+        eprintln!("Failed writing log output: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn log_file_errors() {
+        let error = |kind| {
+            let err = Error::IoPathError(PathBuf::from("0.log"), "getting log file handle", kind);
+            log_file_error(0, &err)
+        };
+
+        assert_eq!(
+            error(io::Error::from(io::ErrorKind::NotFound)),
+            "The log of task 0 doesn't exist."
+        );
+        let message = error(io::Error::from(io::ErrorKind::PermissionDenied));
+        assert!(
+            message.contains("different user") && message.contains("client.read_local_logs"),
+            "Unexpected message: {message}"
+        );
+        let message = error(io::Error::other("broken"));
+        assert!(
+            message.starts_with("Failed to get log file handle"),
+            "Unexpected message: {message}"
+        );
+    }
 }
@@ -1,162 +1,1336 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufWriter, IsTerminal, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
 use comfy_table::{Attribute as ComfyAttribute, Cell, CellAlignment, Table};
-use crossterm::style::Color;
+use crossterm::style::{Attribute, Color};
 use pueue_lib::{
     Client,
+    log::OutputStream,
     message::{TaskLogResponse, TaskSelection, *},
     settings::Settings,
+    state::{PUEUE_DEFAULT_GROUP, State},
     task::{Task, TaskResult, TaskStatus},
 };
+use strum::EnumString;
 
-use super::{OutputStyle, handle_response, selection_from_params};
-use crate::internal_prelude::*;
+use super::{
+    FollowOptions, OutputStyle, follow as follow_cmd, get_state, handle_response,
+    selection_from_params,
+};
+use crate::{client::display_helper::format_runtime, internal_prelude::*};
 
+mod annotate;
+mod ansi;
+mod archive;
+mod context;
+mod count;
+mod dashboard;
+mod fetch;
+mod file;
+mod filter;
 mod json;
+mod label;
+mod levels;
+mod lines;
 mod local;
+mod numbers;
+mod oneline;
+mod pager;
+mod raw;
 mod remote;
+mod repeats;
+mod search;
+mod since_last;
+mod streams;
+mod system_log;
+mod timestamps;
+#[cfg(feature = "inotify-follow")]
+mod watcher;
+mod window;
+mod wrap;
 
+use annotate::annotation;
+use ansi::StripAnsi;
+use archive::write_log_archive;
+use context::{CONTEXT_SEPARATOR, LineContext};
+use count::print_log_sizes;
+pub use fetch::{TaskLog, fetch_task_log};
+pub use file::print_log_file;
+pub use filter::{LineFilter, filter_line};
 use json::*;
+pub use json::{JsonEnvs, JsonGroupBy};
+pub use label::LabelPattern;
+use levels::LineLevels;
+pub use lines::LineAmount;
+use lines::{TaskLines, resolve_percentage};
+pub use local::log_file_error;
 use local::*;
+use numbers::{LineNumbers, warn_unknown_line_numbers};
+use oneline::print_last_lines;
+use pager::Pager;
+pub use pager::is_broken_pipe;
+use raw::print_raw_log;
 use remote::*;
+pub use repeats::SquashRepeats;
+use search::search_logs;
+use since_last::print_new_output;
+pub use streams::{LineStreams, warn_missing_stream, warn_remote_interleaved};
+pub use system_log::SystemLog;
+use system_log::SystemLogWriter;
+pub use timestamps::{LineTimestamps, TimestampFormat, TimestampOptions};
+#[cfg(feature = "inotify-follow")]
+pub use watcher::LogWatcher;
+pub use window::{LineWindow, TimeWindow};
+pub use wrap::LineWrap;
+
+/// All options of the `log` command.
+///
+/// Use [LogOptions::default] and the builder methods to construct the options, so newly added
+/// options don't break existing callers.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct LogOptions {
+    /// Show the logs of these specific tasks.
+    pub task_ids: Vec<usize>,
+    /// Show the logs of this group's tasks.
+    pub group: Option<String>,
+    /// Show the logs of all groups' tasks.
+    pub all: bool,
+    /// Show the logs of tasks whose label matches this pattern.
+    /// If a group is given as well, only tasks of that group are shown.
+    pub label: Option<LabelPattern>,
+    /// Print the tasks and their output as json.
+    pub json: bool,
+    /// Indent the json of `json`, which makes it easier to read for humans.
+    pub json_pretty: bool,
+    /// The keys of the top-level object of `json`.
+    pub json_group_by: JsonGroupBy,
+    /// Print each task and its output as a separate json record on its own line.
+    pub json_lines: bool,
+    /// Whether the tasks' environment variables are included in the json output.
+    pub envs: JsonEnvs,
+    /// Only print the last X lines of each task's output, or a percentage of it.
+    pub lines: Option<LineAmount>,
+    /// Only print the first X lines of each task's output.
+    /// If `lines` is set as well, the last lines are printed after them.
+    pub head: Option<usize>,
+    /// Only print roughly the last X bytes of each task's output.
+    pub max_bytes: Option<u64>,
+    /// Show the whole output.
+    pub full: bool,
+    /// Add timestamps to each line of the output.
+    pub timestamps: Option<TimestampOptions>,
+    /// Only show lines that match this filter.
+    pub filter: Option<LineFilter>,
+    /// Search the full logs of all finished tasks and only show the matching lines.
+    pub search: Option<LineFilter>,
+    /// Only print the amount of lines and bytes of each task's output.
+    /// When searching, only the amount of matching lines is printed instead.
+    pub count: bool,
+    /// Only print a single line per task, with its status and the last line of its output.
+    pub oneline: bool,
+    /// Only print the output that has been written since the previous `log --since-last`.
+    pub since_last: bool,
+    /// Dump the log of a single task as it's stored or sent, without decompressing it.
+    pub raw: bool,
+    /// Only show output that has been written within this time window.
+    pub window: Option<TimeWindow>,
+    /// Only show the output of this stream.
+    pub stream: Option<OutputStream>,
+    /// Prefix each line with the stream it has been written to.
+    pub interleaved: bool,
+    /// Prefix each line with its line number in the full output.
+    pub line_numbers: bool,
+    /// Collapse consecutive identical lines into a single line and a `(repeated N times)` marker.
+    pub squash_repeats: bool,
+    /// Wrap long lines at the terminal width, if stdout is a terminal.
+    pub wrap: bool,
+    /// Highlight lines by their log level, if colors are enabled.
+    pub color_levels: bool,
+    /// Reset the terminal styles wherever the output has been cut.
+    pub ansi_safe_truncate: bool,
+    /// Remove ANSI escape sequences, such as colors, from the output.
+    pub strip_ansi: bool,
+    /// Print the task information and output headers to stdout instead of stderr.
+    pub header_to_stdout: bool,
+    /// Only print a single line of task information without any tables or output headers.
+    pub plain: bool,
+    /// Don't print any task information or output headers, only the output itself.
+    pub no_task_info: bool,
+    /// Prepend a line of machine-parseable task metadata, which starts with this prefix,
+    /// to each task's output.
+    pub annotate: Option<String>,
+    /// Whether binary output is shown.
+    pub binary: BinaryOutput,
+    /// The line that's printed between the logs of two tasks, instead of an empty line.
+    pub separator: Option<String>,
+    /// Print a summary line with the amount of shown tasks to stderr, in front of the logs.
+    pub summary: bool,
+    /// Pipe the output through a pager, if stdout is a terminal.
+    pub pager: bool,
+    /// Write the output into files instead of printing it.
+    pub output: Option<OutputPath>,
+    /// Forward each line of the output to the system logger instead of printing it.
+    pub system_log: Option<SystemLog>,
+    /// Bundle the logs of all finished tasks into this archive.
+    pub archive: Option<PathBuf>,
+    /// Follow the output of running tasks, once the logs of all other tasks have been printed.
+    pub follow: bool,
+    /// Print this log file instead of the logs of tasks, without contacting the daemon.
+    pub file: Option<PathBuf>,
+    /// Only print the logs of this many of the most recently started tasks.
+    pub max_tasks: Option<usize>,
+    /// Also show the task information of tasks with these statuses, which don't have any output.
+    pub include_status: Vec<LogStatus>,
+    /// Continuously show the last lines of each task on an alternate screen,
+    /// which is refreshed in this interval.
+    pub watch: Option<Duration>,
+}
+
+impl LogOptions {
+    pub fn task_ids(mut self, task_ids: Vec<usize>) -> Self {
+        self.task_ids = task_ids;
+        self
+    }
+
+    pub fn group(mut self, group: impl Into<Option<String>>) -> Self {
+        self.group = group.into();
+        self
+    }
+
+    pub fn all(mut self, all: bool) -> Self {
+        self.all = all;
+        self
+    }
+
+    pub fn label(mut self, label: impl Into<Option<LabelPattern>>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    pub fn json(mut self, json: bool) -> Self {
+        self.json = json;
+        self
+    }
+
+    pub fn json_pretty(mut self, json_pretty: bool) -> Self {
+        self.json_pretty = json_pretty;
+        self
+    }
+
+    pub fn json_group_by(mut self, json_group_by: JsonGroupBy) -> Self {
+        self.json_group_by = json_group_by;
+        self
+    }
+
+    pub fn json_lines(mut self, json_lines: bool) -> Self {
+        self.json_lines = json_lines;
+        self
+    }
+
+    pub fn envs(mut self, envs: JsonEnvs) -> Self {
+        self.envs = envs;
+        self
+    }
+
+    pub fn lines(mut self, lines: impl Into<Option<LineAmount>>) -> Self {
+        self.lines = lines.into();
+        self
+    }
+
+    pub fn head(mut self, head: impl Into<Option<usize>>) -> Self {
+        self.head = head.into();
+        self
+    }
+
+    pub fn max_bytes(mut self, max_bytes: impl Into<Option<u64>>) -> Self {
+        self.max_bytes = max_bytes.into();
+        self
+    }
+
+    pub fn full(mut self, full: bool) -> Self {
+        self.full = full;
+        self
+    }
+
+    pub fn timestamps(mut self, timestamps: impl Into<Option<TimestampOptions>>) -> Self {
+        self.timestamps = timestamps.into();
+        self
+    }
+
+    pub fn filter(mut self, filter: impl Into<Option<LineFilter>>) -> Self {
+        self.filter = filter.into();
+        self
+    }
+
+    pub fn search(mut self, search: impl Into<Option<LineFilter>>) -> Self {
+        self.search = search.into();
+        self
+    }
+
+    pub fn count(mut self, count: bool) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn oneline(mut self, oneline: bool) -> Self {
+        self.oneline = oneline;
+        self
+    }
+
+    pub fn since_last(mut self, since_last: bool) -> Self {
+        self.since_last = since_last;
+        self
+    }
+
+    pub fn raw(mut self, raw: bool) -> Self {
+        self.raw = raw;
+        self
+    }
+
+    pub fn window(mut self, window: impl Into<Option<TimeWindow>>) -> Self {
+        self.window = window.into();
+        self
+    }
+
+    pub fn stream(mut self, stream: impl Into<Option<OutputStream>>) -> Self {
+        self.stream = stream.into();
+        self
+    }
+
+    pub fn interleaved(mut self, interleaved: bool) -> Self {
+        self.interleaved = interleaved;
+        self
+    }
+
+    pub fn line_numbers(mut self, line_numbers: bool) -> Self {
+        self.line_numbers = line_numbers;
+        self
+    }
+
+    pub fn squash_repeats(mut self, squash_repeats: bool) -> Self {
+        self.squash_repeats = squash_repeats;
+        self
+    }
+
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn color_levels(mut self, color_levels: bool) -> Self {
+        self.color_levels = color_levels;
+        self
+    }
+
+    pub fn ansi_safe_truncate(mut self, ansi_safe_truncate: bool) -> Self {
+        self.ansi_safe_truncate = ansi_safe_truncate;
+        self
+    }
+
+    pub fn strip_ansi(mut self, strip_ansi: bool) -> Self {
+        self.strip_ansi = strip_ansi;
+        self
+    }
+
+    pub fn header_to_stdout(mut self, header_to_stdout: bool) -> Self {
+        self.header_to_stdout = header_to_stdout;
+        self
+    }
+
+    pub fn plain(mut self, plain: bool) -> Self {
+        self.plain = plain;
+        self
+    }
+
+    pub fn no_task_info(mut self, no_task_info: bool) -> Self {
+        self.no_task_info = no_task_info;
+        self
+    }
+
+    pub fn annotate(mut self, annotate: impl Into<Option<String>>) -> Self {
+        self.annotate = annotate.into();
+        self
+    }
+
+    pub fn summary(mut self, summary: bool) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    pub fn binary(mut self, binary: BinaryOutput) -> Self {
+        self.binary = binary;
+        self
+    }
+
+    pub fn separator(mut self, separator: impl Into<Option<String>>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    pub fn pager(mut self, pager: bool) -> Self {
+        self.pager = pager;
+        self
+    }
+
+    pub fn output(mut self, output: impl Into<Option<OutputPath>>) -> Self {
+        self.output = output.into();
+        self
+    }
+
+    pub fn system_log(mut self, system_log: impl Into<Option<SystemLog>>) -> Self {
+        self.system_log = system_log.into();
+        self
+    }
+
+    pub fn archive(mut self, archive: impl Into<Option<PathBuf>>) -> Self {
+        self.archive = archive.into();
+        self
+    }
+
+    pub fn follow(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    pub fn file(mut self, file: impl Into<Option<PathBuf>>) -> Self {
+        self.file = file.into();
+        self
+    }
+
+    pub fn max_tasks(mut self, max_tasks: impl Into<Option<usize>>) -> Self {
+        self.max_tasks = max_tasks.into();
+        self
+    }
+
+    pub fn include_status(mut self, include_status: Vec<LogStatus>) -> Self {
+        self.include_status = include_status;
+        self
+    }
+
+    pub fn watch(mut self, watch: impl Into<Option<Duration>>) -> Self {
+        self.watch = watch.into();
+        self
+    }
+}
+
+/// Write the log output of tasks into files instead of printing it.
+#[derive(Clone, Debug)]
+pub struct OutputPath {
+    pub path: PathBuf,
+    /// Also write the task information and output headers into the files.
+    pub with_header: bool,
+}
+
+impl OutputPath {
+    /// Get the file that the log of a task is written to.
+    ///
+    /// If the path is a directory, each task is written to a `<task_id>.log` file inside of it.
+    /// Otherwise, the task id is appended to the path if the logs of multiple tasks are written.
+    fn task_path(&self, task_id: usize, multiple: bool) -> PathBuf {
+        if self.path.is_dir() {
+            return self.path.join(format!("{task_id}.log"));
+        }
+
+        if multiple {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{task_id}"));
+            return PathBuf::from(path);
+        }
+
+        self.path.clone()
+    }
+}
+
+/// Where headers, such as the task information, are printed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeaderTarget {
+    Stdout,
+    Stderr,
+    /// Write the headers to the same destination as the log output.
+    Output,
+    Hidden,
+}
+
+impl HeaderTarget {
+    /// Print a header to its respective destination.
+    pub fn print(self, out: &mut dyn Write, header: &str) -> io::Result<()> {
+        match self {
+            HeaderTarget::Stdout => writeln!(io::stdout(), "{header}")?,
+            HeaderTarget::Stderr => eprintln!("{header}"),
+            HeaderTarget::Output => writeln!(out, "{header}")?,
+            HeaderTarget::Hidden => (),
+        }
+
+        Ok(())
+    }
+}
 
 /// Print the log output of finished tasks.
 /// This may be selected tasks, all tasks of a group or **all** tasks.
-#[allow(clippy::too_many_arguments)]
 pub async fn print_logs(
     client: &mut Client,
     settings: Settings,
     style: &OutputStyle,
-    task_ids: Vec<usize>,
-    group: Option<String>,
-    all: bool,
-    json: bool,
-    lines: Option<usize>,
-    full: bool,
-    timestamps: bool,
+    options: LogOptions,
 ) -> Result<()> {
-    let lines = determine_log_line_amount(full, &lines);
-    let selection = selection_from_params(all, group.clone(), task_ids.clone());
+    let LogOptions {
+        ref task_ids,
+        ref group,
+        all,
+        ref label,
+        json,
+        json_group_by,
+        json_lines,
+        lines,
+        head,
+        max_bytes,
+        full,
+        ref timestamps,
+        ref filter,
+        ref search,
+        count,
+        oneline,
+        since_last,
+        raw,
+        ref window,
+        stream,
+        interleaved,
+        line_numbers,
+        squash_repeats,
+        wrap,
+        color_levels,
+        strip_ansi,
+        header_to_stdout,
+        plain,
+        no_task_info,
+        ref annotate,
+        binary,
+        ref separator,
+        summary,
+        pager,
+        ref output,
+        system_log,
+        ref archive,
+        follow,
+        max_tasks,
+        ref include_status,
+        watch,
+        ..
+    } = options;
+
+    // Percentages are resolved for each task, once the tasks have been selected.
+    let percent = lines.and_then(LineAmount::percent);
+    let lines = lines.and_then(LineAmount::fixed);
+    let full_by_default = head.is_some()
+        || search.is_some()
+        || max_bytes.is_some()
+        || window.is_some()
+        || output.is_some()
+        || system_log.is_some()
+        || archive.is_some();
+    let lines = determine_log_line_amount(
+        full || percent.is_some(),
+        &lines,
+        full_by_default,
+        settings.client.default_log_lines,
+    );
+    // Labels and the task limit are applied by us, so the tasks are selected by their ids.
+    let state = if label.is_some() || max_tasks.is_some() {
+        Some(get_state(client).await?)
+    } else {
+        None
+    };
+    let selection = match (label, &state) {
+        (Some(label), Some(state)) => {
+            let task_ids: Vec<usize> = state
+                .tasks
+                .values()
+                .filter(|task| group.as_ref().is_none_or(|group| task.group == *group))
+                .filter(|task| shows_task(task, include_status) && label.matches(task))
+                .map(|task| task.id)
+                .collect();
+            if task_ids.is_empty() {
+                eprintln!("There are no finished tasks matching label '{label}'");
+                return Ok(());
+            }
+            TaskSelection::TaskIds(task_ids)
+        }
+        _ => selection_from_params(all, group.clone(), task_ids.clone()),
+    };
+    // The daemon only sends the logs of the most recent tasks, if their amount is limited.
+    let (selection, omitted_tasks) = match (max_tasks, &state) {
+        (Some(max_tasks), Some(state)) => limit_tasks(state, selection, max_tasks),
+        _ => (selection, 0),
+    };
+
+    if let Some(interval) = watch {
+        if percent.is_some() {
+            bail!("--watch splits the terminal between tasks and doesn't support percentages.");
+        }
+        return dashboard::watch_logs(client, &settings, style, selection, interval, &options)
+            .await;
+    }
+    if count && search.is_none() {
+        return print_log_sizes(client, &settings, style, selection, stream, json).await;
+    }
+    if oneline {
+        return print_last_lines(client, &settings, style, selection, stream, json).await;
+    }
+    if since_last {
+        return print_new_output(client, &settings, style, selection, plain, header_to_stdout)
+            .await;
+    }
+    if raw {
+        return print_raw_log(client, &settings, style, task_ids).await;
+    }
+
+    // Tasks whose logs couldn't be counted show their full output.
+    let lines = TaskLines {
+        default: lines,
+        tasks: match percent {
+            Some(percent) => {
+                resolve_percentage(client, &settings, &selection, stream, percent).await?
+            }
+            None => BTreeMap::new(),
+        },
+    };
 
+    // If the first lines are requested, the last lines are sent as `tail`.
+    // Older daemons don't know about either and send the full output, which is trimmed by us.
+    let (request_lines, tail) = match head {
+        Some(_) => (None, lines.default),
+        None => (lines.default, None),
+    };
+    // Chunks are sent in order of the task ids, while grouped tasks are printed in another order.
+    let request_chunked = !json || json_group_by == JsonGroupBy::Id;
     client
         .send_request(LogRequest {
             tasks: selection.clone(),
             send_logs: !settings.client.read_local_logs,
-            lines,
+            lines: request_lines,
+            head,
+            tail,
+            stream,
+            line_numbers,
+            chunked: request_chunked,
+            count: false,
+            compression: settings.shared.log_compression,
+            compression_level: settings.shared.log_compression_level,
+            task_lines: lines.tasks.clone(),
         })
         .await?;
 
     let response = client.receive_response().await?;
 
-    let Response::Log(task_logs) = response else {
+    let Response::Log(mut task_logs) = response else {
         handle_response(style, response)?;
         return Ok(());
     };
 
+    // Daemons that support chunked logs send the output after the tasks.
+    // Older daemons send the output along with each task instead.
+    // This needs to be checked before any tasks are hidden, as their chunks are sent regardless.
+    let chunked = request_chunked
+        && !settings.client.read_local_logs
+        && !task_logs.is_empty()
+        && task_logs.values().all(|log| log.output.is_none());
+
+    // Hide all tasks that didn't run during the requested time window.
+    if let Some(window) = window {
+        task_logs.retain(|_, task_log| window.overlaps_task(&task_log.task));
+    }
+
+    let mut chunks = LogChunks::new(client, chunked);
+    if !json && !json_lines {
+        print_missing_logs_hint(&task_logs, &selection);
+    }
+
+    // Running tasks are followed instead, once the logs of all other tasks have been printed.
+    let followed_ids: Vec<usize> = task_logs
+        .values()
+        .filter(|task_log| follow && task_log.task.is_running())
+        .map(|task_log| task_log.task.id)
+        .collect();
+    task_logs.retain(|task_id, _| !followed_ids.contains(task_id));
+
     // Return the server response in json representation.
     if json {
-        print_log_json(task_logs, &settings, lines, timestamps);
-        return Ok(());
+        print_log_json(&task_logs, &settings, &options, &lines, &mut chunks).await?;
+    } else if json_lines {
+        print_log_json_lines(&task_logs, &settings, &options, &lines, &mut chunks).await?;
+    } else if let Some(path) = archive {
+        let count = write_log_archive(path, &task_logs, &settings, &mut chunks).await?;
+        println!("Archived the logs of {count} tasks to {path:?}");
+    } else if let Some(search) = search {
+        search_logs(&task_logs, style, &settings, search, &options, &mut chunks).await?;
+    } else if let Some(output) = output {
+        // Files are written without any styling, thereby their lines aren't highlighted either.
+        // Each task's log is written to its own file.
+        let multiple = task_logs.values().filter(|log| has_log(&log.task)).count() > 1;
+        for task_log in task_logs.values().filter(|log| has_log(&log.task)) {
+            let path = output.task_path(task_log.task.id, multiple);
+            let file = File::create(&path)
+                .wrap_err_with(|| format!("Failed to create log output file {path:?}"))?;
+            let mut file = BufWriter::new(file);
+
+            let header = if output.with_header {
+                HeaderTarget::Output
+            } else {
+                HeaderTarget::Hidden
+            };
+            let render = TaskRender {
+                lines: lines.get(task_log.task.id),
+                wrap: None,
+                levels: None,
+                strip_ansi,
+                plain,
+                annotate: annotate.as_deref(),
+                hide_binary: binary.hide(false),
+                header: Some(header),
+            };
+            print_log(
+                task_log,
+                style,
+                &settings,
+                &options,
+                &render,
+                &mut file,
+                &mut chunks,
+            )
+            .await?;
+
+            file.flush()
+                .wrap_err_with(|| format!("Failed to write log output file {path:?}"))?;
+        }
+    } else if let Some(system_log) = system_log {
+        // Each line is forwarded as a single message without any styling or task information.
+        for task_log in task_logs.values().filter(|log| has_log(&log.task)) {
+            let mut writer = SystemLogWriter::connect(system_log, &task_log.task)?;
+            let render = TaskRender {
+                lines: lines.get(task_log.task.id),
+                wrap: None,
+                levels: None,
+                strip_ansi: true,
+                plain: true,
+                annotate: None,
+                hide_binary: false,
+                header: Some(HeaderTarget::Hidden),
+            };
+            print_log(
+                task_log,
+                style,
+                &settings,
+                &options,
+                &render,
+                &mut writer,
+                &mut chunks,
+            )
+            .await?;
+            writer
+                .finish()
+                .context("Failed to forward log output to the system logger")?;
+        }
+    } else {
+        // The combined output of all tasks is piped through the pager, including the headers.
+        let mut pager = if pager {
+            Pager::start(style.enabled)
+        } else {
+            None
+        };
+        let paging = pager.is_some();
+        // Lines are only wrapped on terminals, whose width is known.
+        let wrap = if wrap { LineWrap::detect() } else { None };
+        let levels = color_levels
+            .then(|| LineLevels::new(&settings, style))
+            .flatten();
+        let header = if no_task_info {
+            Some(HeaderTarget::Hidden)
+        } else {
+            (header_to_stdout || paging).then_some(HeaderTarget::Output)
+        };
+        let hide_binary = binary.hide(io::stdout().is_terminal());
+        let mut stdout = io::stdout();
+
+        if summary {
+            let count = task_logs
+                .values()
+                .filter(|log| shows_task(&log.task, include_status))
+                .count();
+            let scope = if all {
+                Some("all groups".to_string())
+            } else {
+                selection_scope(group.as_deref(), label.as_ref(), task_ids)
+            };
+            let summary = log_summary(count, scope.as_deref());
+            eprintln!("{}", style.style_text(summary, None, Some(Attribute::Bold)));
+        }
+
+        // Iterate over each task and print the respective log.
+        // Tasks without any log aren't printed and thereby aren't separated either,
+        // unless their status has been explicitly included.
+        let mut printed_any = false;
+        for task_log in task_logs
+            .values()
+            .filter(|log| shows_task(&log.task, include_status))
+        {
+            // Stop printing, once the user closed the pager.
+            if pager.as_mut().is_some_and(Pager::closed) {
+                break;
+            }
+            let out: &mut dyn Write = match pager.as_mut() {
+                Some(pager) => pager,
+                None => &mut stdout,
+            };
+
+            // Separate the task from the previously printed one by a single empty line, unless
+            // another separator is given.
+            // Without task information, the output is only separated if a separator is given.
+            let mut result: Result<()> = match (printed_any, no_task_info, separator) {
+                (false, _, _) | (true, true, None) => Ok(()),
+                (true, _, Some(separator)) => writeln!(out, "{separator}").map_err(Into::into),
+                (true, false, None) => writeln!(out).map_err(Into::into),
+            };
+            printed_any = true;
+
+            if result.is_ok() && !has_log(&task_log.task) {
+                result =
+                    print_task_without_log(&task_log.task, style, &settings, plain, out, header);
+            } else if result.is_ok() {
+                let render = TaskRender {
+                    lines: lines.get(task_log.task.id),
+                    wrap,
+                    levels: levels.as_ref(),
+                    strip_ansi,
+                    plain,
+                    annotate: annotate.as_deref(),
+                    hide_binary,
+                    header,
+                };
+                result = print_log(
+                    task_log,
+                    style,
+                    &settings,
+                    &options,
+                    &render,
+                    out,
+                    &mut chunks,
+                )
+                .await;
+            }
+
+            match result {
+                Err(err) if paging && is_broken_pipe(&err) => break,
+                result => result?,
+            }
+        }
+
+        if let Some(pager) = pager {
+            pager.finish()?;
+        }
+    }
+
+    if chunked {
+        chunks.finish().await?;
+    }
+
+    if omitted_tasks > 0 {
+        let noun = if omitted_tasks == 1 { "task" } else { "tasks" };
+        eprintln!(
+            "… {omitted_tasks} more {noun} omitted (raise or remove --max-tasks to show them)."
+        );
+    }
+
+    // Just like `tail -f`, the last lines of the running tasks are shown before following them.
+    // All followed tasks show the same amount of lines, which is the largest of their amounts.
+    if !followed_ids.is_empty() {
+        let lines = if lines.tasks.is_empty() {
+            lines.default
+        } else {
+            followed_ids.iter().filter_map(|id| lines.get(*id)).max()
+        };
+        // The task information has already been printed with the logs.
+        let options = FollowOptions::default()
+            .lines(lines)
+            .max_bytes(max_bytes)
+            .timestamps(timestamps.clone())
+            .filter(filter.clone())
+            .stream(stream)
+            .interleaved(interleaved)
+            .summary(true)
+            .squash_repeats(squash_repeats);
+        follow_cmd(
+            client,
+            settings,
+            style,
+            TaskSelection::TaskIds(followed_ids),
+            options,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Only select the `max_tasks` most recently started tasks with log output.
+///
+/// Returns the new selection and the amount of tasks that have been omitted.
+/// If none of the tasks has any output, the selection is kept, so the user is told why.
+fn limit_tasks(
+    state: &State,
+    selection: TaskSelection,
+    max_tasks: usize,
+) -> (TaskSelection, usize) {
+    let mut tasks: Vec<&Task> = state
+        .tasks
+        .values()
+        .filter(|task| match &selection {
+            TaskSelection::All => true,
+            TaskSelection::TaskIds(task_ids) => task_ids.contains(&task.id),
+            TaskSelection::Group(group) => task.group == *group,
+        })
+        .filter(|task| has_log(task))
+        .collect();
+    if tasks.is_empty() {
+        return (selection, 0);
     }
 
-    if task_logs.is_empty() {
-        match selection {
-            TaskSelection::TaskIds(_) => {
+    // Most recent first, so the oldest tasks are omitted.
+    tasks.sort_by_key(|task| std::cmp::Reverse(task.start_and_end().0));
+    let omitted = tasks.len().saturating_sub(max_tasks);
+    let task_ids = tasks
+        .into_iter()
+        .take(max_tasks)
+        .map(|task| task.id)
+        .collect();
+
+    (TaskSelection::TaskIds(task_ids), omitted)
+}
+
+/// Tell the user why there's no output for the selected tasks.
+///
+/// Tasks that haven't been started yet are pointed out, as their output can be followed once
+/// they run. Explicitly selected tasks are always pointed out, while groups and all tasks are
+/// only mentioned if none of their tasks have any output.
+fn print_missing_logs_hint(
+    task_logs: &BTreeMap<usize, TaskLogResponse>,
+    selection: &TaskSelection,
+) {
+    let waiting: Vec<&Task> = task_logs
+        .values()
+        .map(|task_log| &task_log.task)
+        .filter(|task| !has_log(task))
+        .collect();
+    let any_log = waiting.len() < task_logs.len();
+
+    match selection {
+        TaskSelection::TaskIds(_) => {
+            for task in &waiting {
+                eprintln!("{}", waiting_tasks_hint(&[task]));
+            }
+            if task_logs.is_empty() {
                 eprintln!("There are no finished tasks for your specified ids");
-                return Ok(());
             }
-            TaskSelection::Group(group) => {
+        }
+        TaskSelection::Group(group) if !any_log => {
+            if waiting.is_empty() {
                 eprintln!("There are no finished tasks for group '{group}'");
-                return Ok(());
+            } else {
+                let hint = waiting_tasks_hint(&waiting);
+                eprintln!("There are no finished tasks for group '{group}' yet. {hint}");
             }
-            TaskSelection::All => {
+        }
+        TaskSelection::All if !any_log => {
+            if waiting.is_empty() {
                 eprintln!("There are no finished tasks");
-                return Ok(());
+            } else {
+                let hint = waiting_tasks_hint(&waiting);
+                eprintln!("There are no finished tasks yet. {hint}");
             }
         }
+        _ => (),
     }
+}
 
-    // Iterate over each task and print the respective log.
-    let mut task_iter = task_logs.iter().peekable();
-    while let Some((_, task_log)) = task_iter.next() {
-        print_log(task_log, style, &settings, lines, timestamps);
-
-        // Add a newline if there is another task that's going to be printed.
-        if let Some((_, task_log)) = task_iter.peek() {
-            if matches!(
-                &task_log.task.status,
-                TaskStatus::Done { .. } | TaskStatus::Running { .. } | TaskStatus::Paused { .. }
-            ) {
-                println!();
-            }
+/// Point out tasks that haven't been started yet and how their output can be followed.
+fn waiting_tasks_hint(tasks: &[&Task]) -> String {
+    match tasks {
+        [task] => {
+            let status = task.status.to_string().to_lowercase();
+            format!(
+                "Task {0} is still {status}; use `pueue follow {0}`.",
+                task.id
+            )
+        }
+        tasks => {
+            let ids: Vec<String> = tasks.iter().map(|task| task.id.to_string()).collect();
+            format!(
+                "Tasks {} haven't been started; use `pueue follow {}` to follow the first one.",
+                ids.join(", "),
+                tasks[0].id
+            )
         }
     }
-
-    Ok(())
 }
 
 /// Determine how many lines of output should be printed/returned.
 /// `None` implicates that all lines are printed.
 ///
-/// By default, everything is returned for single tasks and only some lines for multiple.
-/// `json` is an exception to this, in json mode we always only return some lines
-/// (unless otherwise explicitly requested).
+/// By default, `default_lines` lines are returned per task, as configured via the
+/// `client.default_log_lines` setting.
 ///
 /// `full` always forces the full log output
 /// `lines` force a specific amount of lines
-fn determine_log_line_amount(full: bool, lines: &Option<usize>) -> Option<usize> {
+/// `full_by_default` shows all lines unless `lines` is given, e.g. for time windows or files.
+fn determine_log_line_amount(
+    full: bool,
+    lines: &Option<usize>,
+    full_by_default: bool,
+    default_lines: usize,
+) -> Option<usize> {
     if full {
         None
     } else if let Some(lines) = lines {
         Some(*lines)
+    } else if full_by_default {
+        None
     } else {
         // By default, only some lines are shown per task
-        Some(15)
+        Some(default_lines)
     }
 }
 
+/// The marker that's printed between the first and last lines of a task's output, if any
+/// lines have been omitted in between.
+const OMISSION_MARKER: &str = "...";
+
+/// Resets all styles of the terminal, such as colors that have been set by a task.
+///
+/// This is printed wherever the output has been cut, if `--ansi-safe-truncate` is given.
+/// Otherwise, a color that has been set before the cut would leak into the shown output.
+const STYLE_RESET: &str = "\x1b[0m";
+
+/// The amount of bytes at the start of some output, which are checked for NUL bytes to detect
+/// binary output, just like `grep` does.
+const BINARY_CHECK_SIZE: usize = 8192;
+
+/// Shown instead of binary output, which would garble the terminal.
+const BINARY_NOTICE: &str = "Binary output; use --raw or --text to display";
+
+/// Whether binary output is shown, as chosen via `--text` and `--binary`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BinaryOutput {
+    /// Binary output is only hidden, if it'd be printed to a terminal.
+    #[default]
+    Terminal,
+    /// Always show binary output.
+    Show,
+    /// Never show binary output.
+    Hide,
+}
+
+impl BinaryOutput {
+    /// Whether binary output is hidden, depending on whether it'd be printed to a `terminal`.
+    fn hide(self, terminal: bool) -> bool {
+        match self {
+            BinaryOutput::Terminal => terminal,
+            BinaryOutput::Show => false,
+            BinaryOutput::Hide => true,
+        }
+    }
+}
+
+/// Whether some output looks binary, which is the case if its first bytes contain NUL bytes.
+fn is_binary(output: &[u8]) -> bool {
+    output[..output.len().min(BINARY_CHECK_SIZE)].contains(&0)
+}
+
+/// Tell the user that a task didn't write any output.
+/// This is shown instead of the output header, so it's printed to the same destination.
+fn print_no_output(out: &mut dyn Write, target: HeaderTarget) -> io::Result<()> {
+    target.print(out, "\n(no output)")
+}
+
+/// The hint that's shown next to the output header, if not all lines are shown.
+///
+/// The `total_lines` of the output are included, if they're known.
+fn line_info(
+    output_complete: bool,
+    head: Option<usize>,
+    lines: Option<usize>,
+    max_bytes: Option<u64>,
+    total_lines: Option<usize>,
+) -> String {
+    if output_complete {
+        return String::new();
+    }
+
+    let of_total = total_lines
+        .map(|total| format!(" of {total}"))
+        .unwrap_or_default();
+    match (head, lines, max_bytes) {
+        (Some(head), Some(lines), _) => {
+            format!(" (first {head} and last {lines}{of_total} lines)")
+        }
+        (Some(head), None, _) => format!(" (first {head}{of_total} lines)"),
+        (None, Some(lines), Some(max_bytes)) => {
+            format!(" (last {lines}{of_total} lines, at most {max_bytes} bytes)")
+        }
+        (None, Some(lines), None) => format!(" (last {lines}{of_total} lines)"),
+        (None, None, Some(max_bytes)) => match total_lines {
+            Some(total) => format!(" (last {max_bytes} bytes of {total} lines)"),
+            None => format!(" (last {max_bytes} bytes)"),
+        },
+        (None, None, None) => String::new(),
+    }
+}
+
+/// Return the position at which the last `max_bytes` of some output start.
+///
+/// This is the in-memory counterpart to [pueue_lib::log::seek_to_last_bytes], which moves the
+/// position forward to the start of the next line, unless there's no line break within the limit.
+pub fn last_bytes_start(output: &[u8], max_bytes: u64) -> usize {
+    let max_bytes = usize::try_from(max_bytes).unwrap_or(usize::MAX);
+    let Some(limit) = output
+        .len()
+        .checked_sub(max_bytes)
+        .filter(|limit| *limit > 0)
+    else {
+        return 0;
+    };
+
+    // The byte right before the limit is included, in case the limit is exactly at a line start.
+    match output[limit - 1..].iter().position(|byte| *byte == b'\n') {
+        Some(position) if limit + position < output.len() => limit + position,
+        _ => limit,
+    }
+}
+
+/// Only keep the first `head` and last `tail` lines of some output.
+///
+/// This is the client-side counterpart to what the daemon does for [LogRequest::head], which is
+/// needed for older daemons that always send the full output.
+/// Returns the trimmed output, the size of its head and whether the output is complete.
+fn trim_head_and_tail(output: &[u8], head: usize, tail: Option<usize>) -> (Vec<u8>, u64, bool) {
+    let lines: Vec<&[u8]> = output.split_inclusive(|byte| *byte == b'\n').collect();
+    let head_end = head.min(lines.len());
+    let tail_start = match tail {
+        Some(tail) => lines.len().saturating_sub(tail).max(head_end),
+        None => lines.len(),
+    };
+
+    if tail_start == head_end {
+        return (output.to_vec(), output.len() as u64, true);
+    }
+
+    let mut trimmed = lines[..head_end].concat();
+    let head_size = trimmed.len() as u64;
+    trimmed.extend(lines[tail_start..].concat());
+
+    (trimmed, head_size, false)
+}
+
+/// How the log of a single task is rendered, besides the [LogOptions].
+///
+/// This depends on the task and on where its log is printed to, e.g. a terminal or a file.
+#[derive(Clone, Copy, Default)]
+pub struct TaskRender<'a> {
+    /// Only print the last X lines of the output. `None` prints the whole output.
+    pub lines: Option<usize>,
+    /// Wrap long lines at the terminal width.
+    pub wrap: Option<LineWrap>,
+    /// Highlight lines by their log level.
+    pub levels: Option<&'a LineLevels>,
+    /// Remove ANSI escape sequences from the output.
+    pub strip_ansi: bool,
+    /// Only print a single line of task information and no output header.
+    pub plain: bool,
+    /// Prepend a line of task metadata with this prefix to the output.
+    pub annotate: Option<&'a str>,
+    /// Print a notice instead of output that looks binary.
+    pub hide_binary: bool,
+    /// Where the headers are printed. `None` uses the default destinations for terminals.
+    pub header: Option<HeaderTarget>,
+}
+
 /// Print the log of a single task.
 ///
 /// message: The message returned by the daemon. This message includes all
 ///          requested tasks and the tasks' logs, if we don't read local logs.
-/// lines: Whether we should reduce the log output of each task to a specific number of lines.
-///         `None` implicates that everything should be printed.
-///         This is only important, if we read local lines.
-fn print_log(
+/// out: The destination of the log output.
+/// chunks: Receives the log output, if the daemon sends it in chunks.
+async fn print_log(
     message: &TaskLogResponse,
     style: &OutputStyle,
     settings: &Settings,
-    lines: Option<usize>,
-    timestamps: bool,
-) {
+    options: &LogOptions,
+    render: &TaskRender<'_>,
+    out: &mut dyn Write,
+    chunks: &mut LogChunks<'_>,
+) -> Result<()> {
     let task = &message.task;
     // We only show logs of finished or running tasks.
-    if !matches!(
-        task.status,
-        TaskStatus::Done { .. } | TaskStatus::Running { .. } | TaskStatus::Paused { .. }
-    ) {
-        return;
+    if !has_log(task) {
+        return Ok(());
+    }
+
+    // The escape sequences are removed while the output is written, without buffering it.
+    let mut stripped;
+    let out: &mut dyn Write = if render.strip_ansi {
+        stripped = StripAnsi::new(out);
+        &mut stripped
+    } else {
+        out
+    };
+
+    let info = if render.plain {
+        plain_task_info(task)
+    } else {
+        task_info(task, style, settings)
+    };
+    render
+        .header
+        .unwrap_or(HeaderTarget::Stderr)
+        .print(out, &info)?;
+    // The metadata is part of the output, so it isn't lost if the headers are.
+    if let Some(prefix) = render.annotate {
+        writeln!(out, "{}", annotation(prefix, task))?;
     }
+    // The output header is only shown next to the full task information.
+    let output_header = |default| match (render.plain, render.header) {
+        (true, _) => HeaderTarget::Hidden,
+        (false, header) => header.unwrap_or(default),
+    };
 
-    print_task_info(task, style);
+    // Only the task information is shown, if no lines are requested.
+    // Any output that's sent by the daemon is skipped while receiving the next task's output.
+    if render.lines == Some(0) && options.head.is_none() {
+        return Ok(());
+    }
 
     if settings.client.read_local_logs {
-        print_local_log(message.task.id, style, settings, lines, timestamps);
-    } else if message.output.is_some() {
-        print_remote_log(message, style, lines, timestamps);
+        let header = output_header(HeaderTarget::Stderr);
+        print_local_log(task.id, style, settings, options, render, out, header);
+        return Ok(());
+    }
+
+    // The daemon falls back to the output of both streams, if there's no separate log.
+    if let (Some(stream), None) = (options.stream, message.stream) {
+        warn_missing_stream(task.id, stream);
+    }
+    if options.interleaved {
+        warn_remote_interleaved();
+    }
+
+    if message.output.is_some() {
+        // Older daemons send the output as a single payload.
+        let header = output_header(HeaderTarget::Stdout);
+        print_remote_log(message, style, options, render, out, header)?;
+    } else if chunks.is_chunked() {
+        let header = output_header(HeaderTarget::Stdout);
+        print_remote_log_chunks(message, style, options, render, out, header, chunks).await?;
     } else {
-        println!("Logs requested from pueue daemon, but none received. Please report this bug.");
+        // The output has neither been sent along with the task nor in chunks.
+        error!("{}", missing_output_error(task.id));
     }
+
+    Ok(())
 }
 
-/// Print some information about a task, which is displayed on top of the task's log output.
-fn print_task_info(task: &Task, style: &OutputStyle) {
-    // Print task id and exit code.
-    let task_cell = style.styled_cell(
-        format!("Task {}: ", task.id),
-        None,
-        Some(ComfyAttribute::Bold),
-    );
+/// Describe which tasks have been selected by a group or label, e.g. `group 'ci'`.
+/// Without either of them, tasks are selected by their ids or the default group.
+fn selection_scope(
+    group: Option<&str>,
+    label: Option<&LabelPattern>,
+    task_ids: &[usize],
+) -> Option<String> {
+    match (group, label) {
+        (Some(group), Some(label)) => Some(format!("group '{group}', label '{label}'")),
+        (None, Some(label)) => Some(format!("label '{label}'")),
+        (Some(group), None) => Some(format!("group '{group}'")),
+        (None, None) if task_ids.is_empty() => Some(format!("group '{PUEUE_DEFAULT_GROUP}'")),
+        (None, None) => None,
+    }
+}
+
+/// The line that's shown in front of the logs via `--summary`, e.g.
+/// `Showing logs for 5 tasks (group 'ci')`.
+fn log_summary(count: usize, scope: Option<&str>) -> String {
+    let tasks = if count == 1 { "task" } else { "tasks" };
+    match scope {
+        Some(scope) => format!("Showing logs for {count} {tasks} ({scope})"),
+        None => format!("Showing logs for {count} {tasks}"),
+    }
+}
+
+/// Whether a task can have any log output.
+fn has_log(task: &Task) -> bool {
+    matches!(
+        task.status,
+        TaskStatus::Done { .. } | TaskStatus::Running { .. } | TaskStatus::Paused { .. }
+    )
+}
 
-    let (exit_status, color) = match &task.status {
+/// The statuses of tasks without any log output, whose task information can still be shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString)]
+pub enum LogStatus {
+    #[strum(serialize = "queued", serialize = "Queued")]
+    Queued,
+    #[strum(serialize = "stashed", serialize = "Stashed")]
+    Stashed,
+    #[strum(serialize = "locked", serialize = "Locked")]
+    Locked,
+}
+
+impl LogStatus {
+    fn matches(self, task: &Task) -> bool {
+        matches!(
+            (self, &task.status),
+            (LogStatus::Queued, TaskStatus::Queued { .. })
+                | (LogStatus::Stashed, TaskStatus::Stashed { .. })
+                | (LogStatus::Locked, TaskStatus::Locked { .. })
+        )
+    }
+}
+
+/// Whether a task is shown, which is the case if it has log output or its status is included.
+fn shows_task(task: &Task, include_status: &[LogStatus]) -> bool {
+    has_log(task) || include_status.iter().any(|status| status.matches(task))
+}
+
+/// The note that's shown instead of the output of tasks that haven't been started yet.
+const NO_OUTPUT_NOTE: &str = "(no output / not started)";
+
+/// Print the task information of a task that doesn't have any log output, followed by a note
+/// that there's no output.
+fn print_task_without_log(
+    task: &Task,
+    style: &OutputStyle,
+    settings: &Settings,
+    plain: bool,
+    out: &mut dyn Write,
+    header: Option<HeaderTarget>,
+) -> Result<()> {
+    let info = if plain {
+        plain_task_info(task)
+    } else {
+        task_info(task, style, settings)
+    };
+    header.unwrap_or(HeaderTarget::Stderr).print(out, &info)?;
+    writeln!(out, "{NO_OUTPUT_NOTE}")?;
+
+    Ok(())
+}
+
+/// A human-readable description of a task's status and the color it's displayed in.
+pub fn task_status(task: &Task) -> (String, Color) {
+    match &task.status {
         TaskStatus::Paused { .. } => ("paused".into(), Color::White),
         TaskStatus::Running { .. } => ("running".into(), Color::Yellow),
         TaskStatus::Done { result, .. } => match result {
@@ -164,13 +1338,49 @@ fn print_task_info(task: &Task, style: &OutputStyle) {
             TaskResult::Failed(exit_code) => {
                 (format!("failed with exit code {exit_code}"), Color::Red)
             }
-            TaskResult::FailedToSpawn(_err) => ("Failed to spawn".to_string(), Color::Red),
+            TaskResult::FailedToSpawn(err) => (format!("failed to spawn: {err}"), Color::Red),
             TaskResult::Killed => ("killed by system or user".into(), Color::Red),
             TaskResult::Errored => ("some IO error.\n Check daemon log.".into(), Color::Red),
             TaskResult::DependencyFailed => ("dependency failed".into(), Color::Red),
         },
         _ => (task.status.to_string(), Color::White),
-    };
+    }
+}
+
+/// Return a single line of unstyled information about a task, which is displayed on top of the
+/// task's log output in `--plain` mode.
+fn plain_task_info(task: &Task) -> String {
+    let (status, _) = task_status(task);
+    format!("Task {} ({status}):", task.id)
+}
+
+/// The colors that task ids are colored with, if `client.colorize_task_ids` is enabled.
+/// Green, red and yellow are left out, as they're used for the task status.
+const TASK_ID_COLORS: [Color; 5] = [
+    Color::Cyan,
+    Color::Magenta,
+    Color::Blue,
+    Color::DarkCyan,
+    Color::DarkMagenta,
+];
+
+/// Return some information about a task, which is displayed on top of the task's log output.
+///
+/// If `client.colorize_task_ids` is set, the task id is colored depending on the task, so
+/// consecutive tasks can be told apart.
+pub fn task_info(task: &Task, style: &OutputStyle, settings: &Settings) -> String {
+    // Print task id and exit code.
+    let id_color = settings
+        .client
+        .colorize_task_ids
+        .then(|| TASK_ID_COLORS[task.id % TASK_ID_COLORS.len()]);
+    let task_cell = style.styled_cell(
+        format!("Task {}: ", task.id),
+        id_color,
+        Some(ComfyAttribute::Bold),
+    );
+
+    let (exit_status, color) = task_status(task);
     let status_cell = style.styled_cell(exit_status, Some(color), None);
 
     // The styling of the task number and status is done by a single-row table.
@@ -183,7 +1393,7 @@ fn print_task_info(task: &Task, style: &OutputStyle) {
     if style.enabled {
         table.enforce_styling();
     }
-    eprintln!("{table}");
+    let header = table.to_string();
 
     // All other information is aligned and styled by using a separate table.
     let mut table = Table::new();
@@ -199,6 +1409,12 @@ fn print_task_info(task: &Task, style: &OutputStyle) {
         style.styled_cell("Path:", None, Some(ComfyAttribute::Bold)),
         Cell::new(task.path.to_string_lossy()),
     ]);
+    if task.group != PUEUE_DEFAULT_GROUP || settings.client.show_default_group {
+        table.add_row(vec![
+            style.styled_cell("Group:", None, Some(ComfyAttribute::Bold)),
+            Cell::new(&task.group),
+        ]);
+    }
     if let Some(label) = &task.label {
         table.add_row(vec![
             style.styled_cell("Label:", None, Some(ComfyAttribute::Bold)),
@@ -207,18 +1423,42 @@ fn print_task_info(task: &Task, style: &OutputStyle) {
     }
 
     let (start, end) = task.start_and_end();
+    let now = Local::now();
+    // Scripts may parse the absolute times, which is why relative times are only appended.
+    let time_cell = |time: DateTime<Local>| {
+        if settings.client.show_relative_times {
+            Cell::new(format!(
+                "{} ({} ago)",
+                time.to_rfc2822(),
+                format_runtime(now - time)
+            ))
+        } else {
+            Cell::new(time.to_rfc2822())
+        }
+    };
 
     // Start and end time
     if let Some(start) = start {
         table.add_row(vec![
             style.styled_cell("Start:", None, Some(ComfyAttribute::Bold)),
-            Cell::new(start.to_rfc2822()),
+            time_cell(start),
         ]);
     }
     if let Some(end) = end {
         table.add_row(vec![
             style.styled_cell("End:", None, Some(ComfyAttribute::Bold)),
-            Cell::new(end.to_rfc2822()),
+            time_cell(end),
+        ]);
+    }
+    // Tasks that are still running show how long they've been running so far.
+    if let (true, Some(start)) = (settings.client.show_relative_times, start) {
+        let duration = match end {
+            Some(end) => format_runtime(end - start),
+            None => format!("{} so far", format_runtime(now - start)),
+        };
+        table.add_row(vec![
+            style.styled_cell("Duration:", None, Some(ComfyAttribute::Bold)),
+            Cell::new(duration),
         ]);
     }
 
@@ -227,5 +1467,5 @@ fn print_task_info(task: &Task, style: &OutputStyle) {
     first_column.set_cell_alignment(CellAlignment::Right);
     first_column.set_padding((0, 0));
 
-    eprintln!("{table}");
+    format!("{header}\n{table}")
 }
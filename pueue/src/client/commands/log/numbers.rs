@@ -0,0 +1,43 @@
+use std::sync::Once;
+
+/// Numbers the lines of a task's output by their position in the full log, like `cat -n`.
+pub struct LineNumbers {
+    next: usize,
+}
+
+impl LineNumbers {
+    /// Start numbering at the given line, starting at `1` for the first line of the log.
+    pub fn new(first_line: usize) -> Self {
+        Self { next: first_line }
+    }
+
+    /// Continue numbering at the given line, e.g. after some lines have been omitted.
+    pub fn skip_to(&mut self, line: usize) {
+        self.next = line;
+    }
+
+    /// Skip the given amount of lines, which aren't shown.
+    pub fn skip(&mut self, lines: usize) {
+        self.next += lines;
+    }
+
+    /// Return the prefix for the next line.
+    /// Lines that are filtered still need to be counted, so their numbers aren't reused.
+    pub fn next_prefix(&mut self) -> String {
+        let prefix = format!("{:>6}\t", self.next);
+        self.next += 1;
+        prefix
+    }
+}
+
+/// Inform the user once per invocation, that lines can't be numbered by their position in the
+/// full log, as the daemon doesn't tell where the received output starts.
+pub fn warn_unknown_line_numbers() {
+    static WARNING: Once = Once::new();
+    WARNING.call_once(|| {
+        eprintln!(
+            "Pueue: The daemon doesn't support line numbers. \
+            Lines are numbered from the start of the shown output instead."
+        );
+    });
+}
@@ -0,0 +1,130 @@
+use std::{borrow::Cow, collections::BTreeMap, io::Read};
+
+use pueue_lib::{
+    Client,
+    log::{OutputStream, get_stream_log_file_handle, read_last_lines},
+    message::{LogRequest, Response, TaskLogResponse, TaskSelection},
+    settings::Settings,
+};
+use serde::Serialize;
+
+use super::{LineWrap, has_log, log_file_error, print_missing_logs_hint, task_status};
+use crate::{
+    client::{commands::handle_response, style::OutputStyle},
+    internal_prelude::*,
+};
+
+/// The status and the last line of output of a task.
+#[derive(Debug, Serialize)]
+struct TaskLine {
+    status: String,
+    last_line: String,
+}
+
+/// Print a single line for each selected task, which consists of its id, its status and the
+/// last line of its output.
+///
+/// Only the last line of each log is read, or sent by the daemon respectively.
+/// Lines are cut off at the terminal width, if stdout is a terminal.
+pub async fn print_last_lines(
+    client: &mut Client,
+    settings: &Settings,
+    style: &OutputStyle,
+    selection: TaskSelection,
+    stream: Option<OutputStream>,
+    json: bool,
+) -> Result<()> {
+    let read_local_logs = settings.client.read_local_logs;
+    client
+        .send_request(LogRequest {
+            tasks: selection.clone(),
+            send_logs: !read_local_logs,
+            lines: Some(1),
+            head: None,
+            tail: None,
+            stream,
+            line_numbers: false,
+            chunked: false,
+            count: false,
+            compression: settings.shared.log_compression,
+            compression_level: settings.shared.log_compression_level,
+            task_lines: BTreeMap::new(),
+        })
+        .await?;
+
+    let response = client.receive_response().await?;
+    let Response::Log(task_logs) = response else {
+        handle_response(style, response)?;
+        return Ok(());
+    };
+    if !json {
+        print_missing_logs_hint(&task_logs, &selection);
+    }
+
+    let mut lines = BTreeMap::new();
+    for (task_id, task_log) in task_logs.iter() {
+        if !has_log(&task_log.task) {
+            continue;
+        }
+
+        let last_line = if read_local_logs {
+            local_last_line(settings, *task_id, stream)
+        } else {
+            remote_last_line(task_log)?
+        };
+        // Some status descriptions span multiple lines, of which only the first one is shown.
+        let (status, _) = task_status(&task_log.task);
+        let status = status.lines().next().unwrap_or_default().to_string();
+        lines.insert(*task_id, TaskLine { status, last_line });
+    }
+
+    if json {
+        println!("{}", serde_json::to_string(&lines)?);
+        return Ok(());
+    }
+
+    let wrap = LineWrap::detect();
+    for (task_id, TaskLine { status, last_line }) in lines {
+        let (_, color) = task_status(&task_logs[&task_id].task);
+        let prefix = format!("{task_id} {status} ");
+        let last_line = match wrap {
+            Some(wrap) => wrap.truncate(&prefix, Cow::Owned(last_line)),
+            None => Cow::Owned(last_line),
+        };
+        let status = style.style_text(status, Some(color), None);
+        println!("{task_id} {status} {last_line}");
+    }
+
+    Ok(())
+}
+
+/// Read the last line of a task's local log file.
+/// If the log file can't be read, the error is printed and an empty line is shown instead.
+fn local_last_line(settings: &Settings, task_id: usize, stream: Option<OutputStream>) -> String {
+    match get_stream_log_file_handle(task_id, stream, &settings.shared.pueue_directory()) {
+        Ok((mut file, _)) => read_last_lines(&mut file, 1).trim_end().to_string(),
+        Err(err) => {
+            eprintln!("{}", log_file_error(task_id, &err));
+            String::new()
+        }
+    }
+}
+
+/// Take the last line of the output that has been sent by the daemon.
+/// Older daemons send the full output, which is why only its last line is kept.
+fn remote_last_line(task_log: &TaskLogResponse) -> Result<String> {
+    let Some(bytes) = task_log.output.as_ref() else {
+        return Ok(String::new());
+    };
+    let mut output = Vec::new();
+    task_log
+        .compression
+        .decompressor(bytes.as_slice())?
+        .read_to_end(&mut output)
+        .context("Failed to decompress remote log output")?;
+
+    let output = String::from_utf8_lossy(&output);
+    let last_line = output.trim_end().rsplit('\n').next().unwrap_or_default();
+
+    Ok(last_line.trim_end_matches('\r').to_string())
+}
@@ -0,0 +1,96 @@
+use std::{
+    env,
+    io::{self, BufWriter, IsTerminal, Write},
+    path::Path,
+    process::{Child, ChildStdin, Command, Stdio},
+};
+
+use color_eyre::Report;
+
+use crate::internal_prelude::*;
+
+/// Pipes the output of `pueue log` through a pager, such as `less`.
+pub struct Pager {
+    child: Child,
+    stdin: BufWriter<ChildStdin>,
+}
+
+impl Pager {
+    /// Start the pager, which is `$PAGER` or `less` if that isn't set.
+    ///
+    /// Returns `None` if stdout isn't a terminal or if the pager can't be started, in which case
+    /// the output should be printed directly.
+    pub fn start(colors: bool) -> Option<Self> {
+        if !io::stdout().is_terminal() {
+            return None;
+        }
+
+        let pager = env::var("PAGER")
+            .ok()
+            .filter(|pager| !pager.trim().is_empty())
+            .unwrap_or_else(|| "less".to_string());
+        let mut parts = pager.split_whitespace();
+        let program = parts.next()?;
+
+        let mut command = Command::new(program);
+        command.args(parts).stdin(Stdio::piped());
+        // `less` only shows colors, if it's told to pass through the escape sequences.
+        if colors
+            && Path::new(program)
+                .file_name()
+                .is_some_and(|name| name == "less")
+        {
+            command.arg("-R");
+        }
+
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(err) => {
+                eprintln!("Pueue: Failed to start pager {program}: {err}");
+                return None;
+            }
+        };
+        let stdin = BufWriter::new(child.stdin.take()?);
+
+        Some(Self { child, stdin })
+    }
+
+    /// Whether the user already closed the pager.
+    pub fn closed(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(Some(_)))
+    }
+
+    /// Close the pager's input and wait until the user closes the pager.
+    pub fn finish(self) -> Result<()> {
+        let Self { mut child, stdin } = self;
+        // The pager might have been closed already, in which case there's nobody left to read.
+        if let Err(err) = stdin.into_inner().map_err(|err| err.into_error()) {
+            if err.kind() != io::ErrorKind::BrokenPipe {
+                return Err(err).context("Failed to write to pager");
+            }
+        }
+        child.wait().context("Failed to wait for pager")?;
+
+        Ok(())
+    }
+}
+
+impl Write for Pager {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stdin.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdin.flush()
+    }
+}
+
+/// Whether an error has been caused by writing to a closed pipe, e.g. because a pager has been
+/// closed or the output is piped into `head`.
+pub fn is_broken_pipe(err: &Report) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|err| err.kind() == io::ErrorKind::BrokenPipe)
+    })
+}
@@ -0,0 +1,91 @@
+use std::{
+    fs::File,
+    io::{self, Write},
+};
+
+use pueue_lib::{
+    Client,
+    log::{get_compressed_log_path, get_log_path},
+    message::{LogRequest, Response, TaskSelection},
+    settings::Settings,
+};
+
+use super::has_log;
+use crate::{
+    client::{commands::handle_response, style::OutputStyle},
+    internal_prelude::*,
+};
+
+/// Dump the log of a single task exactly as it's stored, without decompressing or decoding it.
+///
+/// This is meant for debugging corrupted logs or codec mismatches, which can't be printed
+/// otherwise. Local logs are copied from the file on disk, which is the compressed file, if
+/// the daemon compressed the log. Remote logs are the compressed output sent by the daemon.
+pub async fn print_raw_log(
+    client: &mut Client,
+    settings: &Settings,
+    style: &OutputStyle,
+    task_ids: &[usize],
+) -> Result<()> {
+    let [task_id] = task_ids else {
+        bail!("--raw only dumps the log of a single task.");
+    };
+    let task_id = *task_id;
+
+    let read_local_logs = settings.client.read_local_logs;
+    client
+        .send_request(LogRequest {
+            tasks: TaskSelection::TaskIds(vec![task_id]),
+            send_logs: !read_local_logs,
+            lines: None,
+            head: None,
+            tail: None,
+            stream: None,
+            line_numbers: false,
+            // The output is dumped as a single payload, just like it's been compressed.
+            chunked: false,
+            count: false,
+            compression: settings.shared.log_compression,
+            compression_level: settings.shared.log_compression_level,
+            task_lines: Default::default(),
+        })
+        .await?;
+
+    let response = client.receive_response().await?;
+    let Response::Log(mut task_logs) = response else {
+        handle_response(style, response)?;
+        return Ok(());
+    };
+    let Some(task_log) = task_logs.remove(&task_id) else {
+        bail!("There's no task with id {task_id}");
+    };
+    if !has_log(&task_log.task) {
+        eprintln!("Task {task_id} hasn't been started yet and doesn't have a log.");
+        return Ok(());
+    }
+
+    let mut stdout = io::stdout().lock();
+    if read_local_logs {
+        let pueue_dir = settings.shared.pueue_directory();
+        let mut path = get_log_path(task_id, &pueue_dir);
+        if !path.exists() {
+            path = get_compressed_log_path(task_id, &pueue_dir);
+            eprintln!("Pueue: The log file {path:?} is snap compressed.");
+        }
+        eprintln!("Pueue: The raw log may contain binary data.");
+        let mut file =
+            File::open(&path).wrap_err_with(|| format!("Failed to open log file {path:?}"))?;
+        io::copy(&mut file, &mut stdout).context("Failed to dump the log file")?;
+    } else {
+        eprintln!(
+            "Pueue: The raw log is compressed with {} and may contain binary data.",
+            task_log.compression
+        );
+        if let Some(bytes) = &task_log.output {
+            stdout.write_all(bytes)?;
+        }
+    }
+    stdout.flush()?;
+
+    Ok(())
+}
@@ -1,68 +1,654 @@
-use std::io::{self, BufRead, BufReader, Write};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    io::{self, BufRead, Read, Write},
+};
 
-use chrono::Local;
 use crossterm::style::{Attribute, Color};
-use pueue_lib::message::TaskLogResponse;
-use snap::read::FrameDecoder;
+use pueue_lib::{
+    Client,
+    log::LogReader,
+    message::{LogChunkResponse, Response, TaskLogResponse},
+};
 
-use super::OutputStyle;
+use super::{
+    BINARY_NOTICE, CONTEXT_SEPARATOR, HeaderTarget, LineContext, LineFilter, LineLevels,
+    LineNumbers, LineTimestamps, LineWrap, LogOptions, OMISSION_MARKER, OutputStyle, STYLE_RESET,
+    SquashRepeats, TaskRender, filter_line, is_binary, is_broken_pipe, last_bytes_start, line_info,
+    print_no_output, trim_head_and_tail, warn_unknown_line_numbers,
+};
 use crate::internal_prelude::*;
 
+/// Lines that are held back to be shown around matches, along with their line number prefix.
+type RemoteContext = LineContext<(String, String)>;
+
 /// Prints log output received from the daemon.
+///
+/// This output is only sent by older daemons, which don't tell where the output starts.
+/// Lines are thereby numbered from the start of the received output.
+/// If `hide_binary` is set and the output looks binary, a notice is printed instead.
 pub fn print_remote_log(
     task_log: &TaskLogResponse,
     style: &OutputStyle,
-    lines: Option<usize>,
-    timestamps: bool,
-) {
-    if let Some(bytes) = task_log.output.as_ref() {
-        if !bytes.is_empty() {
-            // Add a hint if we should limit the output to X lines **and** there are actually more
-            // lines than that given limit.
-            let mut line_info = String::new();
-            if !task_log.output_complete {
-                line_info = lines.map_or(String::new(), |lines| format!(" (last {lines} lines)"));
+    options: &LogOptions,
+    render: &TaskRender<'_>,
+    out: &mut dyn Write,
+    header: HeaderTarget,
+) -> Result<()> {
+    let (head, lines, max_bytes) = (options.head, render.lines, options.max_bytes);
+    let Some(bytes) = task_log.output.as_ref() else {
+        return Ok(());
+    };
+    if bytes.is_empty() {
+        print_no_output(out, header)?;
+        return Ok(());
+    }
+
+    let numbers = options.line_numbers.then(|| {
+        if !task_log.output_complete {
+            warn_unknown_line_numbers();
+        }
+        LineNumbers::new(1)
+    });
+    let mut printer = RemoteLines::new(options, render, numbers);
+
+    let mut decompressor = LogReader::from_response(task_log)?;
+    // Empty output is still compressed, so it's only noticed once it has been decompressed.
+    let start = decompressor
+        .fill_buf()
+        .context("Failed to decompress remote log output")?;
+    if start.is_empty() {
+        print_no_output(out, header)?;
+        return Ok(());
+    }
+    if render.hide_binary && is_binary(start) {
+        print_remote_log_header(style, "", out, header)?;
+        writeln!(out, "{BINARY_NOTICE}")?;
+        return Ok(());
+    }
+
+    let result = match (head, task_log.head_size, max_bytes) {
+        // Older daemons don't know about `head` and send the full output, which we trim here.
+        (Some(head), None, _) => {
+            let mut output = Vec::new();
+            decompressor
+                .read_to_end(&mut output)
+                .context("Failed to decompress remote log output")?;
+            let (trimmed, head_size, output_complete) = trim_head_and_tail(&output, head, lines);
+            // The full output has been received, so the omitted lines can be counted.
+            let tail_lines = count_newlines(&trimmed[head_size as usize..]);
+            let tail_line = count_newlines(&output) - tail_lines + 1;
+
+            let total_lines = Some(count_output_lines(&output));
+            let line_info = line_info(output_complete, Some(head), lines, None, total_lines);
+            print_remote_log_header(style, &line_info, out, header)?;
+            let omitted = !output_complete && lines.is_some();
+            print_head_and_tail(
+                &mut trimmed.as_slice(),
+                Some(head_size),
+                omitted,
+                out,
+                &mut printer,
+                options.ansi_safe_truncate,
+                Some(tail_line),
+            )
+        }
+        // The daemon doesn't know about the byte limit, so the output is capped in here.
+        (None, _, Some(max_bytes)) => {
+            let mut output = Vec::new();
+            decompressor
+                .read_to_end(&mut output)
+                .context("Failed to decompress remote log output")?;
+            let start = last_bytes_start(&output, max_bytes);
+            if let Some(numbers) = printer.numbers.as_mut() {
+                numbers.skip(count_newlines(&output[..start]));
             }
 
-            // Print a newline between the task information and the first output.
-            let header = style.style_text("output:", Some(Color::Green), Some(Attribute::Bold));
-            println!("\n{header}{line_info}");
+            // The omitted lines can only be counted, if the daemon sent the full output.
+            let total_lines = if task_log.output_complete {
+                Some(count_output_lines(&output))
+            } else {
+                task_log.total_lines
+            };
+            let output_complete = task_log.output_complete && start == 0;
+            let line_info = line_info(output_complete, None, lines, Some(max_bytes), total_lines);
+            print_remote_log_header(style, &line_info, out, header)?;
+            // Styles that have been set before the cut mustn't leak into the shown output.
+            if options.ansi_safe_truncate && !output_complete {
+                write!(out, "{STYLE_RESET}")?;
+            }
+            printer
+                .print(&mut &output[start..], out)
+                .and_then(|()| printer.finish_squash(out))
+        }
+        (_, head_size, _) => {
+            let line_info = line_info(
+                task_log.output_complete,
+                head,
+                lines,
+                None,
+                task_log.total_lines,
+            );
+            print_remote_log_header(style, &line_info, out, header)?;
+            let omitted = !task_log.output_complete && lines.is_some();
+            print_head_and_tail(
+                &mut decompressor,
+                head_size,
+                omitted,
+                out,
+                &mut printer,
+                options.ansi_safe_truncate,
+                None,
+            )
+        }
+    };
 
-            if let Err(err) = decompress_and_print_remote_log(bytes, timestamps) {
-                eprintln!("Error while parsing stdout: {err}");
+    report_output_error(result)
+}
+
+/// Print the output of a task, which may consist of its first lines and its last lines.
+///
+/// `head_size` is the size of the first lines, after which the [OMISSION_MARKER] is printed if
+/// lines have been `omitted` between them and the last lines.
+///
+/// If lines are numbered and the number of the first of the last lines is known as `tail_line`,
+/// the last lines are numbered starting at that line.
+///
+/// If `ansi_safe_truncate` is set, the terminal styles are reset wherever lines have been
+/// omitted.
+fn print_head_and_tail(
+    output: &mut dyn Read,
+    head_size: Option<u64>,
+    omitted: bool,
+    out: &mut dyn Write,
+    printer: &mut RemoteLines<'_>,
+    ansi_safe_truncate: bool,
+    tail_line: Option<usize>,
+) -> Result<()> {
+    // Styles that have been set before a cut mustn't leak into the shown output.
+    let reset = ansi_safe_truncate && omitted;
+    if let Some(head_size) = head_size {
+        printer.print(&mut output.take(head_size), out)?;
+        // Repeats aren't squashed across omitted lines, nor are lines shown around matches.
+        printer.finish_squash(out)?;
+        printer.reset_context();
+        if omitted {
+            if reset {
+                write!(out, "{STYLE_RESET}")?;
             }
+            writeln!(out, "{OMISSION_MARKER}")?;
         }
+    } else if reset {
+        // Without any first lines, the output starts right at the cut.
+        write!(out, "{STYLE_RESET}")?;
+    }
+
+    if let (Some(numbers), Some(tail_line)) = (printer.numbers.as_mut(), tail_line) {
+        numbers.skip_to(tail_line);
     }
+    printer.print(output, out)?;
+    printer.finish_squash(out)
 }
 
-/// We cannot easily stream log output from the client to the daemon (yet).
-/// Right now, the output is compressed in the daemon and sent as a single payload to the
-/// client. In here, we take that payload, decompress it and stream it it directly to stdout.
-fn decompress_and_print_remote_log(bytes: &[u8], timestamps: bool) -> Result<()> {
-    let mut decompressor = FrameDecoder::new(bytes);
-
-    if timestamps {
-        let reader = BufReader::new(decompressor);
-        let stdout = io::stdout();
-        let mut write = stdout.lock();
-
-        for line_result in reader.lines() {
-            match line_result {
-                Ok(line) => {
-                    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-                    writeln!(write, "[{}] {}", timestamp, line)?;
+/// Prints log output that's received from the daemon in a series of chunks.
+///
+/// Each chunk is printed as soon as it arrives, which allows us to start printing before the
+/// full output has been received.
+/// If the output is capped to some bytes, it's only printed once all chunks have been received.
+/// If `hide_binary` is set and the first chunk looks binary, a notice is printed instead.
+pub async fn print_remote_log_chunks(
+    task_log: &TaskLogResponse,
+    style: &OutputStyle,
+    options: &LogOptions,
+    render: &TaskRender<'_>,
+    out: &mut dyn Write,
+    header: HeaderTarget,
+    chunks: &mut LogChunks<'_>,
+) -> Result<()> {
+    let (head, lines, max_bytes) = (options.head, render.lines, options.max_bytes);
+    let (ansi_safe_truncate, hide_binary) = (options.ansi_safe_truncate, render.hide_binary);
+
+    if let (None, Some(max_bytes)) = (head, max_bytes) {
+        let numbers = options
+            .line_numbers
+            .then(|| LineNumbers::new(first_line(task_log)));
+        let mut printer = RemoteLines::new(options, render, numbers);
+        // Only the output within the byte limit is kept in memory.
+        let mut output = Vec::new();
+        let mut capped = false;
+        // The newlines of all received chunks, in case the daemon sent the full output.
+        let mut received_newlines = 0;
+        while let Some(bytes) = chunks.next(task_log.task.id).await? {
+            let received = output.len();
+            let result = task_log
+                .compression
+                .decompressor(bytes.as_slice())
+                .and_then(|mut decoder| decoder.read_to_end(&mut output));
+            if let Err(err) = result {
+                eprintln!("Error while parsing stdout: {err}");
+                continue;
+            }
+            received_newlines += count_newlines(&output[received..]);
+
+            let start = last_bytes_start(&output, max_bytes);
+            if start > 0 {
+                if let Some(numbers) = printer.numbers.as_mut() {
+                    numbers.skip(count_newlines(&output[..start]));
                 }
-                Err(err) => {
-                    eprintln!("Failed reading line from decompressed log: {err}");
-                    break;
+                output.drain(..start);
+                capped = true;
+            }
+        }
+        if report_chunk_error(chunks, task_log.task.id) && output.is_empty() {
+            return Ok(());
+        }
+        if output.is_empty() {
+            print_no_output(out, header)?;
+            return Ok(());
+        }
+
+        let total_lines = if task_log.output_complete {
+            // The last line is counted as well, if it doesn't end with a newline.
+            let unterminated = output.last().is_some_and(|byte| *byte != b'\n');
+            Some(received_newlines + usize::from(unterminated))
+        } else {
+            task_log.total_lines
+        };
+        let output_complete = task_log.output_complete && !capped;
+        let line_info = line_info(output_complete, None, lines, Some(max_bytes), total_lines);
+        print_remote_log_header(style, &line_info, out, header)?;
+        if hide_binary && is_binary(&output) {
+            writeln!(out, "{BINARY_NOTICE}")?;
+            return Ok(());
+        }
+        // Styles that have been set before the cut mustn't leak into the shown output.
+        if ansi_safe_truncate && !output_complete {
+            write!(out, "{STYLE_RESET}")?;
+        }
+        report_output_error(printer.print(&mut output.as_slice(), out))?;
+        printer.finish_squash(out)?;
+
+        return Ok(());
+    }
+
+    // The first lines always start at the top, the daemon tells us where the last lines start.
+    let tail_line = options.line_numbers.then(|| first_line(task_log));
+    let numbers = tail_line.map(|tail_line| match task_log.head_size {
+        Some(_) => LineNumbers::new(1),
+        None => LineNumbers::new(tail_line),
+    });
+    // Repeats are squashed across chunks, as a chunk may end in the middle of them.
+    // The same goes for the lines around matches.
+    let mut printer = RemoteLines::new(options, render, numbers);
+
+    let mut header_printed = false;
+    // The amount of decompressed output that has been received so far.
+    let mut received: u64 = 0;
+    let mut in_head = task_log.head_size.is_some();
+    let omitted = !task_log.output_complete && head.is_some() && lines.is_some();
+    while let Some(bytes) = chunks.next(task_log.task.id).await? {
+        if !header_printed {
+            let line_info = line_info(
+                task_log.output_complete,
+                head,
+                lines,
+                None,
+                task_log.total_lines,
+            );
+            print_remote_log_header(style, &line_info, out, header)?;
+            header_printed = true;
+
+            // Without any first lines, the output starts right at the cut.
+            if ansi_safe_truncate && !task_log.output_complete && head.is_none() {
+                write!(out, "{STYLE_RESET}")?;
+            }
+        }
+
+        let mut output = Vec::new();
+        let result = task_log
+            .compression
+            .decompressor(bytes.as_slice())
+            .and_then(|mut decoder| decoder.read_to_end(&mut output));
+        if let Err(err) = result {
+            eprintln!("Error while parsing stdout: {err}");
+            continue;
+        }
+        // The remaining chunks of this task are skipped while receiving the next task's output.
+        if hide_binary && received == 0 && is_binary(&output) {
+            writeln!(out, "{BINARY_NOTICE}")?;
+            return Ok(());
+        }
+
+        // The daemon never sends chunks that cross the end of the first lines.
+        if in_head && task_log.head_size.is_some_and(|size| received >= size) {
+            in_head = false;
+            printer.finish_squash(out)?;
+            printer.reset_context();
+            if omitted {
+                // Styles that have been set before the cut mustn't leak into the shown output.
+                if ansi_safe_truncate {
+                    write!(out, "{STYLE_RESET}")?;
                 }
+                writeln!(out, "{OMISSION_MARKER}")?;
+            }
+            if let (Some(numbers), Some(tail_line)) = (printer.numbers.as_mut(), tail_line) {
+                numbers.skip_to(tail_line);
             }
         }
-    } else {
-        let stdout = io::stdout();
-        let mut write = stdout.lock();
-        io::copy(&mut decompressor, &mut write)?;
+        received += output.len() as u64;
+
+        // The daemon splits chunks at line boundaries, so they can be processed on their own.
+        report_output_error(printer.print(&mut output.as_slice(), out))?;
     }
+    printer.finish_squash(out)?;
+    let failed = report_chunk_error(chunks, task_log.task.id);
+    // The daemon doesn't send any chunks for empty output.
+    if !header_printed && !failed {
+        print_no_output(out, header)?;
+    }
+
+    Ok(())
+}
+
+/// The number of the first line of the received output, or of its last lines if the first lines
+/// have been requested as well.
+fn first_line(task_log: &TaskLogResponse) -> usize {
+    task_log.first_line.unwrap_or_else(|| {
+        // Older daemons don't tell where the output starts.
+        if !task_log.output_complete {
+            warn_unknown_line_numbers();
+        }
+        1
+    })
+}
+
+/// Count the amount of newlines in some output.
+fn count_newlines(output: &[u8]) -> usize {
+    output.iter().filter(|byte| **byte == b'\n').count()
+}
+
+/// Count the lines of some output, including a last line that doesn't end with a newline.
+pub fn count_output_lines(output: &[u8]) -> usize {
+    let unterminated = output.last().is_some_and(|byte| *byte != b'\n');
+    count_newlines(output) + usize::from(unterminated)
+}
+
+/// Print the header that's displayed between the task information and its output.
+///
+/// `line_info` is a hint that's shown, if not all lines are printed.
+fn print_remote_log_header(
+    style: &OutputStyle,
+    line_info: &str,
+    out: &mut dyn Write,
+    target: HeaderTarget,
+) -> Result<()> {
+    // Print a newline between the task information and the first output.
+    let header = style.style_text("output:", Some(Color::Green), Some(Attribute::Bold));
+    target.print(out, &format!("\n{header}{line_info}"))?;
 
     Ok(())
 }
+
+/// The message that's shown, if the output of a task has been requested from the daemon, but
+/// neither been sent along with the task nor in chunks.
+pub fn missing_output_error(task_id: usize) -> String {
+    format!(
+        "Logs of task {task_id} requested from pueue daemon, but none received. \
+        Please report this bug."
+    )
+}
+
+/// Receives the log chunks that're sent by the daemon after the [Response::Log].
+///
+/// The daemon sends all chunks of a task in order, task by task, and closes the stream with a
+/// [Response::Close] once all output has been sent.
+pub struct LogChunks<'a> {
+    client: &'a mut Client,
+    /// Whether the daemon sends any chunks at all.
+    chunked: bool,
+    /// A chunk that has already been received, but belongs to a later task.
+    next_chunk: Option<LogChunkResponse>,
+    /// The reasons why the output of some tasks couldn't be read by the daemon.
+    errors: BTreeMap<usize, String>,
+    closed: bool,
+}
+
+impl<'a> LogChunks<'a> {
+    pub fn new(client: &'a mut Client, chunked: bool) -> Self {
+        Self {
+            client,
+            chunked,
+            next_chunk: None,
+            errors: BTreeMap::new(),
+            closed: false,
+        }
+    }
+
+    pub fn is_chunked(&self) -> bool {
+        self.chunked
+    }
+
+    /// Receive the next chunk of compressed output for the given task.
+    /// Returns `None` once all chunks of that task have been received, or if the daemon couldn't
+    /// read the task's output, see [LogChunks::take_error].
+    pub async fn next(&mut self, task_id: usize) -> Result<Option<Vec<u8>>> {
+        if !self.chunked {
+            // The output should've been sent along with the task, there's nothing to wait for.
+            error!("{}", missing_output_error(task_id));
+            return Ok(None);
+        }
+
+        loop {
+            if self.next_chunk.is_none() {
+                self.receive().await?;
+            }
+            let Some(chunk) = self.next_chunk.take() else {
+                return Ok(None);
+            };
+
+            if chunk.task_id == task_id {
+                if let Some(error) = chunk.error {
+                    self.errors.insert(task_id, error);
+                    return Ok(None);
+                }
+                return Ok(Some(chunk.output));
+            } else if chunk.task_id > task_id {
+                // The chunk belongs to a later task, keep it around.
+                self.next_chunk = Some(chunk);
+                return Ok(None);
+            }
+            // Skip output of tasks that aren't printed.
+        }
+    }
+
+    /// Take the reason why the daemon couldn't read the output of the given task.
+    /// This is only known once all chunks of that task have been received.
+    pub fn take_error(&mut self, task_id: usize) -> Option<String> {
+        self.errors.remove(&task_id)
+    }
+
+    /// Receive the remaining chunks until the daemon closes the stream.
+    pub async fn finish(&mut self) -> Result<()> {
+        while !self.closed {
+            self.next_chunk = None;
+            self.receive().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+
+        match self.client.receive_response().await? {
+            Response::LogChunk(chunk) => self.next_chunk = Some(chunk),
+            Response::Close => self.closed = true,
+            Response::Failure(text) => bail!(text),
+            response => bail!("Received unexpected response while receiving logs: {response:?}"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Processes the lines of the output that's received from the daemon.
+///
+/// Its state is kept across multiple parts of the same output, e.g. chunks, as repeated lines
+/// and the lines around matches may span them.
+struct RemoteLines<'a> {
+    timestamps: Option<LineTimestamps>,
+    filter: Option<&'a LineFilter>,
+    numbers: Option<LineNumbers>,
+    squash: Option<SquashRepeats>,
+    /// Lines that may be shown around a later match.
+    context: Option<RemoteContext>,
+    wrap: Option<LineWrap>,
+    levels: Option<&'a LineLevels>,
+}
+
+impl<'a> RemoteLines<'a> {
+    fn new(options: &'a LogOptions, render: &TaskRender<'a>, numbers: Option<LineNumbers>) -> Self {
+        let filter = options.filter.as_ref();
+        Self {
+            timestamps: options.timestamps.as_ref().map(LineTimestamps::remote),
+            filter,
+            numbers,
+            squash: options.squash_repeats.then(SquashRepeats::default),
+            context: filter.and_then(LineFilter::context),
+            wrap: render.wrap,
+            levels: render.levels,
+        }
+    }
+
+    /// Print the marker of any lines that have been squashed at the end of some output.
+    fn finish_squash(&mut self, out: &mut dyn Write) -> Result<()> {
+        if let Some(squash) = self.squash.as_mut() {
+            squash.finish(out, "")?;
+        }
+
+        Ok(())
+    }
+
+    /// Forget the lines that have been held back, e.g. once lines have been omitted.
+    fn reset_context(&mut self) {
+        if let Some(context) = self.context.as_mut() {
+            context.reset();
+        }
+    }
+
+    /// The output is compressed in the daemon and sent either as a single payload or in a series
+    /// of chunks. In here, we take such decompressed output and stream it directly to the output.
+    ///
+    /// If lines are filtered, numbered, timestamped, squashed, wrapped or highlighted, the output
+    /// is processed line by line. The marker of squashed lines at the end of the output is left
+    /// to the caller.
+    fn print(&mut self, output: &mut dyn Read, out: &mut dyn Write) -> Result<()> {
+        let Self {
+            timestamps,
+            filter,
+            numbers,
+            squash,
+            context,
+            wrap,
+            levels,
+        } = self;
+        let (filter, wrap, levels) = (*filter, *wrap, *levels);
+        if timestamps.is_some()
+            || filter.is_some()
+            || numbers.is_some()
+            || squash.is_some()
+            || wrap.is_some()
+            || levels.is_some()
+        {
+            let mut print_line = |out: &mut dyn Write, number: &str, line: Cow<'_, str>| {
+                if let Some(squash) = squash.as_mut() {
+                    if squash.skip_line(out, &line, "")? {
+                        return Ok(());
+                    }
+                }
+                // Timestamps are merged into JSON lines before they're styled.
+                let (prefix, line) = match timestamps.as_ref() {
+                    Some(timestamps) => timestamps.apply(0, line),
+                    None => (String::new(), line),
+                };
+                let line = match levels {
+                    Some(levels) => levels.style_line(line),
+                    None => line,
+                };
+                // Wrapped lines are aligned under the content, after all prefixes.
+                let line = match wrap {
+                    Some(wrap) => wrap.wrap(&format!("{number}{prefix}"), line),
+                    None => line,
+                };
+                writeln!(out, "{number}{prefix}{line}")
+            };
+
+            for line_result in LogReader::new(output) {
+                match line_result {
+                    Ok(line) => {
+                        // Lines that are filtered are numbered as well.
+                        let number = numbers
+                            .as_mut()
+                            .map(LineNumbers::next_prefix)
+                            .unwrap_or_default();
+                        let filtered = filter_line(filter, &line);
+                        match (context.as_mut(), filtered) {
+                            // Lines around the matches are shown as well, which are held back until
+                            // it's clear whether they're close to a match.
+                            (Some(context), filtered) => {
+                                let matched = filtered.is_some();
+                                let line =
+                                    filtered.map_or_else(|| line.to_string(), Cow::into_owned);
+                                let shown = context.next((number, line), matched);
+                                if shown.separator {
+                                    writeln!(out, "{CONTEXT_SEPARATOR}")?;
+                                }
+                                for (number, line) in shown.lines {
+                                    print_line(out, &number, Cow::Owned(line))?;
+                                }
+                            }
+                            (None, Some(line)) => print_line(out, &number, line)?,
+                            (None, None) => continue,
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("Failed reading line from decompressed log: {err}");
+                        break;
+                    }
+                }
+            }
+        } else {
+            io::copy(output, out)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Report that the daemon couldn't read the output of a task, once all of its chunks have been
+/// received. Returns whether there was such an error.
+fn report_chunk_error(chunks: &mut LogChunks<'_>, task_id: usize) -> bool {
+    let Some(error) = chunks.take_error(task_id) else {
+        return false;
+    };
+    eprintln!("Task {task_id}: {error}");
+
+    true
+}
+
+/// Report an error while printing the remote output and continue with the next task.
+///
+/// Errors due to a closed pipe are passed on instead, as there's nobody left to print to,
+/// see [is_broken_pipe].
+fn report_output_error(result: Result<()>) -> Result<()> {
+    match result {
+        Err(err) if is_broken_pipe(&err) => Err(err),
+        Err(err) => {
+            eprintln!("Error while parsing stdout: {err}");
+            Ok(())
+        }
+        Ok(()) => Ok(()),
+    }
+}
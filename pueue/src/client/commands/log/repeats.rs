@@ -0,0 +1,67 @@
+use std::io::{self, Write};
+
+/// Collapses consecutive identical lines into a single line, which is followed by a
+/// `(repeated N times)` marker, similar to journald.
+///
+/// Only complete lines must be passed, so partial lines aren't mistaken for repeats.
+#[derive(Clone, Debug, Default)]
+pub struct SquashRepeats {
+    previous: Option<String>,
+    /// How often the previous line has been seen in a row.
+    count: usize,
+}
+
+impl SquashRepeats {
+    /// Check whether a line repeats the previous line, in which case it's only counted and must
+    /// not be printed.
+    ///
+    /// Otherwise, the marker for the repeats of the previous line is written, which is prefixed
+    /// with `prefix`.
+    pub fn skip_line(&mut self, out: &mut dyn Write, line: &str, prefix: &str) -> io::Result<bool> {
+        if self.previous.as_deref() == Some(line) {
+            self.count += 1;
+            return Ok(true);
+        }
+
+        self.finish(out, prefix)?;
+        self.previous = Some(line.to_string());
+        self.count = 1;
+
+        Ok(false)
+    }
+
+    /// Write the pending marker for the repeats of the last line, e.g. once the output ended.
+    pub fn finish(&mut self, out: &mut dyn Write, prefix: &str) -> io::Result<()> {
+        if self.count > 1 {
+            writeln!(out, "{prefix}(repeated {} times)", self.count)?;
+        }
+        self.previous = None;
+        self.count = 0;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn squash_consecutive_lines() -> io::Result<()> {
+        let mut squash = SquashRepeats::default();
+        let mut out = Vec::new();
+        for line in ["a", "b", "b", "b", "a", "c", "c"] {
+            if !squash.skip_line(&mut out, line, "")? {
+                writeln!(out, "{line}")?;
+            }
+        }
+        squash.finish(&mut out, "")?;
+
+        assert_eq!(
+            String::from_utf8_lossy(&out),
+            "a\nb\n(repeated 3 times)\na\nc\n(repeated 2 times)\n"
+        );
+
+        Ok(())
+    }
+}
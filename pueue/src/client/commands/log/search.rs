@@ -0,0 +1,167 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    io::{self, BufRead, BufReader, Read, Write},
+};
+
+use pueue_lib::{
+    log::{decode_line, get_log_file_handle},
+    message::TaskLogResponse,
+    settings::Settings,
+};
+
+use super::{
+    CONTEXT_SEPARATOR, LineContext, LineFilter, LogChunks, LogOptions, log_file_error,
+    plain_task_info, task_info,
+};
+use crate::{client::style::OutputStyle, internal_prelude::*};
+
+/// Search the logs of all finished tasks for lines that match a regular expression.
+///
+/// Only tasks with at least one matching line are shown, followed by their matching lines and
+/// the lines around them, if the search has some context.
+/// Logs are scanned line by line, so non-matching output is never kept in memory.
+/// If `count` is set, only the amount of matching lines of each task is printed.
+pub async fn search_logs(
+    task_logs: &BTreeMap<usize, TaskLogResponse>,
+    style: &OutputStyle,
+    settings: &Settings,
+    search: &LineFilter,
+    options: &LogOptions,
+    chunks: &mut LogChunks<'_>,
+) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    let mut searched_tasks = 0;
+    let mut matched_tasks = 0;
+
+    for task_log in task_logs.values().filter(|log| log.task.is_done()) {
+        let task = &task_log.task;
+        searched_tasks += 1;
+
+        let header = if options.plain {
+            plain_task_info(task)
+        } else {
+            task_info(task, style, settings)
+        };
+        let mut matches = TaskMatches {
+            search,
+            print_lines: !options.count,
+            header: Some(header),
+            header_to_stdout: options.header_to_stdout,
+            // Tasks are separated by a newline, just like in the normal log output.
+            separate: matched_tasks > 0,
+            count: 0,
+            context: search.context(),
+        };
+
+        if settings.client.read_local_logs {
+            let file = get_log_file_handle(task.id, &settings.shared.pueue_directory())
+                .map_err(|err| eyre!(log_file_error(task.id, &err)))?;
+            matches.search_reader(&mut stdout, file)?;
+        } else if let Some(bytes) = &task_log.output {
+            // Older daemons send the output as a single payload.
+            let decoder = task_log.compression.decompressor(bytes.as_slice())?;
+            matches.search_reader(&mut stdout, decoder)?;
+        } else {
+            // The daemon splits chunks at line boundaries, so they can be searched on their own.
+            while let Some(bytes) = chunks.next(task.id).await? {
+                let decoder = task_log.compression.decompressor(bytes.as_slice())?;
+                matches.search_reader(&mut stdout, decoder)?;
+            }
+            if let Some(error) = chunks.take_error(task.id) {
+                bail!(error);
+            }
+        }
+
+        if matches.count == 0 {
+            continue;
+        }
+        matched_tasks += 1;
+
+        if options.count {
+            let noun = if matches.count == 1 {
+                "match"
+            } else {
+                "matches"
+            };
+            writeln!(stdout, "Task {}: {} {noun}", task.id, matches.count)?;
+        }
+    }
+
+    eprintln!("Matched in {matched_tasks} of {searched_tasks} tasks.");
+
+    Ok(())
+}
+
+/// Searches the log of a single task and prints the matching lines.
+struct TaskMatches<'a> {
+    search: &'a LineFilter,
+    /// Print the matching lines, instead of only counting them.
+    print_lines: bool,
+    /// The task information, which is printed in front of the first matching line.
+    /// `None` once it has been printed.
+    header: Option<String>,
+    header_to_stdout: bool,
+    /// Print a newline in front of the task information.
+    separate: bool,
+    /// The amount of matching lines.
+    count: usize,
+    /// Lines that may be shown around the next match.
+    context: Option<LineContext<String>>,
+}
+
+impl TaskMatches<'_> {
+    fn search_reader(&mut self, out: &mut dyn Write, reader: impl Read) -> Result<()> {
+        let mut reader = BufReader::new(reader);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read_bytes = reader
+                .read_until(b'\n', &mut line)
+                .context("Failed reading line of task log")?;
+            if read_bytes == 0 {
+                return Ok(());
+            }
+
+            let line = decode_line(&line);
+            let found = self.search.apply(&line);
+            if found.is_some() {
+                self.count += 1;
+            }
+            if !self.print_lines {
+                continue;
+            }
+
+            let (separator, lines): (bool, Vec<String>) = match (self.context.as_mut(), found) {
+                (Some(context), found) => {
+                    let matched = found.is_some();
+                    let line = found.map_or_else(|| line.to_string(), Cow::into_owned);
+                    let shown = context.next(line, matched);
+                    (shown.separator, shown.lines.collect())
+                }
+                (None, Some(line)) => (false, vec![line.into_owned()]),
+                (None, None) => continue,
+            };
+            if lines.is_empty() {
+                continue;
+            }
+
+            if let Some(header) = self.header.take() {
+                if self.separate {
+                    writeln!(out)?;
+                }
+                if self.header_to_stdout {
+                    writeln!(out, "{header}")?;
+                } else {
+                    eprintln!("{header}");
+                }
+            }
+            if separator {
+                writeln!(out, "{CONTEXT_SEPARATOR}")?;
+            }
+            for line in lines {
+                writeln!(out, "{line}")?;
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use crossterm::style::{Attribute, Color};
+use pueue_lib::{
+    Client,
+    log::get_log_file_handle,
+    message::{LogRequest, Response, TaskSelection},
+    settings::Settings,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    HeaderTarget, has_log, log_file_error, plain_task_info, print_missing_logs_hint, task_info,
+};
+use crate::{
+    client::{commands::handle_response, style::OutputStyle},
+    internal_prelude::*,
+};
+
+/// The file in the pueue directory, in which the printed parts of the logs are remembered.
+const OFFSETS_FILE: &str = "log_offsets.json";
+
+/// How many bytes of each task's log have been printed by the previous `log --since-last`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LogOffsets(BTreeMap<usize, u64>);
+
+impl LogOffsets {
+    fn path(settings: &Settings) -> PathBuf {
+        settings.shared.pueue_directory().join(OFFSETS_FILE)
+    }
+
+    /// Load the offsets of the previous invocation.
+    /// If they can't be read, e.g. because this is the first invocation, all output is new.
+    fn load(path: &Path) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_vec(self)?;
+        fs::write(path, content)
+            .wrap_err_with(|| format!("Failed to write log offsets to {path:?}"))
+    }
+
+    /// The position in a task's log at which its new output starts.
+    ///
+    /// If the log shrank since then, it has been rotated or the task has been restarted.
+    /// In that case, the whole log is new.
+    fn start(&self, task_id: usize, length: u64) -> u64 {
+        self.0
+            .get(&task_id)
+            .copied()
+            .filter(|offset| *offset <= length)
+            .unwrap_or(0)
+    }
+}
+
+/// Only print the output that has been written since the previous `log --since-last`.
+///
+/// The amount of printed bytes of each task is remembered in [OFFSETS_FILE], so repeated calls
+/// behave like an incremental tail. This only works with local logs, as the positions refer to
+/// the local log files.
+pub async fn print_new_output(
+    client: &mut Client,
+    settings: &Settings,
+    style: &OutputStyle,
+    selection: TaskSelection,
+    plain: bool,
+    header_to_stdout: bool,
+) -> Result<()> {
+    if !settings.client.read_local_logs {
+        bail!(
+            "--since-last continues at the position that has been printed last in the log files, \
+            which requires `client.read_local_logs` to be enabled."
+        );
+    }
+
+    client
+        .send_request(LogRequest {
+            tasks: selection.clone(),
+            send_logs: false,
+            lines: None,
+            head: None,
+            tail: None,
+            stream: None,
+            line_numbers: false,
+            chunked: false,
+            count: false,
+            compression: Default::default(),
+            compression_level: None,
+            task_lines: BTreeMap::new(),
+        })
+        .await?;
+
+    let response = client.receive_response().await?;
+    let Response::Log(task_logs) = response else {
+        handle_response(style, response)?;
+        return Ok(());
+    };
+    print_missing_logs_hint(&task_logs, &selection);
+
+    let path = LogOffsets::path(settings);
+    let mut offsets = LogOffsets::load(&path);
+    let header = if header_to_stdout {
+        HeaderTarget::Output
+    } else {
+        HeaderTarget::Stderr
+    };
+    let mut stdout = io::stdout().lock();
+    let mut printed_any = false;
+    for task in task_logs.values().map(|task_log| &task_log.task) {
+        if !has_log(task) {
+            continue;
+        }
+        let mut file = match get_log_file_handle(task.id, &settings.shared.pueue_directory()) {
+            Ok(file) => file,
+            Err(err) => {
+                eprintln!("{}", log_file_error(task.id, &err));
+                continue;
+            }
+        };
+        let length = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        let start = offsets.start(task.id, length);
+
+        // Tasks are separated by a single empty line, just like in the normal log output.
+        if printed_any {
+            writeln!(stdout)?;
+        }
+        printed_any = true;
+        let info = if plain {
+            plain_task_info(task)
+        } else {
+            task_info(task, style, settings)
+        };
+        header.print(&mut stdout, &info)?;
+
+        if start == length {
+            header.print(&mut stdout, "\n(no new output)")?;
+        } else {
+            if !plain {
+                let title = if start == 0 { "output:" } else { "new output:" };
+                let title = style.style_text(title, Some(Color::Green), Some(Attribute::Bold));
+                header.print(&mut stdout, &format!("\n{title}"))?;
+            }
+            file.seek(SeekFrom::Start(start))
+                .context("Failed to seek to the new output")?;
+            io::copy(&mut file.take(length - start), &mut stdout)
+                .context("Failed to print the new output")?;
+        }
+        offsets.0.insert(task.id, length);
+    }
+    stdout.flush()?;
+
+    // The output has already been printed, so failing to remember it isn't fatal.
+    if let Err(err) = offsets.save(&path) {
+        eprintln!("Pueue: {err:#}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn offsets_reset_after_shrinking() {
+        let offsets = LogOffsets(BTreeMap::from([(0, 10)]));
+        assert_eq!(offsets.start(0, 15), 10);
+        assert_eq!(offsets.start(0, 10), 10);
+        // The log shrank, so it's printed from the start.
+        assert_eq!(offsets.start(0, 4), 0);
+        // Tasks that haven't been printed before start at the beginning as well.
+        assert_eq!(offsets.start(1, 4), 0);
+    }
+}
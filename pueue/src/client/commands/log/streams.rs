@@ -0,0 +1,128 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Once,
+};
+
+use crossterm::style::{Attribute, Color};
+use pueue_lib::{
+    log::{LogStreams, OutputStream},
+    settings::{Settings, StderrColor},
+};
+
+use crate::client::style::OutputStyle;
+
+/// Determines to which output stream each line of a task's log file has been written.
+///
+/// This is only possible if the daemon keeps separate log streams, in which case it records a
+/// stream index for each task.
+pub struct LineStreams {
+    index: Option<LogStreams>,
+    /// The task and pueue directory, which are needed to reload the index of local logs.
+    source: Option<(usize, PathBuf)>,
+    /// The style and color with which lines of stderr are highlighted.
+    stderr_style: Option<(OutputStyle, StderrColor)>,
+}
+
+impl LineStreams {
+    /// Load the stream index of a task's local log file.
+    pub fn local(task_id: usize, settings: &Settings, style: &OutputStyle) -> Self {
+        let pueue_directory = settings.shared.pueue_directory();
+        let index = read_index(task_id, &pueue_directory);
+        if index.is_none() {
+            warn_missing_index();
+        }
+
+        Self {
+            index,
+            source: Some((task_id, pueue_directory)),
+            stderr_style: Some((style.clone(), settings.client.stderr_color)),
+        }
+    }
+
+    /// Logs that're received from the daemon don't come with a stream index.
+    pub fn remote() -> Self {
+        warn_remote_interleaved();
+
+        Self {
+            index: None,
+            source: None,
+            stderr_style: None,
+        }
+    }
+
+    /// Reload the index of a local log file, e.g. because new output has been written.
+    pub fn reload(&mut self) {
+        if let Some((task_id, pueue_directory)) = &self.source {
+            self.index = read_index(*task_id, pueue_directory);
+        }
+    }
+
+    /// Return the stream prefix for a line that starts at the given byte offset.
+    /// Lines of unknown streams don't get a prefix.
+    pub fn prefix(&self, offset: u64) -> &'static str {
+        match self.stream(offset) {
+            Some(OutputStream::Stdout) => "O: ",
+            Some(OutputStream::Stderr) => "E: ",
+            None => "",
+        }
+    }
+
+    /// Highlight a line that starts at the given byte offset, if it has been written to stderr.
+    /// Nothing is highlighted, if styling is disabled, e.g. because stdout isn't a terminal.
+    pub fn style_line(&self, offset: u64, line: String) -> String {
+        let Some((style, color)) = &self.stderr_style else {
+            return line;
+        };
+        if self.stream(offset) != Some(OutputStream::Stderr) {
+            return line;
+        }
+
+        match color {
+            StderrColor::Red => style.style_text(line, Some(Color::Red), None),
+            StderrColor::Dim => style.style_text(line, None, Some(Attribute::Dim)),
+            StderrColor::None => line,
+        }
+    }
+
+    fn stream(&self, offset: u64) -> Option<OutputStream> {
+        self.index
+            .as_ref()
+            .and_then(|index| index.at_offset(offset))
+    }
+}
+
+fn read_index(task_id: usize, pueue_directory: &Path) -> Option<LogStreams> {
+    match LogStreams::read(task_id, pueue_directory) {
+        Ok(index) => index,
+        Err(err) => {
+            eprintln!("Failed to read stream index: {err}");
+            None
+        }
+    }
+}
+
+/// Inform the user once per invocation, that the output streams can't be told apart.
+fn warn_missing_index() {
+    static WARNING: Once = Once::new();
+    WARNING.call_once(|| {
+        eprintln!(
+            "Pueue: The output streams of the log are unknown. Enable \
+            `daemon.separate_log_streams` and read logs locally to tell them apart."
+        );
+    });
+}
+
+/// Inform the user, that the lines of logs that're received from the daemon can't be assigned
+/// to their output stream, e.g. if they're requested with `--interleaved`.
+pub fn warn_remote_interleaved() {
+    warn_missing_index();
+}
+
+/// Inform the user, that a single output stream has been requested, but the output of both
+/// streams is shown.
+pub fn warn_missing_stream(task_id: usize, stream: OutputStream) {
+    eprintln!(
+        "Pueue: There's no separate {stream} log for task {task_id}, \
+        showing the output of both streams instead."
+    );
+}
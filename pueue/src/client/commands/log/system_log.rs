@@ -0,0 +1,235 @@
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+
+use pueue_lib::task::Task;
+
+use crate::internal_prelude::*;
+
+/// The system logger that the log output of tasks is forwarded to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SystemLog {
+    /// The local syslog daemon. Each line is tagged with `pueue[task=<id>]`.
+    Syslog,
+    /// The systemd journal. The task id and label are attached as `PUEUE_TASK_ID` and
+    /// `PUEUE_TASK_LABEL` fields.
+    #[cfg(all(feature = "journald", target_os = "linux"))]
+    Journald,
+}
+
+/// The socket of the local syslog daemon.
+#[cfg(target_os = "macos")]
+const SYSLOG_SOCKET: &str = "/var/run/syslog";
+#[cfg(not(target_os = "macos"))]
+const SYSLOG_SOCKET: &str = "/dev/log";
+
+/// The socket of the native journald protocol.
+#[cfg(all(feature = "journald", target_os = "linux"))]
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// The syslog priority of the forwarded lines, which is the `user` facility and `info` severity.
+const SYSLOG_PRIORITY: u8 = 14;
+
+/// Forwards the log output of a single task to the system logger, one message per line.
+///
+/// Partial lines are kept back until their newline has been written or [Self::finish] is called.
+#[cfg(unix)]
+pub struct SystemLogWriter {
+    target: SystemLog,
+    socket: UnixDatagram,
+    task_id: usize,
+    #[cfg(all(feature = "journald", target_os = "linux"))]
+    label: Option<String>,
+    buffer: Vec<u8>,
+}
+
+#[cfg(unix)]
+impl SystemLogWriter {
+    /// Connect to the socket of the system logger.
+    pub fn connect(target: SystemLog, task: &Task) -> Result<Self> {
+        let path = match target {
+            SystemLog::Syslog => SYSLOG_SOCKET,
+            #[cfg(all(feature = "journald", target_os = "linux"))]
+            SystemLog::Journald => JOURNALD_SOCKET,
+        };
+        let socket = UnixDatagram::unbound().context("Failed to create system log socket")?;
+        socket
+            .connect(path)
+            .wrap_err_with(|| format!("Failed to connect to the system logger at {path}"))?;
+
+        Ok(Self::new(target, socket, task))
+    }
+
+    /// Use an already connected socket.
+    pub fn new(target: SystemLog, socket: UnixDatagram, task: &Task) -> Self {
+        Self {
+            target,
+            socket,
+            task_id: task.id,
+            #[cfg(all(feature = "journald", target_os = "linux"))]
+            label: task.label.clone(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Send the last line, if it doesn't end with a newline.
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let line = std::mem::take(&mut self.buffer);
+        self.send(&line)
+    }
+
+    fn send(&self, line: &[u8]) -> io::Result<()> {
+        let line = String::from_utf8_lossy(line);
+        let line = line.strip_suffix('\r').unwrap_or(&line);
+        let message = match self.target {
+            SystemLog::Syslog => syslog_message(self.task_id, line),
+            #[cfg(all(feature = "journald", target_os = "linux"))]
+            SystemLog::Journald => journald_message(self.task_id, self.label.as_deref(), line),
+        };
+        self.socket.send(&message)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+impl Write for SystemLogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        let mut start = 0;
+        while let Some(end) = self.buffer[start..].iter().position(|byte| *byte == b'\n') {
+            let end = start + end;
+            self.send(&self.buffer[start..end])?;
+            start = end + 1;
+        }
+        self.buffer.drain(..start);
+
+        Ok(buf.len())
+    }
+
+    /// Lines are sent as soon as they're complete, there's nothing else to flush.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// There's no system logger to connect to on other platforms.
+#[cfg(not(unix))]
+pub struct SystemLogWriter;
+
+#[cfg(not(unix))]
+impl SystemLogWriter {
+    pub fn connect(_target: SystemLog, _task: &Task) -> Result<Self> {
+        bail!("Forwarding logs to the system logger is only supported on Unix")
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+impl Write for SystemLogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Format a line as a message of the local syslog protocol (RFC 3164).
+/// The timestamp and hostname are added by the syslog daemon.
+fn syslog_message(task_id: usize, line: &str) -> Vec<u8> {
+    format!("<{SYSLOG_PRIORITY}>pueue[task={task_id}]: {line}").into_bytes()
+}
+
+/// Format a line as a message of the native journald protocol.
+#[cfg(all(feature = "journald", target_os = "linux"))]
+fn journald_message(task_id: usize, label: Option<&str>, line: &str) -> Vec<u8> {
+    let mut message = Vec::new();
+    let mut field = |name: &str, value: &str| {
+        message.extend_from_slice(name.as_bytes());
+        // Values with newlines have to be prefixed with their length instead.
+        if value.contains('\n') {
+            message.push(b'\n');
+            message.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            message.push(b'=');
+        }
+        message.extend_from_slice(value.as_bytes());
+        message.push(b'\n');
+    };
+    field("MESSAGE", line);
+    field("PRIORITY", &(SYSLOG_PRIORITY % 8).to_string());
+    field("SYSLOG_IDENTIFIER", "pueue");
+    field("PUEUE_TASK_ID", &task_id.to_string());
+    if let Some(label) = label {
+        field("PUEUE_TASK_LABEL", label);
+    }
+
+    message
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use chrono::Local;
+    use pretty_assertions::assert_eq;
+    use pueue_lib::task::TaskStatus;
+
+    use super::*;
+
+    #[test]
+    fn syslog_lines() -> Result<()> {
+        let (sender, receiver) = UnixDatagram::pair()?;
+        let task = Task::new(
+            "echo".to_string(),
+            Default::default(),
+            Default::default(),
+            "default".to_string(),
+            TaskStatus::Queued {
+                enqueued_at: Local::now(),
+            },
+            Vec::new(),
+            0,
+            None,
+        );
+        let mut writer = SystemLogWriter::new(SystemLog::Syslog, sender, &task);
+        // Lines are sent once they're complete, regardless of how they're written.
+        writer.write_all(b"first\r\nsec")?;
+        writer.write_all(b"ond\nlast")?;
+        writer.finish()?;
+
+        let mut buffer = [0; 64];
+        let mut messages = Vec::new();
+        for _ in 0..3 {
+            let size = receiver.recv(&mut buffer)?;
+            messages.push(String::from_utf8_lossy(&buffer[..size]).to_string());
+        }
+        assert_eq!(
+            messages,
+            vec![
+                "<14>pueue[task=0]: first",
+                "<14>pueue[task=0]: second",
+                "<14>pueue[task=0]: last",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[cfg(all(feature = "journald", target_os = "linux"))]
+    #[test]
+    fn journald_fields() {
+        let message = journald_message(3, Some("build"), "done");
+        assert_eq!(
+            String::from_utf8_lossy(&message),
+            "MESSAGE=done\nPRIORITY=6\nSYSLOG_IDENTIFIER=pueue\nPUEUE_TASK_ID=3\n\
+             PUEUE_TASK_LABEL=build\n"
+        );
+    }
+}
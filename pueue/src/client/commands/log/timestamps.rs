@@ -0,0 +1,252 @@
+use std::{borrow::Cow, fmt::Display, str::FromStr, sync::Once};
+
+use chrono::{DateTime, Local, SecondsFormat, TimeZone, Utc, format::StrftimeItems};
+use pueue_lib::{log::LogTimestamps, settings::Settings};
+
+use crate::internal_prelude::*;
+
+/// The format in which timestamps are prefixed to log lines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// RFC 3339 with millisecond precision, e.g. `2025-03-09T12:00:00.000+01:00`.
+    Rfc3339,
+    /// Seconds since the unix epoch with millisecond precision.
+    Unix,
+    /// A custom `strftime` format string.
+    Strftime(String),
+}
+
+impl TimestampFormat {
+    /// Format a timestamp in its respective timezone.
+    pub fn format<Tz: TimeZone>(&self, time: DateTime<Tz>) -> String
+    where
+        Tz::Offset: Display,
+    {
+        match self {
+            TimestampFormat::Rfc3339 => time.to_rfc3339_opts(SecondsFormat::Millis, true),
+            TimestampFormat::Unix => time.format("%s%.3f").to_string(),
+            TimestampFormat::Strftime(format) => time.format(format).to_string(),
+        }
+    }
+}
+
+impl FromStr for TimestampFormat {
+    type Err = String;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        match src {
+            "rfc3339" => Ok(TimestampFormat::Rfc3339),
+            "unix" => Ok(TimestampFormat::Unix),
+            _ => {
+                // Make sure the format is valid, as chrono would otherwise panic while formatting.
+                StrftimeItems::new(src)
+                    .parse()
+                    .map_err(|_| format!("invalid strftime format string: '{src}'"))?;
+                Ok(TimestampFormat::Strftime(src.to_string()))
+            }
+        }
+    }
+}
+
+/// Everything that's needed to know how timestamps should be rendered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimestampOptions {
+    pub format: TimestampFormat,
+    /// Show timestamps in UTC instead of the local timezone.
+    pub utc: bool,
+    /// Merge the timestamps into lines that are JSON objects as a field with this key,
+    /// instead of prefixing them.
+    pub json_key: Option<String>,
+}
+
+impl TimestampOptions {
+    /// Assemble the options from the commandline parameters.
+    /// If no format is explicitly provided, the `client.log_timestamp_format` setting is used.
+    pub fn from_settings(
+        format: Option<TimestampFormat>,
+        utc: bool,
+        settings: &Settings,
+    ) -> Result<Self> {
+        let format = match format {
+            Some(format) => format,
+            None => TimestampFormat::from_str(&settings.client.log_timestamp_format)
+                .map_err(|err| eyre!("Invalid client.log_timestamp_format setting: {err}"))?,
+        };
+
+        Ok(TimestampOptions {
+            format,
+            utc,
+            json_key: None,
+        })
+    }
+
+    pub fn json_key(mut self, json_key: Option<String>) -> Self {
+        self.json_key = json_key;
+        self
+    }
+
+    /// Format a timestamp.
+    /// This is used for all timestamps that're prefixed to log output.
+    pub fn format(&self, time: DateTime<Local>) -> String {
+        if self.utc {
+            self.format.format(time.with_timezone(&Utc))
+        } else {
+            self.format.format(time)
+        }
+    }
+}
+
+/// Determines the timestamps that're prefixed to log lines when `--timestamps` is used.
+///
+/// If the daemon recorded a timestamp index for a task, the time at which a line has actually
+/// been written is used. Otherwise, we fall back to the time at which the line is read.
+pub struct LineTimestamps {
+    options: TimestampOptions,
+    index: Option<LogTimestamps>,
+}
+
+impl LineTimestamps {
+    /// Load the timestamp index of a task's local log file.
+    pub fn local(task_id: usize, settings: &Settings, options: &TimestampOptions) -> Self {
+        let index = match LogTimestamps::read(task_id, &settings.shared.pueue_directory()) {
+            Ok(index) => index,
+            Err(err) => {
+                eprintln!("Failed to read timestamp index: {err}");
+                None
+            }
+        };
+
+        if index.is_none() {
+            warn_missing_index();
+        }
+
+        Self {
+            options: options.clone(),
+            index,
+        }
+    }
+
+    /// Logs that're received from the daemon don't come with a timestamp index.
+    pub fn remote(options: &TimestampOptions) -> Self {
+        warn_remote_index();
+
+        Self {
+            options: options.clone(),
+            index: None,
+        }
+    }
+
+    /// Arbitrary log files, which don't belong to a task, don't come with a timestamp index.
+    pub fn file(options: &TimestampOptions) -> Self {
+        warn_missing_index();
+
+        Self {
+            options: options.clone(),
+            index: None,
+        }
+    }
+
+    /// Output that's streamed live is timestamped when it's received, which is accurate enough.
+    pub fn live(options: &TimestampOptions) -> Self {
+        Self {
+            options: options.clone(),
+            index: None,
+        }
+    }
+
+    /// Stop using the timestamp index, e.g. if it no longer matches the log file.
+    /// Lines are then timestamped when they're read instead.
+    pub fn discard_index(&mut self) {
+        self.index = None;
+    }
+
+    /// Return the timestamp prefix for a line that starts at the given byte offset.
+    pub fn prefix(&self, offset: u64) -> String {
+        format!("[{}] ", self.options.format(self.time(offset)))
+    }
+
+    /// Timestamp a line that starts at the given byte offset.
+    ///
+    /// If a `json_key` is set, lines that are JSON objects get the timestamp merged in as a field
+    /// instead, unless they already have such a field. All other lines are passed through as is.
+    /// Returns the prefix and the line.
+    pub fn apply<'a>(&self, offset: u64, line: Cow<'a, str>) -> (String, Cow<'a, str>) {
+        let time = self.options.format(self.time(offset));
+        if let Some(key) = &self.options.json_key {
+            if let Some(line) = merge_json_field(&line, key, &time) {
+                return (String::new(), Cow::Owned(line));
+            }
+        }
+
+        (format!("[{time}] "), line)
+    }
+
+    fn time(&self, offset: u64) -> DateTime<Local> {
+        self.index
+            .as_ref()
+            .and_then(|index| index.at_offset(offset))
+            .unwrap_or_else(Local::now)
+    }
+}
+
+/// Add a string field in front of all other fields of a line that's a JSON object.
+///
+/// The rest of the line is kept as is, so the order and formatting of its fields don't change.
+/// Returns `None` if the line isn't a JSON object or if it already has a field with that key.
+fn merge_json_field(line: &str, key: &str, value: &str) -> Option<String> {
+    let object: serde_json::Map<String, serde_json::Value> = serde_json::from_str(line).ok()?;
+    if object.contains_key(key) {
+        return None;
+    }
+    let fields = line.trim_start().strip_prefix('{')?;
+    let key = serde_json::to_string(key).ok()?;
+    let value = serde_json::to_string(value).ok()?;
+    let separator = if object.is_empty() { "" } else { "," };
+
+    Some(format!("{{{key}:{value}{separator}{fields}"))
+}
+
+/// Inform the user once per invocation, that the shown timestamps aren't the actual write times.
+fn warn_missing_index() {
+    static WARNING: Once = Once::new();
+    WARNING.call_once(|| {
+        eprintln!(
+            "Pueue: No log timestamps have been recorded, showing the time of reading instead. \
+            Enable `daemon.record_log_timestamps` to record them."
+        );
+    });
+}
+
+/// Inform the user once per invocation, that recorded timestamps can only be read locally.
+fn warn_remote_index() {
+    static WARNING: Once = Once::new();
+    WARNING.call_once(|| {
+        eprintln!(
+            "Pueue: Log timestamps aren't sent by the daemon, showing the time of reading \
+            instead. Enable `client.read_local_logs` to show the recorded timestamps."
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn merge_json_fields() {
+        let merged = |line| merge_json_field(line, "_pueue_ts", "12:00");
+        assert_eq!(
+            merged(r#"{"level": "info", "msg": "done"}"#).as_deref(),
+            Some(r#"{"_pueue_ts":"12:00","level": "info", "msg": "done"}"#)
+        );
+        assert_eq!(merged(" {}").as_deref(), Some(r#"{"_pueue_ts":"12:00"}"#));
+        // Existing fields are never overwritten.
+        assert_eq!(merged(r#"{"_pueue_ts": 1}"#), None);
+        // Anything but objects is passed through.
+        assert_eq!(merged("[1, 2]"), None);
+        assert_eq!(merged("plain text"), None);
+        assert_eq!(merged(r#"{"broken": "#), None);
+    }
+}
@@ -0,0 +1,51 @@
+use std::{path::Path, time::Duration};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+
+use crate::internal_prelude::*;
+
+/// Watches the directory of the local task logs, so followed log files only need to be read
+/// once they actually changed.
+pub struct LogWatcher {
+    /// The watcher stops once it's dropped, so it needs to be kept around.
+    _watcher: RecommendedWatcher,
+    changes: UnboundedReceiver<()>,
+}
+
+impl LogWatcher {
+    /// Start watching the task log directory of the given pueue directory.
+    pub fn new(pueue_directory: &Path) -> Result<Self> {
+        let (sender, changes) = unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<_>| {
+            // Opening and reading files doesn't change them, which is what we do ourselves.
+            let changed = match event {
+                Ok(notify::Event { kind, .. }) => !matches!(kind, EventKind::Access(_)),
+                Err(_) => true,
+            };
+            if changed {
+                let _ = sender.send(());
+            }
+        })
+        .context("Failed to create log file watcher")?;
+
+        watcher
+            .watch(
+                &pueue_directory.join("task_logs"),
+                RecursiveMode::NonRecursive,
+            )
+            .context("Failed to watch task log directory")?;
+
+        Ok(Self {
+            _watcher: watcher,
+            changes,
+        })
+    }
+
+    /// Wait until any log file changed or the timeout has passed.
+    pub async fn wait(&mut self, timeout: Duration) {
+        let _ = tokio::time::timeout(timeout, self.changes.recv()).await;
+        // Changes that queued up in the meantime are all covered by the next read.
+        while self.changes.try_recv().is_ok() {}
+    }
+}
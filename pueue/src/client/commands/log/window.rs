@@ -0,0 +1,72 @@
+use chrono::{DateTime, Local};
+use pueue_lib::{log::LogTimestamps, settings::Settings, task::Task};
+
+/// Only show log output that has been written within a time window.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub since: Option<DateTime<Local>>,
+    pub until: Option<DateTime<Local>>,
+}
+
+impl TimeWindow {
+    /// Returns `None` if neither boundary is set, as there's nothing to filter.
+    pub fn new(since: Option<DateTime<Local>>, until: Option<DateTime<Local>>) -> Option<Self> {
+        if since.is_none() && until.is_none() {
+            return None;
+        }
+
+        Some(Self { since, until })
+    }
+
+    /// Check whether a point in time lies within the window.
+    pub fn contains(&self, time: DateTime<Local>) -> bool {
+        self.since.is_none_or(|since| time >= since) && self.until.is_none_or(|until| time <= until)
+    }
+
+    /// Check whether a task has been running at some point within the window.
+    /// Tasks that have never been started didn't write any output and are thereby never included.
+    pub fn overlaps_task(&self, task: &Task) -> bool {
+        let (Some(start), end) = task.start_and_end() else {
+            return false;
+        };
+        // Running tasks may still write output.
+        let end = end.unwrap_or_else(Local::now);
+
+        self.since.is_none_or(|since| end >= since) && self.until.is_none_or(|until| start <= until)
+    }
+}
+
+/// Determines which lines of a task's log file lie within a [TimeWindow].
+///
+/// This is only possible if the daemon recorded a timestamp index for the task.
+/// Otherwise, the window is only applied to the task's start and end time.
+pub struct LineWindow {
+    window: TimeWindow,
+    index: LogTimestamps,
+}
+
+impl LineWindow {
+    /// Load the timestamp index of a task's local log file.
+    /// Returns `None` if no index has been recorded for this task.
+    pub fn local(task_id: usize, settings: &Settings, window: &TimeWindow) -> Option<Self> {
+        let index = match LogTimestamps::read(task_id, &settings.shared.pueue_directory()) {
+            Ok(index) => index?,
+            Err(err) => {
+                eprintln!("Failed to read timestamp index: {err}");
+                return None;
+            }
+        };
+
+        Some(Self {
+            window: window.clone(),
+            index,
+        })
+    }
+
+    /// Check whether the line that starts at the given byte offset lies within the window.
+    pub fn contains(&self, offset: u64) -> bool {
+        // Output that hasn't been indexed yet has just been written.
+        let time = self.index.at_offset(offset).unwrap_or_else(Local::now);
+        self.window.contains(time)
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    borrow::Cow,
+    io::{self, IsTerminal},
+};
+
+use super::{STYLE_RESET, ansi::escape_sequences};
+
+/// The width of a tab stop, as used by most terminals.
+const TAB_WIDTH: usize = 8;
+
+/// Hard-wraps lines of log output at the width of the terminal.
+///
+/// Wrapped lines continue with an indentation, so they're aligned under the content of the
+/// line instead of its prefixes, such as line numbers or timestamps.
+#[derive(Clone, Copy, Debug)]
+pub struct LineWrap {
+    width: usize,
+}
+
+impl LineWrap {
+    /// Detect the width of the terminal.
+    ///
+    /// Returns `None` if stdout isn't a terminal, in which case lines aren't wrapped.
+    pub fn detect() -> Option<Self> {
+        if !io::stdout().is_terminal() {
+            return None;
+        }
+        let (columns, _) = crossterm::terminal::size().ok()?;
+
+        Some(Self {
+            width: usize::from(columns),
+        })
+    }
+
+    /// Wrap the content of a line, which is printed right after the given prefix.
+    ///
+    /// ANSI escape sequences don't take up any space and are never split.
+    pub fn wrap<'a>(&self, prefix: &str, line: Cow<'a, str>) -> Cow<'a, str> {
+        let indent = advance_column(0, prefix);
+        // Keep at least a few columns for the content, even if the prefix is very wide.
+        let end = indent + self.width.saturating_sub(indent).max(TAB_WIDTH);
+        if advance_column(indent, &line) <= end {
+            return line;
+        }
+
+        let sequences = escape_sequences(&line);
+        let mut wrapped = String::with_capacity(line.len());
+        let mut column = indent;
+        for (index, character) in line.char_indices() {
+            if sequences.iter().any(|sequence| sequence.contains(&index)) {
+                wrapped.push(character);
+                continue;
+            }
+
+            let next_column = advance_char(column, character);
+            // Each line contains at least one character, even if it's a very wide tab.
+            if next_column > end && column > indent {
+                wrapped.push('\n');
+                wrapped.extend(std::iter::repeat_n(' ', indent));
+                column = advance_char(indent, character);
+            } else {
+                column = next_column;
+            }
+            wrapped.push(character);
+        }
+
+        Cow::Owned(wrapped)
+    }
+
+    /// Cut the content of a line off at the terminal width, which is marked by a trailing `…`.
+    ///
+    /// ANSI escape sequences don't take up any space and are never split. The styles are reset
+    /// at the end of lines that have been cut off, so they don't leak into the next line.
+    pub fn truncate<'a>(&self, prefix: &str, line: Cow<'a, str>) -> Cow<'a, str> {
+        let indent = advance_column(0, prefix);
+        if advance_column(indent, &line) <= self.width {
+            return line;
+        }
+
+        // Keep a column for the marker.
+        let end = self.width.saturating_sub(1);
+        let sequences = escape_sequences(&line);
+        let mut truncated = String::with_capacity(line.len());
+        let mut column = indent;
+        for (index, character) in line.char_indices() {
+            if sequences.iter().any(|sequence| sequence.contains(&index)) {
+                truncated.push(character);
+                continue;
+            }
+
+            column = advance_char(column, character);
+            if column > end {
+                break;
+            }
+            truncated.push(character);
+        }
+        truncated.push('…');
+        if !sequences.is_empty() {
+            truncated.push_str(STYLE_RESET);
+        }
+
+        Cow::Owned(truncated)
+    }
+}
+
+/// Return the column at which the cursor is after printing some text, starting at `column`.
+fn advance_column(column: usize, text: &str) -> usize {
+    let sequences = escape_sequences(text);
+    text.char_indices()
+        .filter(|(index, _)| !sequences.iter().any(|sequence| sequence.contains(index)))
+        .fold(column, |column, (_, character)| {
+            advance_char(column, character)
+        })
+}
+
+/// Return the column at which the cursor is after printing a single character.
+pub(super) fn advance_char(column: usize, character: char) -> usize {
+    match character {
+        '\t' => (column / TAB_WIDTH + 1) * TAB_WIDTH,
+        _ => column + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn wrap_with_prefix() {
+        let wrap = LineWrap { width: 16 };
+        // The line number prefix is 8 columns wide.
+        let wrapped = wrap.wrap("     1\t", "abcdefghijklmnopqrst".into());
+
+        assert_eq!(wrapped, "abcdefgh\n        ijklmnop\n        qrst");
+    }
+
+    #[test]
+    fn wrap_ignores_escape_sequences() {
+        let wrap = LineWrap { width: 10 };
+        let wrapped = wrap.wrap("", "\x1b[31mred\x1b[0m and more text".into());
+
+        assert_eq!(wrapped, "\x1b[31mred\x1b[0m and mo\nre text");
+    }
+
+    #[test]
+    fn truncate_with_prefix() {
+        let wrap = LineWrap { width: 10 };
+
+        assert_eq!(wrap.truncate("1 ", "abcdefghijkl".into()), "abcdefg…");
+        assert_eq!(
+            wrap.truncate("", "\x1b[31mred\x1b[0m and more".into()),
+            "\x1b[31mred\x1b[0m and m…\x1b[0m"
+        );
+        assert!(matches!(
+            wrap.truncate("1 ", "short".into()),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn short_lines_are_kept() {
+        let wrap = LineWrap { width: 10 };
+
+        assert!(matches!(
+            wrap.wrap("[12:00] ", "short".into()),
+            Cow::Borrowed(_)
+        ));
+    }
+}
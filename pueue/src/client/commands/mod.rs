@@ -5,13 +5,16 @@
 //! daemon, open some files on the filesystem, edit files and so on.
 //! All commands that cannot be simply handled by handling requests or using `pueue_lib`.
 
-use std::io::{Write, stdin, stdout};
+use std::{
+    io::{Write, stdin, stdout},
+    time::Duration,
+};
 
 use pueue_lib::{
     Client, Settings,
+    log::OutputStream,
     message::{Request, Response, TaskSelection},
     state::{PUEUE_DEFAULT_GROUP, State},
-    task::Task,
 };
 
 use crate::internal_prelude::*;
@@ -43,10 +46,18 @@ use clean::clean;
 use edit::edit;
 use enqueue::enqueue;
 use env::env;
-use follow::follow;
+pub use follow::{FollowOptions, LogSource, OnFinish};
+use follow::{follow, follow_offline, wait_for_label, wait_for_next_task};
 use group::group;
 use kill::kill;
-use log::print_logs;
+use log::{
+    BinaryOutput, JsonEnvs, LabelPattern, LogOptions, OutputPath, SystemLog, TimeWindow,
+    print_log_file, print_logs,
+};
+pub use log::{
+    JsonGroupBy, LineAmount, LineFilter, LogStatus, TaskLog, TimestampFormat, TimestampOptions,
+    fetch_task_log, is_broken_pipe,
+};
 use parallel::parallel;
 use pause::pause;
 use remove::remove;
@@ -96,6 +107,24 @@ fn group_or_default(group: &Option<String>) -> String {
         .unwrap_or_else(|| PUEUE_DEFAULT_GROUP.to_string())
 }
 
+/// Map the `--stdout-only` and `--stderr-only` flags to the stream that should be shown.
+fn output_stream(stdout_only: bool, stderr_only: bool) -> Option<OutputStream> {
+    match (stdout_only, stderr_only) {
+        (true, _) => Some(OutputStream::Stdout),
+        (_, true) => Some(OutputStream::Stderr),
+        _ => None,
+    }
+}
+
+/// Map the `--local` and `--remote` flags to the source that logs are forced to be read from.
+fn log_source(local: bool, remote: bool) -> Option<LogSource> {
+    match (local, remote) {
+        (true, _) => Some(LogSource::Local),
+        (_, true) => Some(LogSource::Remote),
+        _ => None,
+    }
+}
+
 // This is a helper function for easy retrieval of the current daemon state.
 // The current daemon state is often needed in more complex commands.
 pub async fn get_state(client: &mut Client) -> Result<State> {
@@ -111,22 +140,6 @@ pub async fn get_state(client: &mut Client) -> Result<State> {
     }
 }
 
-// This is a helper function for easy retrieval of a single task from the daemon state.
-pub async fn get_task(client: &mut Client, task_id: usize) -> Result<Option<Task>> {
-    // Create the message payload and send it to the daemon.
-    client.send_request(Request::Status).await?;
-
-    // Check if we can receive the response from the daemon
-    let response = client.receive_response().await?;
-
-    let state = match response {
-        Response::Status(state) => state,
-        _ => unreachable!(),
-    };
-
-    Ok(state.tasks.get(&task_id).cloned())
-}
-
 /// Most returned messages can be handled in a generic fashion.
 /// However, some commands require to continuously receive messages (streaming).
 ///
@@ -205,10 +218,95 @@ pub async fn handle_command(
         } => enqueue(client, style, task_ids, group, all, delay_until).await,
         SubCommand::Env { cmd } => env(client, style, cmd).await,
         SubCommand::Follow {
-            task_id,
+            task_ids,
+            group,
+            label,
+            next,
             lines,
+            max_bytes,
+            interval,
+            retry,
+            no_summary,
+            quiet_wait,
             timestamps,
-        } => follow(client, settings, style, task_id, lines, timestamps).await,
+            timestamp_format,
+            timestamps_utc,
+            filter,
+            invert_match,
+            ignore_case,
+            no_prefix,
+            stdout_only,
+            stderr_only,
+            interleaved,
+            json,
+            local,
+            remote,
+            timeout,
+            stats,
+            squash_repeats,
+            throttle,
+            follow_restarts,
+            on_finish,
+            tee,
+            clear,
+            exit_code,
+            no_header,
+            // Offline following doesn't need a connection, see [follow_offline_command].
+            offline: _,
+        } => {
+            let mut settings = settings;
+            if let Some(interval) = interval {
+                settings.client.follow_interval_ms = interval;
+            }
+            if throttle.is_some() {
+                settings.client.follow_throttle = throttle;
+            }
+            let timestamps = timestamps
+                .then(|| {
+                    TimestampOptions::from_settings(timestamp_format, timestamps_utc, &settings)
+                })
+                .transpose()?;
+            let filter = filter
+                .map(|pattern| LineFilter::new(&pattern, invert_match, ignore_case, style))
+                .transpose()?;
+            let selection = match (group, label) {
+                (Some(group), _) if next => {
+                    TaskSelection::TaskIds(vec![wait_for_next_task(client, &group).await?])
+                }
+                (Some(group), _) => TaskSelection::Group(group),
+                (None, Some(label)) => {
+                    let task_id = wait_for_label(client, &LabelPattern::new(&label)?).await?;
+                    TaskSelection::TaskIds(vec![task_id])
+                }
+                (None, None) => TaskSelection::TaskIds(task_ids),
+            };
+            let options = FollowOptions::default()
+                .lines(lines)
+                .max_bytes(max_bytes)
+                .timestamps(timestamps)
+                .no_prefix(no_prefix)
+                .filter(filter)
+                .stream(output_stream(stdout_only, stderr_only))
+                .interleaved(interleaved)
+                .json(json)
+                .retry(retry)
+                .summary(!no_summary)
+                .quiet_wait(quiet_wait)
+                .source(log_source(local, remote))
+                .timeout(timeout)
+                .stats(stats)
+                .squash_repeats(squash_repeats)
+                .on_finish(on_finish.unwrap_or(if follow_restarts {
+                    OnFinish::GracePeriod
+                } else {
+                    OnFinish::Exit
+                }))
+                .tee(tee)
+                .clear(clear)
+                .exit_code(exit_code)
+                .header(!no_header && !json);
+            follow(client, settings, style, selection, options).await
+        }
         SubCommand::Group { cmd, json } => group(client, style, cmd, json).await,
         SubCommand::Kill {
             task_ids,
@@ -216,19 +314,15 @@ pub async fn handle_command(
             all,
             signal,
         } => kill(client, settings, style, task_ids, group, all, signal).await,
-        SubCommand::Log {
-            task_ids,
-            group,
-            all,
-            json,
-            lines,
-            full,
-            timestamps,
-        } => {
-            print_logs(
-                client, settings, style, task_ids, group, all, json, lines, full, timestamps,
-            )
-            .await
+        SubCommand::Log { ref output, .. } => {
+            // Files are written without any styling.
+            let style = if output.is_some() {
+                &OutputStyle::new(&settings, false)
+            } else {
+                style
+            };
+            let options = log_options(&settings, style, subcommand)?;
+            print_logs(client, settings, style, options).await
         }
         SubCommand::Parallel {
             parallel_tasks,
@@ -297,6 +391,233 @@ pub async fn handle_command(
     }
 }
 
+/// Assemble the [LogOptions] of the `log` subcommand.
+///
+/// This is also used to print log files, which doesn't need a connection to the daemon.
+fn log_options(
+    settings: &Settings,
+    style: &OutputStyle,
+    subcommand: SubCommand,
+) -> Result<LogOptions> {
+    let SubCommand::Log {
+        task_ids,
+        group,
+        all,
+        label,
+        max_tasks,
+        include_status,
+        json,
+        json_pretty,
+        json_group_by,
+        json_lines,
+        with_env,
+        with_env_raw,
+        lines,
+        head,
+        max_bytes,
+        full,
+        timestamps,
+        timestamp_format,
+        timestamps_utc,
+        parse_json,
+        filter,
+        invert_match,
+        ignore_case,
+        context,
+        after_context,
+        before_context,
+        search,
+        count,
+        oneline,
+        since_last,
+        raw,
+        since,
+        until,
+        stdout_only,
+        stderr_only,
+        interleaved,
+        number,
+        squash_repeats,
+        strip_ansi,
+        header_to_stdout,
+        plain,
+        no_task_info,
+        separator,
+        summary,
+        annotate,
+        annotate_prefix,
+        text,
+        binary,
+        pager,
+        no_pager,
+        wrap,
+        no_wrap,
+        color_levels,
+        ansi_safe_truncate,
+        output,
+        with_header,
+        to_syslog,
+        #[cfg(all(feature = "journald", target_os = "linux"))]
+        to_journald,
+        archive,
+        follow,
+        file,
+        watch,
+        interval,
+    } = subcommand
+    else {
+        unreachable!("Only called for the log subcommand");
+    };
+    // Timestamps are needed to merge them into JSON lines.
+    let timestamps = (timestamps || parse_json.is_some())
+        .then(|| TimestampOptions::from_settings(timestamp_format, timestamps_utc, settings))
+        .transpose()?
+        .map(|options| options.json_key(parse_json));
+    let before_context = before_context.or(context).unwrap_or_default();
+    let after_context = after_context.or(context).unwrap_or_default();
+    let filter = filter
+        .map(|pattern| LineFilter::new(&pattern, invert_match, ignore_case, style))
+        .transpose()?
+        .map(|filter| filter.with_context(before_context, after_context));
+    let search = search
+        .map(|pattern| LineFilter::new(&pattern, false, ignore_case, style))
+        .transpose()?
+        .map(|search| search.with_context(before_context, after_context));
+    let system_log = to_syslog.then_some(SystemLog::Syslog);
+    #[cfg(all(feature = "journald", target_os = "linux"))]
+    let system_log = system_log.or(to_journald.then_some(SystemLog::Journald));
+    let binary = match (text, binary) {
+        (true, _) => BinaryOutput::Show,
+        (false, true) => BinaryOutput::Hide,
+        (false, false) => BinaryOutput::Terminal,
+    };
+    let envs = match (with_env, with_env_raw) {
+        (_, true) => JsonEnvs::Raw,
+        (true, false) => JsonEnvs::redacted(settings)?,
+        (false, false) => JsonEnvs::Hidden,
+    };
+    Ok(LogOptions::default()
+        .task_ids(task_ids)
+        .group(group)
+        .all(all)
+        .label(label.as_deref().map(LabelPattern::new).transpose()?)
+        .json(json)
+        .json_pretty(json_pretty)
+        .json_group_by(json_group_by.unwrap_or_default())
+        .json_lines(json_lines)
+        .envs(envs)
+        .lines(lines)
+        .head(head)
+        .max_bytes(max_bytes)
+        .full(full)
+        .timestamps(timestamps)
+        .filter(filter)
+        .search(search)
+        .count(count)
+        .oneline(oneline)
+        .since_last(since_last)
+        .raw(raw)
+        .window(TimeWindow::new(since, until))
+        .stream(output_stream(stdout_only, stderr_only))
+        .interleaved(interleaved)
+        .line_numbers(number)
+        .squash_repeats(squash_repeats)
+        .wrap((wrap || settings.client.wrap_logs) && !no_wrap)
+        .color_levels(color_levels)
+        .ansi_safe_truncate(ansi_safe_truncate)
+        .strip_ansi(strip_ansi)
+        .header_to_stdout(header_to_stdout)
+        .plain(plain)
+        .no_task_info(no_task_info)
+        .separator(separator)
+        .summary(summary)
+        .binary(binary)
+        .annotate(annotate.then(|| {
+            annotate_prefix.unwrap_or_else(|| settings.client.log_annotation_prefix.clone())
+        }))
+        .pager((pager || settings.client.pager) && !no_pager)
+        .output(output.map(|path| OutputPath { path, with_header }))
+        .system_log(system_log)
+        .max_tasks(max_tasks)
+        .include_status(include_status)
+        .archive(archive)
+        .follow(follow)
+        .file(file)
+        .watch(watch.then(|| Duration::from_millis(interval))))
+}
+
+/// Follow the local log files of `pueue follow --offline`, without connecting to the daemon.
+pub async fn follow_offline_command(
+    mut settings: Settings,
+    style: &OutputStyle,
+    subcommand: SubCommand,
+) -> Result<()> {
+    let SubCommand::Follow {
+        task_ids,
+        lines,
+        interval,
+        timestamps,
+        timestamp_format,
+        timestamps_utc,
+        filter,
+        invert_match,
+        ignore_case,
+        no_prefix,
+        stdout_only,
+        stderr_only,
+        interleaved,
+        json,
+        squash_repeats,
+        throttle,
+        tee,
+        clear,
+        ..
+    } = subcommand
+    else {
+        unreachable!("Only called for the follow subcommand");
+    };
+
+    if let Some(interval) = interval {
+        settings.client.follow_interval_ms = interval;
+    }
+    if throttle.is_some() {
+        settings.client.follow_throttle = throttle;
+    }
+    let timestamps = timestamps
+        .then(|| TimestampOptions::from_settings(timestamp_format, timestamps_utc, &settings))
+        .transpose()?;
+    let filter = filter
+        .map(|pattern| LineFilter::new(&pattern, invert_match, ignore_case, style))
+        .transpose()?;
+
+    let options = FollowOptions::default()
+        .lines(lines)
+        .timestamps(timestamps)
+        .no_prefix(no_prefix)
+        .filter(filter)
+        .stream(output_stream(stdout_only, stderr_only))
+        .interleaved(interleaved)
+        .json(json)
+        .squash_repeats(squash_repeats)
+        .tee(tee)
+        .clear(clear);
+    follow_offline(&settings, style, task_ids, &options).await
+}
+
+/// Print the log file of `pueue log --file`, without connecting to the daemon.
+pub fn print_log_file_command(
+    settings: &Settings,
+    style: &OutputStyle,
+    subcommand: SubCommand,
+) -> Result<()> {
+    let options = log_options(settings, style, subcommand)?;
+    let Some(path) = options.file.clone() else {
+        bail!("No log file has been given");
+    };
+
+    print_log_file(settings, style, &path, options)
+}
+
 /// Prints a warning and prompt for a given action and tasks.
 /// Returns `Ok(())` if the action was confirmed.
 pub fn handle_user_confirmation(action: &str, task_ids: &[usize]) -> Result<()> {
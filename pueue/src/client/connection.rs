@@ -0,0 +1,134 @@
+use std::str::FromStr;
+
+use pueue_lib::settings::Settings;
+#[cfg(not(target_os = "windows"))]
+use pueue_lib::settings::expand_home;
+
+use crate::client::cli::CliArguments;
+
+/// A TCP address of a daemon, as given via `--host`, such as `server`, `server:6924` or
+/// `[::1]:6924`. Without a port, the configured port is used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HostAddress {
+    pub host: String,
+    pub port: Option<String>,
+}
+
+impl FromStr for HostAddress {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        // IPv6 addresses contain colons, so they need to be put into brackets to add a port.
+        let (host, port) = if let Some(address) = value.strip_prefix('[') {
+            match address.split_once(']') {
+                Some((host, "")) => (host, None),
+                Some((host, port)) => match port.strip_prefix(':') {
+                    Some(port) => (host, Some(port)),
+                    None => return Err(format!("'{value}' isn't a valid address")),
+                },
+                None => return Err(format!("'{value}' is missing a closing bracket")),
+            }
+        } else {
+            match value.split_once(':') {
+                Some((host, port)) if !port.contains(':') => (host, Some(port)),
+                // A plain IPv6 address without a port.
+                _ => (value, None),
+            }
+        };
+
+        if host.is_empty() {
+            return Err("The host mustn't be empty".to_string());
+        }
+        if let Some(port) = port {
+            port.parse::<u16>()
+                .map_err(|_| format!("'{port}' isn't a valid port"))?;
+        }
+
+        Ok(HostAddress {
+            host: host.to_string(),
+            port: port.map(ToString::to_string),
+        })
+    }
+}
+
+/// Connect to the daemon via the `--socket` or `--host` of a single invocation, instead of the
+/// configured endpoint. The shared secret and TLS certificate are still taken from the config.
+///
+/// If the endpoint differs from the configured one, it most likely belongs to another daemon,
+/// whose log files aren't in our pueue directory. The logs are thereby sent by the daemon.
+pub fn override_connection(settings: &mut Settings, opt: &CliArguments) {
+    let shared = &mut settings.shared;
+    let mut changed = false;
+
+    #[cfg(not(target_os = "windows"))]
+    if let Some(socket) = &opt.socket {
+        changed = !shared.use_unix_socket || shared.unix_socket_path() != expand_home(socket);
+        shared.use_unix_socket = true;
+        shared.unix_socket_path = Some(socket.clone());
+    }
+
+    if let Some(HostAddress { host, port }) = opt.host.clone() {
+        let port = port.unwrap_or_else(|| shared.port.clone());
+        changed = host != shared.host || port != shared.port;
+        #[cfg(not(target_os = "windows"))]
+        {
+            changed |= shared.use_unix_socket;
+            shared.use_unix_socket = false;
+        }
+        shared.host = host;
+        shared.port = port;
+    }
+
+    if changed {
+        settings.client.read_local_logs = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn address(host: &str, port: Option<&str>) -> Result<HostAddress, String> {
+        Ok(HostAddress {
+            host: host.to_string(),
+            port: port.map(ToString::to_string),
+        })
+    }
+
+    #[test]
+    fn parse_host_address() {
+        assert_eq!("server".parse(), address("server", None));
+        assert_eq!("server:6924".parse(), address("server", Some("6924")));
+        assert_eq!("::1".parse(), address("::1", None));
+        assert_eq!("[::1]".parse(), address("::1", None));
+        assert_eq!("[::1]:6924".parse(), address("::1", Some("6924")));
+        assert!("server:port".parse::<HostAddress>().is_err());
+        assert!(":6924".parse::<HostAddress>().is_err());
+        assert!("[::1".parse::<HostAddress>().is_err());
+    }
+
+    #[test]
+    fn read_local_logs_of_other_daemons() {
+        let mut settings = Settings::default();
+        settings.client.read_local_logs = true;
+        #[cfg(not(target_os = "windows"))]
+        {
+            settings.shared.use_unix_socket = false;
+        }
+
+        // The configured daemon can still be reached via its local logs.
+        let mut configured = settings.clone();
+        let opt = CliArguments::parse_from(["pueue", "--host", &settings.shared.host]);
+        override_connection(&mut configured, &opt);
+        assert!(configured.client.read_local_logs);
+
+        let opt = CliArguments::parse_from(["pueue", "--host", "server:6924"]);
+        override_connection(&mut settings, &opt);
+        assert_eq!(settings.shared.host, "server");
+        assert_eq!(settings.shared.port, "6924");
+        assert!(!settings.client.read_local_logs);
+    }
+}
@@ -1,5 +1,6 @@
 //! This module contains small helper functions that're useful for printing or displaying structured
 //! information. Most of these functions are shared between multiple commands.
+use chrono::TimeDelta;
 use crossterm::style::{Attribute, Color};
 use pueue_lib::state::{Group, GroupStatus};
 
@@ -31,3 +32,17 @@ pub fn get_group_headline(name: &str, group: &Group, style: &OutputStyle) -> Str
 
     format!("{} ({} parallel): {}", name, group.parallel_tasks, status)
 }
+
+/// Format the runtime of a task as hours, minutes and seconds, e.g. `1h 2m 3s`.
+pub fn format_runtime(runtime: TimeDelta) -> String {
+    let seconds = runtime.num_seconds().max(0);
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    if hours > 0 {
+        format!("{hours}h {minutes}m {seconds}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
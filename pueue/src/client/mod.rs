@@ -1,9 +1,14 @@
 pub mod cli;
 /// All subcommands have their dedicated file and functions in here.
 mod commands;
+/// Overrides of the daemon endpoint for a single invocation.
+pub mod connection;
 pub(crate) mod display_helper;
 /// The [`OutputStyle`](style::OutputStyle) helper, responsible for formatting and styling output
 /// based on the current settings.
 pub mod style;
 
-pub use commands::handle_command;
+pub use commands::{
+    TaskLog, fetch_task_log, follow_offline_command, handle_command, is_broken_pipe,
+    print_log_file_command,
+};
@@ -1,7 +1,33 @@
+use std::{
+    env,
+    io::{IsTerminal, stdout},
+};
+
 use comfy_table::{Attribute as ComfyAttribute, Cell, Color as ComfyColor};
 use crossterm::style::{Attribute, Color, Stylize, style};
 use pueue_lib::settings::Settings;
 
+use super::cli::ColorChoice;
+
+/// Determine whether the output should be styled.
+///
+/// `--color=never` always disables styling. Otherwise, a non-empty `CLICOLOR_FORCE` other than
+/// `0` enables styling, while a non-empty `NO_COLOR` disables it, even with `--color=always`.
+/// If neither is set, styling depends on the `--color` option and whether stdout is a terminal.
+pub fn style_enabled(color: &ColorChoice) -> bool {
+    let clicolor_force =
+        env::var_os("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0");
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+
+    match color {
+        ColorChoice::Never => false,
+        _ if clicolor_force => true,
+        _ if no_color => false,
+        ColorChoice::Always => true,
+        ColorChoice::Auto => stdout().is_terminal(),
+    }
+}
+
 /// OutputStyle wrapper for actual colors depending on settings
 /// - Enables styles if color mode is 'always', or if color mode is 'auto' and output is a tty.
 /// - Using dark colors if dark_mode is enabled
@@ -43,6 +69,9 @@ impl OutputStyle {
                 Color::Green => ComfyColor::DarkGreen,
                 Color::Red => ComfyColor::DarkRed,
                 Color::Yellow => ComfyColor::DarkYellow,
+                Color::Cyan | Color::DarkCyan => ComfyColor::DarkCyan,
+                Color::Magenta | Color::DarkMagenta => ComfyColor::DarkMagenta,
+                Color::Blue => ComfyColor::DarkBlue,
                 _ => ComfyColor::White,
             };
         }
@@ -51,6 +80,11 @@ impl OutputStyle {
             Color::Green => ComfyColor::Green,
             Color::Red => ComfyColor::Red,
             Color::Yellow => ComfyColor::Yellow,
+            Color::Cyan => ComfyColor::Cyan,
+            Color::DarkCyan => ComfyColor::DarkCyan,
+            Color::Magenta => ComfyColor::Magenta,
+            Color::DarkMagenta => ComfyColor::DarkMagenta,
+            Color::Blue => ComfyColor::Blue,
             _ => ComfyColor::White,
         }
     }
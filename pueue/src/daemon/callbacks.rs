@@ -4,7 +4,7 @@ use chrono::{DateTime, Local};
 use handlebars::{Handlebars, RenderError};
 use pueue_lib::{
     Settings, Task, TaskResult, TaskStatus,
-    log::{get_log_path, read_last_log_file_lines},
+    log::{get_compressed_log_path, get_log_path, read_last_log_file_lines},
 };
 
 use crate::{
@@ -105,7 +105,11 @@ pub fn build_callback_command(
         parameters.insert("output", "".to_string());
     }
 
-    let out_path = get_log_path(task.id, &settings.shared.pueue_directory());
+    let mut out_path = get_log_path(task.id, &settings.shared.pueue_directory());
+    // The log file has been compressed, once the task finished.
+    if !out_path.exists() {
+        out_path = get_compressed_log_path(task.id, &settings.shared.pueue_directory());
+    }
     // Using Display impl of PathBuf which isn't necessarily a perfect
     // representation of the path but should work for most cases here
     parameters.insert("output_path", out_path.display().to_string());
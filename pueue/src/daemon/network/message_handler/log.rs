@@ -1,18 +1,31 @@
-use std::{collections::BTreeMap, io::Read, path::Path, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
+use chrono::Local;
 use pueue_lib::{
-    Settings, failure_msg,
+    Error, Settings, Task, failure_msg,
     log::*,
     message::*,
     network::protocol::{GenericStream, send_response},
 };
 
-use crate::{daemon::internal_state::SharedState, internal_prelude::*};
+use crate::{
+    daemon::{internal_state::SharedState, network::response_helper::ensure_group_exists},
+    internal_prelude::*,
+};
 
 /// Invoked when calling `pueue log`.
 /// Return tasks and their output to the client.
 pub fn get_log(settings: &Settings, state: &SharedState, message: LogRequest) -> Response {
     let state = { state.lock().unwrap().clone() };
+    let compression = message.compression;
+    let level = message.compression_level;
 
     let task_ids = match message.tasks {
         TaskSelection::All => state.tasks().keys().cloned().collect(),
@@ -26,26 +39,68 @@ pub fn get_log(settings: &Settings, state: &SharedState, message: LogRequest) ->
             // We send log output and the task at the same time.
             // This isn't as efficient as sending the raw compressed data directly,
             // but it's a lot more convenient for now.
-            let (output, output_complete) = if message.send_logs {
-                match read_and_compress_log_file(
-                    *task_id,
-                    &settings.shared.pueue_directory(),
-                    message.lines,
-                ) {
-                    Ok((output, output_complete)) => (Some(output), output_complete),
+            let pueue_directory = settings.shared.pueue_directory();
+            let task_lines = message.task_lines.get(task_id).copied();
+            let result = get_stream_log_file_handle(*task_id, message.stream, &pueue_directory)
+                .and_then(|(mut file, stream)| {
+                    let (output, complete, head_size) = match message.head {
+                        Some(head) => {
+                            let tail = task_lines.or(message.tail);
+                            compress_log_file_head(&mut file, head, tail, compression, level).map(
+                                |(output, complete, head_size)| (output, complete, Some(head_size)),
+                            )?
+                        }
+                        None => {
+                            let lines = task_lines.or(message.lines);
+                            compress_log_file(&mut file, lines, compression, level)
+                                .map(|(output, complete)| (output, complete, None))?
+                        }
+                    };
+                    // The omitted lines are counted, so the client can tell how many there are.
+                    let total_lines = if complete || !message.send_logs {
+                        None
+                    } else {
+                        Some(count_total_lines(&mut file)?)
+                    };
+                    Ok((output, complete, head_size, stream, total_lines))
+                });
+            let (output, output_complete, head_size, stream, total_lines) = if message.send_logs {
+                match result {
+                    Ok((output, output_complete, head_size, stream, total_lines)) => (
+                        Some(output),
+                        output_complete,
+                        head_size,
+                        stream,
+                        total_lines,
+                    ),
                     Err(err) => {
                         // Fail early if there's some problem with getting the log output
                         return failure_msg!("Failed reading process output file: {err:?}");
                     }
                 }
             } else {
-                (None, true)
+                (None, true, None, None, None)
+            };
+
+            // Only the size of the output is sent, if that's all the client asked for.
+            // Tasks that haven't been started yet don't have any output.
+            let (total_lines, total_bytes) = if message.count {
+                get_log_size(*task_id, message.stream, &pueue_directory)
+                    .map_or((None, None), |(lines, bytes)| (Some(lines), Some(bytes)))
+            } else {
+                (total_lines, None)
             };
 
             let task_log = TaskLogResponse {
                 task: task.clone(),
                 output,
                 output_complete,
+                head_size,
+                stream,
+                first_line: None,
+                total_lines,
+                total_bytes,
+                compression,
             };
             tasks.insert(*task_id, task_log);
         }
@@ -53,33 +108,281 @@ pub fn get_log(settings: &Settings, state: &SharedState, message: LogRequest) ->
     Response::Log(tasks)
 }
 
+/// The maximum amount of uncompressed log output that's sent in a single [Response::LogChunk].
+const LOG_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Invoked when calling `pueue log` with chunked logs.
+///
+/// The tasks are sent first, followed by their log output in a series of [Response::LogChunk]
+/// messages. That way, the client can start printing while the output is still being sent,
+/// instead of buffering the full output of all tasks in a single payload.
+///
+/// If the output of a task can't be read, an error chunk is sent for that task and the output of
+/// the remaining tasks is still sent.
+pub async fn send_log_chunks(
+    settings: &Settings,
+    stream: &mut GenericStream,
+    state: &SharedState,
+    message: LogRequest,
+) -> Result<Response> {
+    // Only the requested tasks are cloned, as the log files are read without holding the lock.
+    let requested_tasks: Vec<Task> = {
+        let state = state.lock().unwrap();
+        let task_ids = match &message.tasks {
+            TaskSelection::All => state.tasks().keys().cloned().collect(),
+            TaskSelection::TaskIds(task_ids) => task_ids.clone(),
+            TaskSelection::Group(group) => state.task_ids_in_group(group),
+        };
+        task_ids
+            .iter()
+            .filter_map(|task_id| state.tasks().get(task_id).cloned())
+            .collect()
+    };
+    let pueue_directory = settings.shared.pueue_directory();
+
+    // Open all log files first, so we can tell the client whether the output is complete.
+    let mut tasks = BTreeMap::new();
+    // The output is sent in ascending task order, which is the order in which it's printed.
+    let mut logs = BTreeMap::new();
+    for task in requested_tasks {
+        let task_id = task.id;
+        let log = open_chunked_log(task_id, &message, &pueue_directory)
+            .map_err(|err| format!("Failed reading process output file: {err:?}"));
+
+        // Tasks whose output can't be read are still sent, their error follows as a chunk.
+        let (output_complete, head_size, output_stream, first_line, total_lines) = match &log {
+            Ok(log) => (
+                log.output_complete,
+                log.head_size,
+                log.stream,
+                log.first_line,
+                log.total_lines,
+            ),
+            Err(_) => (true, None, None, None, None),
+        };
+        let task_log = TaskLogResponse {
+            task,
+            output: None,
+            output_complete,
+            head_size,
+            stream: output_stream,
+            first_line,
+            total_lines,
+            total_bytes: None,
+            compression: message.compression,
+        };
+        tasks.insert(task_id, task_log);
+        logs.insert(task_id, log);
+    }
+    send_response(Response::Log(tasks), stream).await?;
+
+    for (task_id, log) in logs {
+        let error = match log {
+            Ok(log) => send_log_ranges(task_id, log, &message, stream).await?,
+            Err(error) => Some(error),
+        };
+        if let Some(error) = error {
+            let response = Response::LogChunk(LogChunkResponse {
+                task_id,
+                output: Vec::new(),
+                error: Some(error),
+            });
+            send_response(response, stream).await?;
+        }
+    }
+
+    Ok(Response::Close)
+}
+
+/// The log file of a task and the parts of it that're sent in chunks.
+struct ChunkedLog {
+    file: File,
+    ranges: Vec<Range<u64>>,
+    output_complete: bool,
+    head_size: Option<u64>,
+    stream: Option<OutputStream>,
+    first_line: Option<usize>,
+    total_lines: Option<usize>,
+}
+
+/// Open the log file of a task and determine the parts of it that should be sent.
+/// If requested, only the first and/or last few lines of the file are sent.
+fn open_chunked_log(
+    task_id: usize,
+    message: &LogRequest,
+    pueue_directory: &Path,
+) -> Result<ChunkedLog, Error> {
+    let (mut file, stream) = get_stream_log_file_handle(task_id, message.stream, pueue_directory)?;
+
+    let tail = match message.head {
+        Some(_) => message.tail,
+        None => message.lines,
+    };
+    let tail = message.task_lines.get(&task_id).copied().or(tail);
+    let (ranges, output_complete) = head_and_tail_ranges(&mut file, message.head, tail)?;
+
+    // The last lines are numbered by the amount of lines in front of them.
+    let first_line = match (message.line_numbers, ranges.last()) {
+        (true, Some(range)) => Some(count_lines(&mut file, range.start)? + 1),
+        (true, None) => Some(1),
+        (false, _) => None,
+    };
+
+    // The omitted lines are counted, so the client can tell how many there are.
+    let total_lines = if output_complete {
+        None
+    } else {
+        Some(count_total_lines(&mut file)?)
+    };
+
+    Ok(ChunkedLog {
+        head_size: message
+            .head
+            .and_then(|_| ranges.first())
+            .map(|range| range.end - range.start),
+        file,
+        ranges,
+        output_complete,
+        stream,
+        first_line,
+        total_lines,
+    })
+}
+
+/// Send the parts of a task's log file in a series of chunks.
+///
+/// Returns the reason why the file couldn't be read, in which case no further output of the task
+/// is sent. Only errors while sending are returned as errors.
+async fn send_log_ranges(
+    task_id: usize,
+    log: ChunkedLog,
+    message: &LogRequest,
+    stream: &mut GenericStream,
+) -> Result<Option<String>> {
+    let ChunkedLog {
+        mut file, ranges, ..
+    } = log;
+    let read_error =
+        |err: std::io::Error| Some(format!("Failed reading process output file: {err:?}"));
+
+    // Each range is sent separately, so chunks never cross the end of the head.
+    for range in ranges {
+        if let Err(err) = file.seek(SeekFrom::Start(range.start)) {
+            return Ok(read_error(err));
+        }
+        let mut reader = (&mut file).take(range.end - range.start);
+
+        // Output that has been read, but hasn't been sent yet.
+        let mut pending = Vec::new();
+        let mut buffer = vec![0; LOG_CHUNK_SIZE];
+        loop {
+            let read_bytes = match reader.read(&mut buffer) {
+                Ok(read_bytes) => read_bytes,
+                Err(err) => return Ok(read_error(err)),
+            };
+            if read_bytes == 0 {
+                break;
+            }
+            pending.extend_from_slice(&buffer[..read_bytes]);
+
+            // Only send complete lines, unless a single line exceeds the chunk size.
+            let split_at = match pending.iter().rposition(|byte| *byte == b'\n') {
+                Some(position) => position + 1,
+                None if pending.len() >= LOG_CHUNK_SIZE => pending.len(),
+                None => continue,
+            };
+            let rest = pending.split_off(split_at);
+            send_log_chunk(
+                task_id,
+                &pending,
+                message.compression,
+                message.compression_level,
+                stream,
+            )
+            .await?;
+            pending = rest;
+        }
+
+        if !pending.is_empty() {
+            send_log_chunk(
+                task_id,
+                &pending,
+                message.compression,
+                message.compression_level,
+                stream,
+            )
+            .await?;
+        }
+    }
+
+    Ok(None)
+}
+
+/// Compress a chunk of log output with the requested codec and level and send it to the client.
+async fn send_log_chunk(
+    task_id: usize,
+    output: &[u8],
+    compression: LogCompression,
+    level: Option<i32>,
+    stream: &mut GenericStream,
+) -> Result<()> {
+    let mut compressed = Vec::new();
+    {
+        let mut compressor = compression.compressor_with_level(&mut compressed, level)?;
+        compressor
+            .write_all(output)
+            .context("Failed to compress log output")?;
+    }
+
+    let response = Response::LogChunk(LogChunkResponse {
+        task_id,
+        output: compressed,
+        error: None,
+    });
+    send_response(response, stream).await?;
+
+    Ok(())
+}
+
 /// Handle the continuous stream of a some log output.
 ///
 /// It's not actually a stream in the sense of a low-level network stream, but rather a series of
 /// [Response::Stream] messages, that each send a portion of new log output.
 ///
 /// It's basically our own chunked stream implementation on top of the protocol we established.
+///
+/// Multiple tasks can be followed at the same time. In that case, tasks that finish or get
+/// removed are simply dropped from the stream, which is closed once all tasks are done.
+/// When following a group, tasks of that group that start running are added to the stream.
 pub async fn follow_log(
     pueue_directory: &Path,
     stream: &mut GenericStream,
     state: &SharedState,
     message: StreamRequest,
 ) -> Result<Response> {
-    // The user can specify the id of the task they want to follow
-    // If the id isn't specified and there's only a single running task, this task will be used.
+    // The user can specify the ids of the tasks they want to follow
+    // If no id is specified and there's only a single running task, this task will be used.
     // However, if there are multiple running tasks, the user will have to specify an id.
-    //
-    // NOTE: Read the docs of [StreamRequest] why we're not yet supporting selection of multiple
-    // tasks yet.
-    let task_id = if let TaskSelection::TaskIds(task_ids) = message.tasks {
-        task_ids.first().cloned()
-    } else {
-        None
-    };
-
-    let task_id = match task_id {
-        Some(id) => id,
-        None => {
+    let mut group = None;
+    // Tasks of the group that finished before we started following are ignored.
+    let mut seen_tasks = BTreeSet::new();
+    let task_ids = match message.tasks {
+        TaskSelection::TaskIds(task_ids) if !task_ids.is_empty() => task_ids,
+        TaskSelection::Group(group_name) => {
+            let mut state = state.lock().unwrap();
+            if let Err(response) = ensure_group_exists(&mut state, &group_name) {
+                return Ok(response);
+            }
+            seen_tasks = state
+                .tasks()
+                .iter()
+                .filter(|(_, task)| task.group == group_name && task.is_done())
+                .map(|(task_id, _)| *task_id)
+                .collect();
+            group = Some(group_name);
+            Vec::new()
+        }
+        _ => {
             // Get all ids of running tasks
             let state = state.lock().unwrap();
             let running_ids: Vec<_> = state
@@ -93,7 +396,7 @@ pub async fn follow_log(
                 0 => {
                     return Ok(create_failure_response("There are no running tasks."));
                 }
-                1 => running_ids[0],
+                1 => vec![running_ids[0]],
                 _ => {
                     let running_ids = running_ids
                         .iter()
@@ -107,93 +410,225 @@ pub async fn follow_log(
             }
         }
     };
+    // Errors of a single followed task are reported directly, as we cannot continue anyway.
+    let single_task = group.is_none() && task_ids.len() == 1;
+
+    // Ensure that all tasks exist.
+    {
+        let state = state.lock().unwrap();
+        if task_ids.iter().any(|id| !state.tasks().contains_key(id)) {
+            return Ok(create_failure_response(
+                "Pueue: The task to be followed doesn't exist.",
+            ));
+        }
+    }
+
+    let mut followed_logs: BTreeMap<usize, FollowedLog> = task_ids
+        .into_iter()
+        .map(|task_id| (task_id, FollowedLog::new(task_id, pueue_directory)))
+        .collect();
 
-    // It might be that the task is not yet running.
-    // Ensure that it exists and is started.
     loop {
+        // Check for each task whether it:
+        // 1. Still exist
+        // 2. Has been started
+        // 3. Is still running
+        //
+        // It might be that some tasks aren't running yet, in which case we wait for them to start.
+        // Tasks that finished are read one last time and then removed from the stream.
+        let mut started_tasks = Vec::new();
         {
             let state = state.lock().unwrap();
-            let Some(task) = state.tasks().get(&task_id) else {
-                return Ok(create_failure_response(
-                    "Pueue: The task to be followed doesn't exist.",
-                ));
-            };
-            // The task is running or finished, we can start to follow.
-            if task.is_running() || task.is_done() {
-                break;
+
+            // Pick up all tasks of the group that started in the meantime.
+            // Tasks are also picked up if they already finished in between two checks.
+            if let Some(group) = &group {
+                for (task_id, task) in state.tasks().iter() {
+                    let started = task.is_running() || task.is_done();
+                    if task.group == *group && started && seen_tasks.insert(*task_id) {
+                        followed_logs.insert(*task_id, FollowedLog::new(*task_id, pueue_directory));
+                    }
+                }
             }
-        }
-        tokio::time::sleep(Duration::from_millis(1000)).await;
-    }
 
-    let mut handle = match get_log_file_handle(task_id, pueue_directory) {
-        Err(_) => {
-            return Ok(create_failure_response(
-                "Couldn't find output files for task. Maybe it finished? Try `log`",
-            ));
-        }
-        Ok(handle) => handle,
-    };
+            for (task_id, log) in followed_logs.iter_mut() {
+                let Some(task) = state.tasks().get(task_id) else {
+                    if single_task {
+                        return Ok(create_failure_response(
+                            "Pueue: The followed task has been removed.",
+                        ));
+                    }
+                    log.finished = true;
+                    continue;
+                };
 
-    // Get the output path.
-    // We need to check continuously, whether the file still exists,
-    // since the file can go away (e.g. due to finishing a task).
-    let path = get_log_path(task_id, pueue_directory);
-
-    // If `lines` is passed as an option, we only want to show the last `X` lines.
-    // To achieve this, we seek the file handle to the start of the `Xth` line
-    // from the end of the file.
-    // The loop following this section will then only copy those last lines to stdout.
-    if let Some(lines) = message.lines {
-        if let Err(err) = seek_to_last_lines(&mut handle, lines) {
-            eprintln!("Error seeking to last lines from log: {err}");
+                let started = task.is_running() || task.is_done();
+                if log.handle.is_none() && started {
+                    started_tasks.push(*task_id);
+                }
+                // Tell the client once, whenever the task gets paused.
+                log.pause_notice = task.is_paused() && !log.paused;
+                log.paused = task.is_paused();
+                // The task is no longer running, read its output a last time.
+                if (log.handle.is_some() || started) && !task.is_running() {
+                    log.finished = true;
+                }
+            }
         }
-    }
 
-    loop {
-        // Check whether the file still exists. Exit if it doesn't.
-        if !path.exists() {
-            return Ok(create_success_response(
-                "Pueue: Log file has gone away. Has the task been removed?",
-            ));
-        }
-        // Read the next chunk of text from the last position.
-        let mut buffer = Vec::new();
+        // Open the log files of tasks that just started.
+        for task_id in started_tasks {
+            let Some(log) = followed_logs.get_mut(&task_id) else {
+                continue;
+            };
+            let mut handle =
+                match get_stream_log_file_handle(task_id, message.stream, pueue_directory) {
+                    Err(_) => {
+                        if single_task {
+                            return Ok(create_failure_response(
+                                "Couldn't find output files for task. Maybe it finished? Try `log`",
+                            ));
+                        }
+                        log.finished = true;
+                        continue;
+                    }
+                    Ok((handle, stream)) => {
+                        // Check the existence of the file that's actually being followed.
+                        if let Some(stream) = stream {
+                            log.path = get_stream_log_path(task_id, stream, pueue_directory);
+                        }
+                        handle
+                    }
+                };
 
-        if let Err(err) = handle.read_to_end(&mut buffer) {
-            return Ok(create_failure_response(format!("Pueue Error: {err}")));
-        };
-        let text = String::from_utf8_lossy(&buffer).to_string();
-
-        // Only send a message, if there's actual new content.
-        if !text.is_empty() {
-            let mut logs = BTreeMap::new();
-            logs.insert(task_id, text);
-            // Send the next chunk.
-            let response = Response::Stream(StreamResponse { logs });
-            send_response(response, stream).await?;
+            match (message.offsets.get(&task_id), message.lines) {
+                // The client is resuming to follow this task, continue where it left off.
+                (Some(offset), _) => {
+                    if let Err(err) = handle.seek(SeekFrom::Start(*offset)) {
+                        eprintln!("Error seeking to previous log offset: {err}");
+                    }
+                }
+                // If `lines` is passed as an option, we only want to show the last `X` lines.
+                // To achieve this, we seek the file handle to the start of the `Xth` line
+                // from the end of the file.
+                // The loop following this section will then only copy those last lines to
+                // stdout. With `0` lines, only output that's written from now on is sent.
+                (None, lines) => {
+                    if let Some(lines) = lines {
+                        if let Err(err) = seek_to_last_lines(&mut handle, lines) {
+                            eprintln!("Error seeking to last lines from log: {err}");
+                        }
+                    }
+                    // If both limits are given, the smaller amount of output is sent.
+                    // Logs that are smaller than the limit are sent from their beginning.
+                    if let Some(max_bytes) = message.max_bytes {
+                        let lines_start = handle.stream_position().unwrap_or_default();
+                        if let Err(err) = seek_to_last_bytes(&mut handle, max_bytes) {
+                            eprintln!("Error seeking to last bytes from log: {err}");
+                        }
+                        if handle.stream_position().unwrap_or_default() < lines_start {
+                            if let Err(err) = handle.seek(SeekFrom::Start(lines_start)) {
+                                eprintln!("Error seeking to last lines from log: {err}");
+                            }
+                        }
+                    }
+                }
+            }
+            log.handle = Some(handle);
         }
 
-        // Check if the task in question does:
-        // 1. Still exist
-        // 2. Is still running
-        //
-        // In case it's not, close the stream.
-        {
-            let state = state.lock().unwrap();
-            let Some(task) = state.tasks().get(&task_id) else {
-                return Ok(create_failure_response(
-                    "Pueue: The followed task has been removed.",
-                ));
+        let mut logs = BTreeMap::new();
+        let mut offsets = BTreeMap::new();
+        let mut starts = BTreeMap::new();
+        let mut paused = BTreeSet::new();
+        for (task_id, log) in followed_logs.iter_mut() {
+            let Some(handle) = log.handle.as_mut() else {
+                continue;
+            };
+            if log.pause_notice {
+                paused.insert(*task_id);
+            }
+
+            // Check whether the file still exists, since the file can go away
+            // (e.g. due to a reset). Log files of finished tasks may have been compressed.
+            let exists = log.path.exists()
+                || (log.path == get_log_path(*task_id, pueue_directory)
+                    && get_compressed_log_path(*task_id, pueue_directory).exists());
+            if !exists {
+                if single_task {
+                    return Ok(create_success_response(
+                        "Pueue: Log file has gone away. Has the task been removed?",
+                    ));
+                }
+                log.finished = true;
+                continue;
+            }
+
+            // Read the next chunk of text from the last position.
+            let start = handle.stream_position();
+            let mut buffer = Vec::new();
+            if let Err(err) = handle.read_to_end(&mut buffer) {
+                return Ok(create_failure_response(format!("Pueue Error: {err}")));
             };
+            let text = String::from_utf8_lossy(&buffer).to_string();
 
-            // The task is done, just close the stream.
-            if !task.is_running() {
-                return Ok(Response::Close);
+            // Only send output, if there's actual new content.
+            if !text.is_empty() {
+                logs.insert(*task_id, text);
+                if let Ok(offset) = handle.stream_position() {
+                    offsets.insert(*task_id, offset);
+                }
+                if let Ok(start) = start {
+                    starts.insert(*task_id, start);
+                }
             }
         }
 
+        // Send the next chunk.
+        if !logs.is_empty() || !paused.is_empty() {
+            let response = Response::Stream(StreamResponse {
+                logs,
+                offsets,
+                starts,
+                paused,
+                daemon_time: Some(Local::now()),
+            });
+            send_response(response, stream).await?;
+        }
+
+        // All tasks are done, just close the stream.
+        followed_logs.retain(|_, log| !log.finished);
+        if followed_logs.is_empty() {
+            return Ok(Response::Close);
+        }
+
         // Wait for 1 second before sending the next chunk.
         tokio::time::sleep(Duration::from_millis(1000)).await;
     }
 }
+
+/// The log file of a task that's being followed.
+struct FollowedLog {
+    /// The handle is only opened once the task has been started.
+    handle: Option<File>,
+    /// We need to check continuously, whether the file still exists.
+    path: PathBuf,
+    /// The task finished or went away. Its output has been read for the last time.
+    finished: bool,
+    /// The task is currently paused.
+    paused: bool,
+    /// The task has just been paused, which the client is told about.
+    pause_notice: bool,
+}
+
+impl FollowedLog {
+    fn new(task_id: usize, pueue_directory: &Path) -> Self {
+        Self {
+            handle: None,
+            path: get_log_path(task_id, pueue_directory),
+            finished: false,
+            paused: false,
+            pause_notice: false,
+        }
+    }
+}
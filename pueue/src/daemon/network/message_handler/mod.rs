@@ -44,6 +44,10 @@ pub async fn handle_request(
             let pueue_directory = settings.shared.pueue_directory();
             follow_log(&pueue_directory, stream, state, payload).await?
         }
+        // The client requested logs in chunks, which are sent as a series of responses.
+        Request::Log(message) if message.send_logs && message.chunked => {
+            log::send_log_chunks(settings, stream, state, message).await?
+        }
         // To initiated a shutdown, a flag in Pueue's state is set that informs the TaskHandler
         // to perform a graceful shutdown.
         //
@@ -1,5 +1,8 @@
 use chrono::Local;
-use pueue_lib::{GroupStatus, Settings, TaskResult, TaskStatus, log::clean_log_handles};
+use pueue_lib::{
+    GroupStatus, Settings, TaskResult, TaskStatus,
+    log::{clean_log_handles, compress_task_log, get_log_stream_index_path},
+};
 
 use crate::{
     daemon::{callbacks::spawn_callback, internal_state::state::LockedState},
@@ -58,6 +61,7 @@ pub fn handle_finished_tasks(settings: &Settings, state: &mut LockedState) {
                 task.clone()
             };
 
+            compress_finished_log(settings, *task_id);
             spawn_callback(settings, state, &task);
             error!("Child {} failed with io::Error: {:?}", task_id, error);
 
@@ -112,6 +116,7 @@ pub fn handle_finished_tasks(settings: &Settings, state: &mut LockedState) {
             task.clone()
         };
         info!("WTF");
+        compress_finished_log(settings, *task_id);
         spawn_callback(settings, state, &task);
 
         if let TaskResult::Failed(_) = result {
@@ -132,6 +137,24 @@ pub fn handle_finished_tasks(settings: &Settings, state: &mut LockedState) {
     ok_or_shutdown!(settings, state, state.save(settings));
 }
 
+/// Compress the log file of a finished task, if `daemon.compress_task_logs` is enabled.
+///
+/// This is done before the callback is spawned, so the callback gets the path to the
+/// compressed log file. Tasks whose streams are kept in separate files are skipped, as their
+/// output might still be copied into the log file.
+fn compress_finished_log(settings: &Settings, task_id: usize) {
+    let pueue_directory = settings.shared.pueue_directory();
+    if !settings.daemon.compress_task_logs
+        || get_log_stream_index_path(task_id, &pueue_directory).exists()
+    {
+        return;
+    }
+
+    if let Err(err) = compress_task_log(task_id, &pueue_directory) {
+        error!("Failed to compress log file of task {task_id}: {err}");
+    }
+}
+
 /// Gather all finished tasks and sort them by finished and errored.
 /// Returns a list of finished task ids and whether they errored or not.
 fn get_finished(state: &mut LockedState) -> Vec<((usize, String, usize), Option<std::io::Error>)> {
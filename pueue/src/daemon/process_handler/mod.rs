@@ -11,6 +11,7 @@ use crate::{
 
 pub mod finish;
 pub mod kill;
+pub mod output;
 pub mod pause;
 pub mod spawn;
 pub mod start;
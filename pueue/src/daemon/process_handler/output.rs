@@ -0,0 +1,106 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use pueue_lib::log::{OutputStream, append_log_stream};
+
+use crate::internal_prelude::*;
+
+/// The log files of a task, whose output streams are kept in separate files.
+pub struct StreamLogFiles {
+    /// The log file that contains the output of both streams.
+    pub log: File,
+    /// The index that records which parts of `log` belong to which stream.
+    pub index: File,
+    pub stdout: File,
+    pub stderr: File,
+}
+
+/// The log file with the output of both streams, which is shared by both copy threads.
+struct CombinedLog {
+    log: File,
+    index: File,
+    /// The current size of `log`.
+    offset: u64,
+    /// The stream that wrote the last output.
+    last_stream: Option<OutputStream>,
+}
+
+/// Copy the output of a task's stdout and stderr into its log files.
+///
+/// Each stream is written into its own log file, as well as into the log file with the output
+/// of both streams. The copying is done in separate threads, which stop once the respective
+/// stream has been closed.
+pub fn copy_output_streams(
+    task_id: usize,
+    files: StreamLogFiles,
+    stdout: impl Read + Send + 'static,
+    stderr: impl Read + Send + 'static,
+) {
+    let combined = Arc::new(Mutex::new(CombinedLog {
+        log: files.log,
+        index: files.index,
+        offset: 0,
+        last_stream: None,
+    }));
+
+    spawn_copy_thread(
+        task_id,
+        OutputStream::Stdout,
+        stdout,
+        files.stdout,
+        combined.clone(),
+    );
+    spawn_copy_thread(
+        task_id,
+        OutputStream::Stderr,
+        stderr,
+        files.stderr,
+        combined,
+    );
+}
+
+fn spawn_copy_thread(
+    task_id: usize,
+    stream: OutputStream,
+    mut reader: impl Read + Send + 'static,
+    mut stream_log: File,
+    combined: Arc<Mutex<CombinedLog>>,
+) {
+    thread::spawn(move || {
+        let mut buffer = [0; 8192];
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    error!("Failed to read {stream} of task {task_id}: {err}");
+                    break;
+                }
+            };
+            let output = &buffer[..read];
+
+            if let Err(err) = stream_log.write_all(output) {
+                error!("Failed to write {stream} log of task {task_id}: {err}");
+            }
+
+            let mut combined = combined.lock().unwrap();
+            // The index entry is written first, so readers always know the stream of any output.
+            if combined.last_stream != Some(stream) {
+                let offset = combined.offset;
+                if let Err(err) = append_log_stream(&mut combined.index, offset, stream) {
+                    error!("Failed to write stream index of task {task_id}: {err}");
+                }
+                combined.last_stream = Some(stream);
+            }
+            if let Err(err) = combined.log.write_all(output) {
+                error!("Failed to write log of task {task_id}: {err}");
+            }
+            combined.offset += read as u64;
+        }
+    });
+}
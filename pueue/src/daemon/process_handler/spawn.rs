@@ -4,9 +4,10 @@ use chrono::Local;
 use command_group::CommandGroup;
 use pueue_lib::{
     GroupStatus, Settings, Task, TaskResult, TaskStatus,
-    log::{create_log_file_handles, get_writable_log_file_handle},
+    log::{create_log_file_handles, create_stream_log_file_handles, get_writable_log_file_handle},
 };
 
+use super::output::{StreamLogFiles, copy_output_streams};
 use crate::{
     daemon::{callbacks::spawn_callback, internal_state::state::LockedState},
     internal_prelude::*,
@@ -133,10 +134,30 @@ pub fn spawn_process(settings: &Settings, state: &mut LockedState, task_id: usiz
 
     // Try to get the log file to which the output of the process will be written to.
     // Panic if this doesn't work! This is unrecoverable.
-    let (stdout_log, stderr_log) = match create_log_file_handles(task_id, &pueue_directory) {
-        Ok((out, err)) => (out, err),
-        Err(err) => {
-            panic!("Failed to create child log files: {err:?}");
+    //
+    // If the output streams are kept separately, the output is piped through the daemon,
+    // which writes it into the respective files.
+    let (stdout, stderr, stream_log_files) = if settings.daemon.separate_log_streams {
+        match create_stream_log_file_handles(task_id, &pueue_directory) {
+            Ok((log, index, stdout, stderr)) => {
+                let files = StreamLogFiles {
+                    log,
+                    index,
+                    stdout,
+                    stderr,
+                };
+                (Stdio::piped(), Stdio::piped(), Some(files))
+            }
+            Err(err) => {
+                panic!("Failed to create child log files: {err:?}");
+            }
+        }
+    } else {
+        match create_log_file_handles(task_id, &pueue_directory) {
+            Ok((out, err)) => (Stdio::from(out), Stdio::from(err), None),
+            Err(err) => {
+                panic!("Failed to create child log files: {err:?}");
+            }
         }
     };
 
@@ -176,12 +197,12 @@ pub fn spawn_process(settings: &Settings, state: &mut LockedState, task_id: usiz
         .stdin(Stdio::piped())
         .env_clear()
         .envs(envs.clone())
-        .stdout(Stdio::from(stdout_log))
-        .stderr(Stdio::from(stderr_log))
+        .stdout(stdout)
+        .stderr(stderr)
         .group_spawn();
 
     // Check if the task managed to spawn
-    let child = match spawned_command {
+    let mut child = match spawned_command {
         Ok(child) => child,
         Err(err) => {
             let error_msg = format!("Failed to spawn child {task_id} with err: {:?}", err);
@@ -208,7 +229,8 @@ pub fn spawn_process(settings: &Settings, state: &mut LockedState, task_id: usiz
                     enqueued_at,
                     start: Local::now(),
                     end: Local::now(),
-                    result: TaskResult::FailedToSpawn(error_msg),
+                    // Only the error itself is kept, as it's shown to the user by the client.
+                    result: TaskResult::FailedToSpawn(err.to_string()),
                 };
                 task.clone()
             };
@@ -222,6 +244,14 @@ pub fn spawn_process(settings: &Settings, state: &mut LockedState, task_id: usiz
         }
     };
 
+    if let Some(files) = stream_log_files {
+        let stdout = child.inner().stdout.take();
+        let stderr = child.inner().stderr.take();
+        if let (Some(stdout), Some(stderr)) = (stdout, stderr) {
+            copy_output_streams(task_id, files, stdout, stderr);
+        }
+    }
+
     // Save the process handle in our self.children datastructure.
     state.children.add_child(&group, worker_id, task_id, child);
 
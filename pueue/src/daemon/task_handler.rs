@@ -1,7 +1,14 @@
-use std::{collections::BTreeMap, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::Duration,
+};
 
 use chrono::prelude::*;
-use pueue_lib::{Group, GroupStatus, Settings, TaskResult, TaskStatus, message::*};
+use pueue_lib::{
+    Group, GroupStatus, Settings, TaskResult, TaskStatus,
+    log::{append_log_timestamp, get_log_path},
+    message::*,
+};
 
 use crate::{
     daemon::{
@@ -25,6 +32,7 @@ use crate::{
 /// - Handle shutdown logic (graceful & not graceful).
 /// - If the client requested a reset: reset the state if all children have been killed and handled.
 /// - Check whether we can spawn new tasks.
+/// - Record the growth of task log files, if enabled.
 ///
 /// We also wait for 300ms to prevent this loop from running hot.
 pub async fn run(state: SharedState, settings: Settings) -> Result<()> {
@@ -38,12 +46,20 @@ pub async fn run(state: SharedState, settings: Settings) -> Result<()> {
         state.children = Children(pools);
     }
 
+    // The last known size of each active task's log file.
+    let mut log_offsets = BTreeMap::new();
+
     loop {
+        // The ids of running tasks, whose log files are checked once the state is unlocked.
+        let mut running_tasks = BTreeSet::new();
         'mutex_block: {
             let mut state = state.lock().unwrap();
 
             check_callbacks(&mut state);
             handle_finished_tasks(&settings, &mut state);
+            if settings.daemon.record_log_timestamps {
+                running_tasks = running_task_ids(&state);
+            }
 
             // Check if we're in shutdown.
             // If all tasks are killed, we do some cleanup and exit.
@@ -58,11 +74,68 @@ pub async fn run(state: SharedState, settings: Settings) -> Result<()> {
             check_failed_dependencies(&settings, &mut state);
             spawn_new(&settings, &mut state);
         }
+        record_log_timestamps(&settings, &running_tasks, &mut log_offsets);
 
         tokio::time::sleep(Duration::from_millis(300)).await;
     }
 }
 
+/// Get the ids of all running tasks, whose log files are watched for [record_log_timestamps].
+fn running_task_ids(state: &LockedState) -> BTreeSet<usize> {
+    state
+        .tasks()
+        .iter()
+        .filter(|(_, task)| task.is_running())
+        .map(|(task_id, _)| *task_id)
+        .collect()
+}
+
+/// Record how far the log files of active tasks have grown since the last check.
+///
+/// The resulting timestamp index is used by the client to show when a line of output has
+/// actually been written, instead of when it's being read.
+/// This touches the filesystem, which is why it's called after the state has been unlocked.
+fn record_log_timestamps(
+    settings: &Settings,
+    running_tasks: &BTreeSet<usize>,
+    log_offsets: &mut BTreeMap<usize, u64>,
+) {
+    if !settings.daemon.record_log_timestamps {
+        return;
+    }
+    let pueue_directory = settings.shared.pueue_directory();
+
+    // Start tracking all active tasks.
+    // Tasks that just finished are still in the map, so their last output is recorded as well.
+    for task_id in running_tasks {
+        log_offsets.entry(*task_id).or_insert(0);
+    }
+
+    let now = Local::now();
+    log_offsets.retain(|task_id, offset| {
+        let path = get_log_path(*task_id, &pueue_directory);
+        let Ok(metadata) = std::fs::metadata(path) else {
+            return false;
+        };
+
+        // The log file has been recreated, e.g. due to a restart.
+        if metadata.len() < *offset {
+            *offset = 0;
+        }
+
+        if metadata.len() > *offset {
+            if let Err(err) = append_log_timestamp(*task_id, &pueue_directory, metadata.len(), now)
+            {
+                warn!("Failed to record log timestamp for task {task_id}: {err}");
+            }
+            *offset = metadata.len();
+        }
+
+        // Stop tracking tasks that're no longer active.
+        running_tasks.contains(task_id)
+    });
+}
+
 /// Check if all tasks are killed.state::InnerState
 /// If they aren't, we'll wait a little longer.
 /// Once they're, we do some cleanup and exit.
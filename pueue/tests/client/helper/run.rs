@@ -1,6 +1,6 @@
 use std::{
     collections::HashMap,
-    process::{Command, Output, Stdio},
+    process::{Child, Command, Output, Stdio},
 };
 
 use assert_cmd::prelude::*;
@@ -18,6 +18,21 @@ pub fn run_client_command(shared: &Shared, args: &[&str]) -> Result<Output> {
     run_client_command_with_env(shared, args, envs)
 }
 
+/// Spawn a client command in the background, whose output can be read while it's running.
+pub fn spawn_client_command(shared: &Shared, args: &[&str]) -> Result<Child> {
+    let child = Command::cargo_bin("pueue")?
+        .arg("--config")
+        .arg(shared.pueue_directory().join("pueue.yml").to_str().unwrap())
+        .args(args)
+        .current_dir(shared.pueue_directory())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context(format!("Failed to spawn pueue with {args:?}"))?;
+
+    Ok(child)
+}
+
 /// Run the status command without the path being included in the output.
 pub async fn run_status_without_path(shared: &Shared, args: &[&str]) -> Result<Output> {
     // Inject an environment variable into the pueue command.
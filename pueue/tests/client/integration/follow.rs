@@ -1,5 +1,11 @@
-use pueue_lib::task::Task;
+use std::{collections::BTreeMap, fs::File, io::Read, sync::mpsc, thread, time::Duration};
+
+use chrono::{DateTime, Local};
+
+use pueue_lib::{log::get_log_path, message::TaskSelection, task::Task};
 use rstest::rstest;
+use serde::Deserialize;
+use tokio::time::sleep;
 
 use crate::{client::helper::*, internal_prelude::*};
 
@@ -58,6 +64,102 @@ async fn follow_on_immediate_add(#[case] read_local_logs: bool) -> Result<()> {
     Ok(())
 }
 
+/// `follow --max-bytes` only shows the complete lines within the last bytes of recent output.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn max_bytes(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    // Add a task which echos 8 lines of output
+    assert_success(add_task(shared, "echo \"1\n2\n3\n4\n5\n6\n7\n8\" && sleep 1").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    // The last 7 bytes contain the end of line 5, which is omitted.
+    let output = run_client_command(shared, &["follow", "--max-bytes=7"])?;
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "6\n7\n8\n");
+
+    Ok(())
+}
+
+/// `follow --tail-bytes` seeks by bytes, which is also done by the daemon for remote logs.
+/// A limit that's larger than the log shows the whole output.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tail_bytes(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo \"1\n2\n3\n4\" && sleep 1").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+    let output = run_client_command(shared, &["follow", "--tail-bytes=4", "0"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3\n4\n");
+
+    assert_success(add_task(shared, "echo \"1\n2\n3\n4\" && sleep 1").await?);
+    wait_for_task_condition(shared, 1, Task::is_running).await?;
+    let output = run_client_command(shared, &["follow", "--tail-bytes=1K", "1"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n2\n3\n4\n");
+
+    Ok(())
+}
+
+/// `follow` prints the task information on stderr before the output, unless `--no-header` is set.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn header(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "sleep 1 && echo test").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+    let output = run_client_command(shared, &["follow"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("running"), "Missing status:\n{stderr}");
+    assert!(
+        stderr.contains("Command: sleep 1 && echo test"),
+        "Missing header:\n{stderr}"
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "test\n");
+
+    assert_success(add_task(shared, "sleep 1 && echo test").await?);
+    wait_for_task_condition(shared, 1, Task::is_running).await?;
+    let output = run_client_command(shared, &["follow", "--no-header", "1"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("Command:"), "Unexpected header:\n{stderr}");
+
+    Ok(())
+}
+
+/// `follow --retry` still stops once the followed task finished.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn retry(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "sleep 1 && echo test").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["follow", "--retry"])?;
+
+    assert_snapshot_matches_output("follow__default", output.stdout)?;
+
+    Ok(())
+}
+
 /// Test that the remote `follow` command works, if one specifies to only show the last few lines
 /// of recent output.
 #[rstest]
@@ -81,6 +183,27 @@ async fn last_lines(#[case] read_local_logs: bool) -> Result<()> {
     Ok(())
 }
 
+/// `follow --lines=0` doesn't show any previous output, only output that's written from now on.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn only_new_output(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    // The last line of the previous output is incomplete, it's skipped as well.
+    assert_success(add_task(shared, "echo old && printf half && sleep 1 && echo new").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["follow", "--lines=0"])?;
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "new\n");
+
+    Ok(())
+}
+
 /// If a task exists but hasn't started yet, wait for it to start.
 #[rstest]
 #[case(true)]
@@ -102,6 +225,581 @@ async fn wait_for_task(#[case] read_local_logs: bool) -> Result<()> {
     Ok(())
 }
 
+/// Follow multiple tasks at once, which finish at different times.
+/// Each line is prefixed with the id of the task it belongs to.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn multiple_tasks(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first && sleep 1 && echo second").await?);
+    assert_success(add_task(shared, "sleep 2 && echo third").await?);
+    wait_for_task_condition(shared, 1, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["follow", "0", "1"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        vec!["[task 0] first", "[task 0] second", "[task 1] third"],
+        "Unexpected output:\n{stdout}"
+    );
+
+    Ok(())
+}
+
+/// The task id prefix can be disabled when following multiple tasks.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn multiple_tasks_no_prefix(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "sleep 1 && echo first").await?);
+    assert_success(add_task(shared, "sleep 2 && echo second").await?);
+    wait_for_task_condition(shared, 1, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["follow", "--no-prefix", "0", "1"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout, "first\nsecond\n");
+
+    Ok(())
+}
+
+/// Follow all running tasks of a group, including tasks that only start while following.
+/// Tasks of other groups are ignored.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn group(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task_to_group(shared, "sleep 3 && echo first", "test_2").await?);
+    assert_success(add_task(shared, "sleep 2 && echo other").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+    run_client_command(
+        shared,
+        &[
+            "add",
+            "--group",
+            "test_2",
+            "--delay",
+            "1 second",
+            "echo second",
+        ],
+    )?;
+
+    let output = run_client_command(shared, &["follow", "--group", "test_2"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout, "[task 2] second\n[task 0] first\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("There are no running tasks left in group 'test_2'"),
+        "Unexpected stderr: {stderr}"
+    );
+
+    Ok(())
+}
+
+/// `--filter` is applied to complete lines while following.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    // The matching line is written in two parts.
+    assert_success(
+        add_task(
+            shared,
+            "echo first && printf 'sec' && sleep 1 && echo ond && echo third",
+        )
+        .await?,
+    );
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["follow", "--filter", "second"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "second\n");
+
+    Ok(())
+}
+
+/// The result and runtime of the followed task is printed once it finished,
+/// unless `--no-summary` is passed.
+#[rstest]
+#[case(true, false)]
+#[case(false, false)]
+#[case(true, true)]
+#[case(false, true)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn summary(#[case] read_local_logs: bool, #[case] no_summary: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "sleep 1 && echo test && exit 3").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let mut args = vec!["follow"];
+    if no_summary {
+        args.push("--no-summary");
+    }
+    let output = run_client_command(shared, &args)?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "test\n");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(
+        stderr.contains("Task 0: failed with exit code 3 (took "),
+        !no_summary,
+        "Unexpected stderr: {stderr}"
+    );
+
+    Ok(())
+}
+
+/// `--exit-code` exits with the exit code of the followed task.
+#[rstest]
+#[case("sleep 1 && exit 3", 3)]
+#[case("sleep 1", 0)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn exit_code(#[case] command: &str, #[case] expected: i32) -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, command).await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["follow", "--exit-code", "0"])?;
+    assert_eq!(output.status.code(), Some(expected));
+
+    // The task's result doesn't matter otherwise.
+    let output = run_client_command(shared, &["follow", "0"])?;
+    assert_eq!(output.status.code(), Some(0));
+
+    Ok(())
+}
+
+/// The output of a truncated log file is followed from its start again.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn log_rotated() -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, true)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first && sleep 2").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    // Truncate the log file while the client is following it.
+    let log_path = get_log_path(0, &shared.pueue_directory());
+    tokio::task::spawn(async move {
+        sleep_ms(1000).await;
+        File::create(log_path).expect("Failed to truncate log file");
+    });
+
+    let output = run_client_command(shared, &["follow"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "first\n--- log rotated ---\n"
+    );
+
+    Ok(())
+}
+
+/// `follow --interval` changes how often local log files are checked, which must be positive.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn interval() -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, true)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(
+        add_task(
+            shared,
+            "sleep 0.5 && echo first && sleep 0.5 && echo second",
+        )
+        .await?,
+    );
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["follow", "--interval", "20"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "first\nsecond\n");
+
+    let output = run_client_command(shared, &["follow", "--interval", "0"])?;
+    assert!(!output.status.success(), "An interval of 0 got accepted");
+
+    Ok(())
+}
+
+/// `follow --json` prints each chunk of output as a json record of its task.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn json(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first && sleep 1 && echo second").await?);
+    assert_success(add_task(shared, "sleep 2 && printf 'third\nunterminated'").await?);
+    wait_for_task_condition(shared, 1, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["follow", "0", "1", "--json"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    #[derive(Deserialize)]
+    struct Record {
+        task_id: usize,
+        content: String,
+        // The timestamp only needs to be valid.
+        #[allow(dead_code)]
+        timestamp: DateTime<Local>,
+    }
+
+    // The content of each task is combined, as it may be split into multiple records.
+    let mut contents: BTreeMap<usize, String> = BTreeMap::new();
+    for line in stdout.lines() {
+        let record: Record =
+            serde_json::from_str(line).with_context(|| format!("Invalid json record: {line}"))?;
+        contents
+            .entry(record.task_id)
+            .or_default()
+            .push_str(&record.content);
+    }
+
+    assert_eq!(
+        contents,
+        BTreeMap::from([
+            (0, "first\nsecond\n".to_string()),
+            (1, "third\nunterminated".to_string()),
+        ]),
+        "Unexpected output:\n{stdout}"
+    );
+
+    Ok(())
+}
+
+/// Paused tasks are followed until they've been resumed and finished.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn paused_task(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first && sleep 1 && echo second").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+    assert_success(pause_tasks(shared, TaskSelection::TaskIds(vec![0])).await?);
+    wait_for_task_condition(shared, 0, Task::is_paused).await?;
+
+    let shared_clone = shared.clone();
+    let follow = thread::spawn(move || run_client_command(&shared_clone, &["follow", "0"]));
+    // Give the client some time to pick up the paused task, before it's resumed.
+    sleep(Duration::from_millis(1500)).await;
+    assert_success(start_tasks(shared, TaskSelection::TaskIds(vec![0])).await?);
+
+    let output = follow.join().unwrap()?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "first\nsecond\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("(task 0 is paused)"),
+        "Missing pause notice:\n{stderr}"
+    );
+
+    Ok(())
+}
+
+/// Tasks whose log file doesn't exist locally are streamed by the daemon, which allows to follow
+/// local and remote tasks at the same time.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn local_and_remote_tasks() -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, true)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo remote").await?);
+    assert_success(add_task(shared, "echo daemon").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    // The client uses its own pueue directory, which only contains a log file for task 1.
+    let client_directory = daemon.tempdir.path().join("client");
+    std::fs::create_dir_all(client_directory.join("task_logs"))?;
+    std::fs::write(get_log_path(1, &client_directory), "local\n")?;
+    let mut settings = daemon.settings.clone();
+    settings.shared.pueue_directory = Some(client_directory.clone());
+    settings
+        .save(&Some(client_directory.join("pueue.yml")))
+        .context("Couldn't write pueue config to client directory")?;
+
+    // Both tasks are followed at the same time, so their output may arrive in any order.
+    for (args, expected) in [
+        (
+            vec!["follow", "0", "1"],
+            ["[task 0] remote", "[task 1] local"],
+        ),
+        (
+            vec!["follow", "--remote", "0", "1"],
+            ["[task 0] remote", "[task 1] daemon"],
+        ),
+    ] {
+        let output = run_client_command(&settings.shared, &args)?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines: Vec<&str> = stdout.lines().collect();
+        lines.sort_unstable();
+        assert_eq!(lines, expected, "Unexpected output for {args:?}");
+    }
+
+    Ok(())
+}
+
+/// `follow --timeout` stops following after the given time, while the task keeps running.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn timeout(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first && sleep 10").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["follow", "--timeout", "1s"])?;
+    assert_eq!(output.status.code(), Some(124), "Unexpected exit code");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "first\n");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("follow timed out after 1s"),
+        "Missing timeout message"
+    );
+    assert!(
+        get_task(shared, 0).await?.is_running(),
+        "Task isn't running"
+    );
+
+    let output = run_client_command(shared, &["follow", "--timeout", "0s"])?;
+    assert!(!output.status.success(), "A timeout of 0s got accepted");
+
+    Ok(())
+}
+
+/// `follow --stats` counts the output of the task, regardless of any added prefixes.
+#[rstest]
+#[case(true, false)]
+#[case(false, false)]
+#[case(true, true)]
+#[case(false, true)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stats(#[case] read_local_logs: bool, #[case] timestamps: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo a && sleep 1 && echo bb").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let mut args = vec!["follow", "--stats"];
+    if timestamps {
+        args.push("--timestamps");
+    }
+    let output = run_client_command(shared, &args)?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Pueue: Followed 2 lines (5 B) in "),
+        "Missing stats:\n{stderr}"
+    );
+
+    Ok(())
+}
+
+/// `follow --squash-repeats` collapses identical lines across reads and prints the marker of the
+/// last lines once the task finished.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn squash_repeats(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    let command = "echo same && echo same && sleep 1 && echo same && echo other && echo other";
+    assert_success(add_task(shared, command).await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["follow", "--squash-repeats"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "same\n(repeated 3 times)\nother\n(repeated 2 times)\n"
+    );
+
+    Ok(())
+}
+
+/// `follow --throttle` skips excess lines and points out how many lines have been skipped.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn throttle(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "sleep 1 && seq 1 1000").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["follow", "--throttle", "10"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let mut printed = 0;
+    let mut skipped = 0;
+    for line in stdout.lines() {
+        if let Some(notice) = line.strip_prefix("(throttled, skipped ") {
+            let amount = notice.split(' ').next().unwrap_or_default();
+            skipped += amount.parse::<usize>()?;
+        } else {
+            printed += 1;
+        }
+    }
+    assert!(printed < 1000, "Output hasn't been throttled:\n{stdout}");
+    assert_eq!(
+        printed + skipped,
+        1000,
+        "Skipped lines are missing:\n{stdout}"
+    );
+
+    Ok(())
+}
+
+/// Incomplete lines are shown right away when stdout is piped, instead of once the line is done.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn incomplete_line_piped(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "printf partial && sleep 3 && echo ' done'").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let mut child = spawn_client_command(shared, &["follow", "0"])?;
+    let mut stdout = child.stdout.take().ok_or_else(|| eyre!("Missing stdout"))?;
+
+    // Read the output in the background, until the incomplete line shows up.
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        let mut buffer = [0; 64];
+        while let Ok(read_bytes @ 1..) = stdout.read(&mut buffer) {
+            output.extend_from_slice(&buffer[..read_bytes]);
+            if output.starts_with(b"partial") {
+                let _ = sender.send(());
+                break;
+            }
+        }
+    });
+
+    let shown = receiver.recv_timeout(Duration::from_secs(2)).is_ok();
+    child.kill()?;
+    child.wait()?;
+    assert!(
+        shown,
+        "The incomplete line wasn't shown while the task was running"
+    );
+
+    Ok(())
+}
+
+/// Tasks that are restarted in place are still followed with `--follow-restarts`.
+/// The output of both runs is shown.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn follow_restarts(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo run").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let shared_clone = shared.clone();
+    let follow = thread::spawn(move || {
+        run_client_command(&shared_clone, &["follow", "--follow-restarts", "0"])
+    });
+    // Give the client some time to follow the finished task, before it's restarted.
+    sleep(Duration::from_millis(1000)).await;
+    let restart = run_client_command(shared, &["restart", "--in-place", "0"])?;
+    assert!(restart.status.success(), "Failed to restart the task");
+
+    let output = follow.join().unwrap()?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "run\nrun\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("(task 0 has been restarted)"),
+        "Missing restart notice:\n{stderr}"
+    );
+
+    Ok(())
+}
+
+/// `follow --on-finish wait` keeps following a finished task until the timeout is reached,
+/// including all of its restarts. `--on-finish exit` stops right away.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn on_finish(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo run").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["follow", "--on-finish", "exit", "0"])?;
+    assert!(output.status.success(), "Follow failed");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "run\n");
+
+    let shared_clone = shared.clone();
+    let follow = thread::spawn(move || {
+        run_client_command(
+            &shared_clone,
+            &["follow", "--on-finish", "wait", "--timeout", "4s", "0"],
+        )
+    });
+    // Give the client some time to follow the finished task, before it's restarted.
+    sleep(Duration::from_millis(1000)).await;
+    let restart = run_client_command(shared, &["restart", "--in-place", "0"])?;
+    assert!(restart.status.success(), "Failed to restart the task");
+
+    // The restarted task finished as well, but we keep waiting for another restart.
+    let output = follow.join().unwrap()?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "run\nrun\n");
+    assert_eq!(output.status.code(), Some(124), "Unexpected exit code");
+
+    Ok(())
+}
+
 /// Fail when following a non-existing task
 #[rstest]
 #[case(true)]
@@ -166,3 +864,137 @@ async fn fail_on_non_existing(#[case] read_local_logs: bool) -> Result<()> {
 //
 //     Ok(())
 // }
+
+/// Tasks can be followed by their label, even before they have been added.
+/// Labels that match multiple tasks are rejected.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn follow_label() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let child = spawn_client_command(shared, &["follow", "--label", "build-*"])?;
+    // Give the client some time to start waiting for the label.
+    sleep(Duration::from_millis(500)).await;
+    let output = run_client_command(shared, &["add", "--label", "build-1", "echo built"])?;
+    assert!(output.status.success(), "Failed to add labeled task");
+
+    let output = child.wait_with_output()?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "built\n");
+
+    let output = run_client_command(shared, &["add", "--label", "build-2", "echo built"])?;
+    assert!(output.status.success(), "Failed to add labeled task");
+    let output = run_client_command(shared, &["follow", "--label", "build"])?;
+    assert!(!output.status.success(), "Ambiguous label got accepted");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("please select one of: 0, 1"),
+        "Unexpected error: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    Ok(())
+}
+
+/// `--next` ignores tasks that are already running and follows the next task of the group.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn follow_next() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "sleep 1 && echo current").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let child = spawn_client_command(shared, &["follow", "--group", "default", "--next"])?;
+    // Give the client some time to start waiting for the next task.
+    sleep(Duration::from_millis(500)).await;
+    assert_success(add_task(shared, "echo next").await?);
+
+    let output = child.wait_with_output()?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "next\n");
+
+    Ok(())
+}
+
+/// `--offline` follows a log file without a running daemon, until the file disappears.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn follow_offline() -> Result<()> {
+    let (settings, tempdir) = daemon_base_setup()?;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let shared = &settings.shared;
+
+    let log_path = get_log_path(0, &shared.pueue_directory());
+    std::fs::create_dir_all(log_path.parent().unwrap())?;
+    std::fs::write(&log_path, "before\n")?;
+
+    let child = spawn_client_command(shared, &["follow", "--offline", "0"])?;
+    sleep(Duration::from_millis(500)).await;
+    let mut file = std::fs::OpenOptions::new().append(true).open(&log_path)?;
+    std::io::Write::write_all(&mut file, b"after\n")?;
+    sleep(Duration::from_millis(500)).await;
+    std::fs::remove_file(&log_path)?;
+
+    let output = child.wait_with_output()?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "before\nafter\n");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("The log file of task 0 has gone away"),
+        "Unexpected stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    Ok(())
+}
+
+/// `--tee` appends exactly what's printed to a file, including the timestamps of the lines.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tee(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    let tee_path = daemon.tempdir.path().join("session.log");
+    std::fs::write(&tee_path, "previous session\n")?;
+
+    assert_success(add_task(shared, "echo first && sleep 1 && echo second").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let output = run_client_command(
+        shared,
+        &[
+            "follow",
+            "--timestamps",
+            "--tee",
+            tee_path.to_str().unwrap(),
+        ],
+    )?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("second"), "Unexpected output:\n{stdout}");
+
+    // The file is appended to, instead of being overwritten.
+    let tee = std::fs::read_to_string(&tee_path)?;
+    assert_eq!(tee, format!("previous session\n{stdout}"));
+
+    Ok(())
+}
+
+/// `--clear` doesn't clear anything if stdout isn't a terminal, the output stays the same.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn clear_without_terminal(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first && sleep 1 && echo second").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["follow", "--clear"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "first\nsecond\n");
+
+    Ok(())
+}
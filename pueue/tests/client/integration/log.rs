@@ -1,6 +1,24 @@
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{File, create_dir, read_to_string},
+    io::{Read, Write},
+};
 
-use pueue_lib::Task;
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use crossterm::style::Stylize;
+use flate2::read::GzDecoder;
+use pueue::{client::fetch_task_log, daemon::network::socket::get_listener};
+use pueue_lib::{
+    Client, PROTOCOL_VERSION, Task,
+    log::{LogCompression, get_compressed_log_path, get_log_path},
+    message::{Request, Response, TaskLogResponse},
+    network::{
+        protocol::{receive_bytes, receive_request, send_bytes, send_response},
+        socket::ConnectionSettings,
+    },
+    secret::{init_shared_secret, read_shared_secret},
+    task::{TaskResult, TaskStatus},
+};
 use rstest::rstest;
 use serde::Deserialize;
 
@@ -37,6 +55,286 @@ async fn read(#[case] read_local_logs: bool) -> Result<()> {
     Ok(())
 }
 
+/// Tasks without any output are marked as such, instead of showing an empty output.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn no_output(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "true").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log"])?;
+    let printed = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        printed.contains("\n(no output)\n"),
+        "Expected no output marker:\n{printed}"
+    );
+
+    Ok(())
+}
+
+/// `--count` only prints the size of a task's output, both as text and as JSON.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn count(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "printf 'a\\nbb\\nccc'").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--count", "0"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "lines=3 bytes=8\n");
+
+    let output = run_client_command(shared, &["log", "--count", "--json"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "{\"0\":{\"lines\":3,\"bytes\":8}}\n"
+    );
+
+    Ok(())
+}
+
+/// The log of a task can be fetched as bytes, without printing anything.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fetch_log(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    daemon.settings.client.read_local_logs = read_local_logs;
+    let settings = &daemon.settings;
+    let shared = &settings.shared;
+
+    assert_success(add_task(shared, "printf 'a\\nb\\nc\\n'").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let secret = read_shared_secret(&shared.shared_secret_path())?;
+    let mut client = Client::new(
+        ConnectionSettings::try_from(shared.clone())?,
+        &secret,
+        false,
+    )
+    .await?;
+
+    let log = fetch_task_log(&mut client, settings, 0, None).await?;
+    assert_eq!(log.task.id, 0);
+    assert_eq!(log.output, b"a\nb\nc\n");
+    assert!(log.output_complete, "Full output is incomplete");
+
+    let log = fetch_task_log(&mut client, settings, 0, Some(1)).await?;
+    assert_eq!(log.output, b"c\n");
+    assert!(!log.output_complete, "Last line is complete output");
+
+    let err = fetch_task_log(&mut client, settings, 5, None)
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "There's no task with id 5");
+
+    Ok(())
+}
+
+/// `--oneline` prints the status and last line of each task, both as text and as JSON.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn oneline(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "printf 'first\\nlast\\n'").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    assert_success(add_task(shared, "echo oops && exit 3").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--oneline"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "0 completed successfully last\n1 failed with exit code 3 oops\n"
+    );
+
+    let output = run_client_command(shared, &["log", "--oneline", "--json", "1"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "{\"1\":{\"status\":\"failed with exit code 3\",\"last_line\":\"oops\"}}\n"
+    );
+
+    Ok(())
+}
+
+/// `--raw` dumps the local log file as it is, or the compressed output sent by the daemon.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn raw(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "printf 'raw\\nbytes\\n'").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--raw", "0"])?;
+    assert!(output.status.success(), "--raw succeeds");
+    let log = if read_local_logs {
+        output.stdout
+    } else {
+        let mut log = Vec::new();
+        LogCompression::Snap
+            .decompressor(output.stdout.as_slice())?
+            .read_to_end(&mut log)?;
+        log
+    };
+    assert_eq!(String::from_utf8_lossy(&log), "raw\nbytes\n");
+
+    Ok(())
+}
+
+/// Percentages of `--lines` show that part of each task's output.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn lines_percentage(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "seq 1 10").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    assert_success(add_task(shared, "seq 1 3").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    // Partial lines are rounded up.
+    let output = run_client_command(shared, &["log", "--lines", "50%", "--no-task-info"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "6\n7\n8\n9\n10\n2\n3\n"
+    );
+
+    let output = run_client_command(shared, &["log", "--lines", "101%"])?;
+    assert!(
+        !output.status.success(),
+        "Percentages above 100% are rejected"
+    );
+
+    Ok(())
+}
+
+/// `--parse-json` merges the timestamps into JSON lines, while other lines are prefixed.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn parse_json(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, r#"echo '{"level":"info"}' && echo plain"#).await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    // A format without any placeholders makes the timestamps predictable.
+    let output = run_client_command(
+        shared,
+        &[
+            "log",
+            "--plain",
+            "--timestamps",
+            "--timestamp-format",
+            "now",
+            "--parse-json=ts",
+            "0",
+        ],
+    )?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "{\"ts\":\"now\",\"level\":\"info\"}\n[now] plain\n"
+    );
+
+    Ok(())
+}
+
+/// Output that's written into a closed pipe, e.g. via `pueue log | head`, is silently dropped.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn closed_pipe(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    // The output is larger than the buffer of the pipe, so writing it fails for sure.
+    assert_success(add_task(shared, "seq 1 200000").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let mut child = spawn_client_command(shared, &["log", "--full", "0"])?;
+    drop(child.stdout.take());
+    let output = child.wait_with_output()?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "Log failed:\n{stderr}");
+    for error in ["Error", "Failed", "panicked"] {
+        assert!(
+            !stderr.contains(error),
+            "Unexpected error output:\n{stderr}"
+        );
+    }
+
+    Ok(())
+}
+
 /// Test that the `log` command properly truncates content and hints this to the user for:
 /// - The log being streamed by the daemon.
 /// - The log being read from the local files.
@@ -68,83 +366,2485 @@ async fn read_truncated(#[case] read_local_logs: bool) -> Result<()> {
     Ok(())
 }
 
-/// If a task has a label, it is included in the log output
+/// `--head` shows the first lines of the output and, combined with `--lines`, the omitted lines
+/// between them and the last lines are marked for:
+/// - The log being streamed by the daemon.
+/// - The log being read from the local files.
+#[rstest]
+#[case(true)]
+#[case(false)]
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-async fn task_with_label() -> Result<()> {
-    let daemon = daemon().await?;
+async fn read_head_and_tail(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
     let shared = &daemon.settings.shared;
 
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
     // Add a task and wait until it finishes.
-    run_client_command(shared, &["add", "--label", "test_label", "echo test"])?;
+    assert_success(add_task(shared, "echo '1\n2\n3\n4\n5\n6\n7\n8\n9\n10'").await?);
     wait_for_task_condition(shared, 0, Task::is_done).await?;
 
-    let output = run_client_command(shared, &["log"])?;
+    let output = run_client_command(shared, &["log", "--head=2", "--tail=3"])?;
 
     let context = get_task_context(&daemon.settings).await?;
-    assert_template_matches("log__with_label", output, context)?;
+    assert_template_matches("log__head_and_tail", output, context)?;
+
+    // Only look at the lines of the task's output.
+    let output_lines = |args: &[&str]| -> Result<Vec<String>> {
+        let output = run_client_command(shared, args)?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| *line == "..." || line.parse::<usize>().is_ok())
+            .map(ToString::to_string)
+            .collect())
+    };
+
+    // Only the first lines are shown, if no amount of last lines is given.
+    assert_eq!(output_lines(&["log", "--head=2"])?, vec!["1", "2"]);
+
+    // Nothing is omitted, if the first and last lines overlap.
+    let all_lines: Vec<String> = (1..=10).map(|line| line.to_string()).collect();
+    assert_eq!(output_lines(&["log", "--head=6", "--lines=6"])?, all_lines);
 
     Ok(())
 }
 
-/// Calling `log` with the `--color=always` flag, colors the output as expected.
+/// The `client.default_log_lines` setting determines how many lines are shown by default.
+/// `0` only shows the task information, while `--lines` and `--full` still take precedence.
+#[rstest]
+#[case(true)]
+#[case(false)]
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-async fn colored() -> Result<()> {
-    let daemon = daemon().await?;
+async fn default_log_lines(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
     let shared = &daemon.settings.shared;
 
-    // Add a task and wait until it finishes.
-    assert_success(add_task(shared, "echo test").await?);
-    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon.settings.client.default_log_lines = 3;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
 
-    let output = run_client_command(shared, &["--color", "always", "log"])?;
+    // Add tasks and wait until they finish.
+    assert_success(add_task(shared, "echo '1\n2\n3\n4\n5\n6\n7\n8\n9\n10'").await?);
+    assert_success(add_task(shared, "echo '11\n12'").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
 
-    let context = get_task_context(&daemon.settings).await?;
-    assert_template_matches("log__colored", output, context)?;
+    // Only look at the lines of the task's output.
+    let output_lines = |args: &[&str]| -> Result<Vec<String>> {
+        let output = run_client_command(shared, args)?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| line.parse::<usize>().is_ok())
+            .map(ToString::to_string)
+            .collect())
+    };
 
-    Ok(())
-}
+    assert_eq!(output_lines(&["log"])?, vec!["8", "9", "10", "11", "12"]);
+    assert_eq!(output_lines(&["log", "0", "--lines=1"])?, vec!["10"]);
+    assert_eq!(output_lines(&["log", "0", "--full"])?.len(), 10);
 
-/// This is the output struct used for task logs.
-/// Since the Pueue client isn't exposed as a library, we have to declare our own for testing
-/// purposes. The counter part can be found in `client/display/log/json.rs`.
-#[derive(Debug, Deserialize)]
-pub struct TaskLog {
-    pub task: Task,
-    pub output: String,
+    // Only the task information is shown, if no lines should be shown by default.
+    daemon.settings.client.default_log_lines = 0;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    let output = run_client_command(shared, &["log"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stdout.lines().all(|line| line.parse::<usize>().is_err()),
+        "Got unexpected output:\n{stdout}"
+    );
+    assert!(!stdout.contains("output:") && !stderr.contains("output:"));
+    assert!(
+        stderr.contains("Task 0") && stderr.contains("Task 1"),
+        "Expected the task information:\n{stderr}"
+    );
+    assert_eq!(output_lines(&["log", "0", "--lines=2"])?, vec!["9", "10"]);
+
+    Ok(())
 }
 
-/// Calling `pueue log --json` prints the expected json output to stdout.
+/// `--number` prefixes lines with their position in the full output, even if only some lines are
+/// shown, for:
+/// - The log being streamed by the daemon.
+/// - The log being read from the local files.
+#[rstest]
+#[case(true)]
+#[case(false)]
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-async fn json() -> Result<()> {
-    let daemon = daemon().await?;
+async fn line_numbers(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
     let shared = &daemon.settings.shared;
 
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
     // Add a task and wait until it finishes.
-    assert_success(add_task(shared, "echo test").await?);
+    assert_success(add_task(shared, "echo '1\n2\n3\n4\n5\n6\n7\n8\n9\n10'").await?);
     wait_for_task_condition(shared, 0, Task::is_done).await?;
 
-    let output = run_client_command(shared, &["log", "--json"])?;
-
-    // Deserialize the json back to the original task BTreeMap.
-    let json = String::from_utf8_lossy(&output.stdout);
-    let mut task_logs: BTreeMap<usize, TaskLog> = serde_json::from_str(&json)
-        .context(format!("Failed to deserialize json tasks: \n{json}"))?;
+    // Only look at the numbered lines of the task's output.
+    let output_lines = |args: &[&str]| -> Result<Vec<String>> {
+        let output = run_client_command(shared, args)?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| line.contains('\t') || *line == "...")
+            .map(ToString::to_string)
+            .collect())
+    };
 
-    // Get the actual BTreeMap from the daemon
-    let mut state = get_state(shared).await?;
+    assert_eq!(
+        output_lines(&["log", "--number", "--lines=2"])?,
+        vec!["     9\t9", "    10\t10"]
+    );
+    assert_eq!(
+        output_lines(&["log", "-n", "--head=1", "--tail=1"])?,
+        vec!["     1\t1", "...", "    10\t10"]
+    );
+    assert_eq!(
+        output_lines(&["log", "-n", "--max-bytes=3"])?,
+        vec!["    10\t10"]
+    );
+    // Filtered lines keep their numbers.
+    assert_eq!(
+        output_lines(&["log", "-n", "--full", "--filter=^[57]$"])?,
+        vec!["     5\t5", "     7\t7"]
+    );
+
+    // The line number is shown in front of the timestamp.
+    let lines = output_lines(&["log", "-n", "--lines=1", "--timestamps"])?;
+    assert_eq!(lines.len(), 1);
+    assert!(
+        lines[0].starts_with("    10\t[") && lines[0].ends_with("] 10"),
+        "Unexpected line: {}",
+        lines[0]
+    );
+
+    Ok(())
+}
+
+/// `--max-bytes` only shows the complete lines within the last bytes of the output.
+/// Lines that exceed the limit by themselves are partially shown.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn max_bytes(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    // Add tasks and wait until they finish.
+    assert_success(add_task(shared, "echo '1\n2\n3\n4\n5\n6\n7\n8\n9\n10'").await?);
+    assert_success(add_task(shared, "echo abcdefghijklmnopqrst").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    // Only look at the lines of the task's output.
+    let output_lines = |args: &[&str]| -> Result<Vec<String>> {
+        let output = run_client_command(shared, args)?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.is_empty() && line.chars().all(|char| char.is_alphanumeric()))
+            .map(ToString::to_string)
+            .collect())
+    };
+
+    // The last 8 bytes contain the end of line 7, which is omitted.
+    assert_eq!(
+        output_lines(&["log", "0", "--max-bytes=8"])?,
+        vec!["8", "9", "10"]
+    );
+    // The hint tells how many lines there are in total.
+    let output = run_client_command(shared, &["log", "0", "--max-bytes=8"])?;
+    let headers = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        headers.contains("(last 8 bytes of 10 lines)"),
+        "Expected the total amount of lines:\n{headers}"
+    );
+    // Human-readable sizes are accepted.
+    assert_eq!(output_lines(&["log", "0", "--max-bytes=1K"])?.len(), 10);
+    // A single line that's longer than the limit is cut.
+    assert_eq!(output_lines(&["log", "1", "--max-bytes=5"])?, vec!["qrst"]);
+
+    let output = run_client_command(shared, &["log", "--max-bytes=5X"])?;
+    assert!(!output.status.success(), "Invalid byte size got accepted");
+
+    Ok(())
+}
+
+/// Output that's too large for a single chunk is completely received from the daemon.
+/// The second task makes sure that chunks are correctly assigned to their respective tasks.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn read_remote_chunks() -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = false;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    // Both tasks write roughly 190KB of output.
+    assert_success(add_task(shared, "seq 1 30000").await?);
+    assert_success(add_task(shared, "seq 1 30000").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--full"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let expected = (1..=30000).fold(String::new(), |mut full, number| {
+        full.push_str(&format!("{number}\n"));
+        full
+    });
+    assert_eq!(
+        stdout.matches(&expected).count(),
+        2,
+        "Expected the full output of both tasks"
+    );
+
+    Ok(())
+}
+
+/// `--output` writes the log output into files instead of printing it.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn output_file(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    // Add two tasks and wait until they finish.
+    assert_success(add_task(shared, "echo first").await?);
+    assert_success(add_task(shared, "echo second").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    // A single task is written to the exact path without any headers.
+    let path = daemon.tempdir.path().join("task.log");
+    let path_arg = path.to_string_lossy().to_string();
+    let output = run_client_command(shared, &["log", "0", "--output", &path_arg])?;
+    assert!(output.stdout.is_empty(), "Expected no output on stdout");
+    assert_eq!(read_to_string(&path)?, "first\n");
+
+    // Headers are only written if explicitly requested.
+    run_client_command(
+        shared,
+        &["log", "0", "--output", &path_arg, "--with-header"],
+    )?;
+    let content = read_to_string(&path)?;
+    assert!(
+        content.contains("Task 0") && content.contains("output:") && content.ends_with("first\n"),
+        "Expected headers in output file:\n{content}"
+    );
+
+    // The task id is appended to the path, if multiple tasks are written.
+    run_client_command(shared, &["log", "--output", &path_arg])?;
+    assert_eq!(read_to_string(path.with_extension("log.0"))?, "first\n");
+    assert_eq!(read_to_string(path.with_extension("log.1"))?, "second\n");
+
+    // Each task gets its own file inside of directories.
+    let directory = daemon.tempdir.path().join("logs");
+    create_dir(&directory)?;
+    run_client_command(shared, &["log", "--output", &directory.to_string_lossy()])?;
+    assert_eq!(read_to_string(directory.join("0.log"))?, "first\n");
+    assert_eq!(read_to_string(directory.join("1.log"))?, "second\n");
+
+    Ok(())
+}
+
+/// `--strip-ansi` removes escape sequences, such as colors, from the log output.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn strip_ansi(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "printf '\\033[1;31mred\\033[0m plain\\n'").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let path = daemon.tempdir.path().join("task.log");
+    let path_arg = path.to_string_lossy().to_string();
+
+    // The output is written as it is by default.
+    run_client_command(shared, &["log", "0", "--output", &path_arg])?;
+    assert_eq!(read_to_string(&path)?, "\x1b[1;31mred\x1b[0m plain\n");
+
+    run_client_command(shared, &["log", "0", "--output", &path_arg, "--strip-ansi"])?;
+    assert_eq!(read_to_string(&path)?, "red plain\n");
+
+    Ok(())
+}
+
+/// `--header-to-stdout` prints the task information and output headers together with the output.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn header_to_stdout(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "echo test").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "0", "--header-to-stdout"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Task 0") && stdout.contains("output:") && stdout.ends_with("test\n"),
+        "Expected headers on stdout:\n{stdout}"
+    );
+    assert!(output.stderr.is_empty(), "Expected no output on stderr");
+
+    Ok(())
+}
+
+/// `--plain` only prints a single line of task information, followed by the raw output.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn plain(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    // Add two tasks and wait until they finish.
+    assert_success(add_task(shared, "echo hello").await?);
+    assert_success(add_task(shared, "echo world && exit 1").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--plain", "--header-to-stdout"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Task 0 (completed successfully):\nhello\n\nTask 1 (failed with exit code 1):\nworld\n"
+    );
+
+    Ok(())
+}
+
+/// The pager is only used if stdout is a terminal, otherwise the output is printed directly.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pager_without_terminal() -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Enable the pager via config file.
+    daemon.settings.client.pager = true;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "echo hello").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    for args in [vec!["log"], vec!["log", "--pager"]] {
+        let output = run_client_command(shared, &args)?;
+        assert!(output.status.success(), "{args:?} failed");
+        assert!(
+            String::from_utf8_lossy(&output.stdout).ends_with("hello\n"),
+            "Expected the output to be printed directly for {args:?}"
+        );
+    }
+
+    let output = run_client_command(shared, &["log", "--pager", "--no-pager"])?;
+    assert!(
+        !output.status.success(),
+        "Conflicting pager flags got accepted"
+    );
+
+    Ok(())
+}
+
+/// `--follow` prints the logs of finished tasks and then follows the running tasks.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn follow_running_tasks(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "echo finished").await?);
+    assert_success(add_task(shared, "echo first && sleep 1 && echo second").await?);
+    wait_for_task_condition(shared, 1, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["log", "--plain", "--follow"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "finished\nfirst\nsecond\n"
+    );
+    let task = get_task(shared, 1).await?;
+    assert!(
+        task.is_done(),
+        "The running task wasn't followed until it finished"
+    );
+
+    Ok(())
+}
+
+/// Lines are only wrapped if stdout is a terminal, as the width is unknown otherwise.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn wrap_without_terminal() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let line = "long ".repeat(100);
+    assert_success(add_task(shared, &format!("echo '{line}'")).await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--plain", "--wrap", "--timestamps"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().count(), 1, "Output got wrapped:\n{stdout}");
+    assert!(
+        stdout.ends_with(&format!("{line}\n")),
+        "Unexpected output:\n{stdout}"
+    );
+
+    let output = run_client_command(shared, &["log", "--wrap", "--no-wrap"])?;
+    assert!(
+        !output.status.success(),
+        "Conflicting wrap flags got accepted"
+    );
+
+    Ok(())
+}
+
+/// `--archive` bundles the logs of all finished tasks of a group into a tarball.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn archive(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    // Add two tasks to a group and one task that shouldn't be archived.
+    run_client_command(
+        shared,
+        &["add", "--group", "test_2", "--label", "a/b", "echo first"],
+    )?;
+    assert_success(add_task_to_group(shared, "echo second && exit 3", "test_2").await?);
+    assert_success(add_task(shared, "echo other").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+    wait_for_task_condition(shared, 2, Task::is_done).await?;
+
+    let path = daemon.tempdir.path().join("logs.tar.gz");
+    let path_arg = path.to_string_lossy().to_string();
+    let output = run_client_command(
+        shared,
+        &["log", "--group", "test_2", "--archive", &path_arg],
+    )?;
+    assert!(output.status.success(), "Failed to create archive");
+
+    // Read all entries of the archive.
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(&path)?));
+    let mut entries = BTreeMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().to_string();
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        entries.insert(name, content);
+    }
+
+    let names: Vec<&str> = entries.keys().map(String::as_str).collect();
+    assert_eq!(names, vec!["0-a_b.log", "1.log", "manifest.json"]);
+    assert_eq!(entries["0-a_b.log"], "first\n");
+    assert_eq!(entries["1.log"], "second\n");
+
+    let manifest: serde_json::Value = serde_json::from_str(&entries["manifest.json"])?;
+    assert_eq!(manifest[0]["command"], "echo first");
+    assert_eq!(manifest[0]["log"], "0-a_b.log");
+    assert_eq!(manifest[0]["exit_code"], 0);
+    assert_eq!(manifest[1]["exit_code"], 3);
+    assert!(
+        manifest[1]["end"].is_string(),
+        "Expected end time in manifest"
+    );
+
+    Ok(())
+}
+
+/// If a task has a label, it is included in the log output
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn task_with_label() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Add a task and wait until it finishes.
+    run_client_command(shared, &["add", "--label", "test_label", "echo test"])?;
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log"])?;
+
+    let context = get_task_context(&daemon.settings).await?;
+    assert_template_matches("log__with_label", output, context)?;
+
+    Ok(())
+}
+
+/// `--label` selects tasks by a glob or a part of their label.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn select_by_label() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    run_client_command(shared, &["add", "--label", "build-linux", "echo linux"])?;
+    run_client_command(shared, &["add", "--label", "test-linux", "echo test"])?;
+    run_client_command(shared, &["add", "--label", "build-macos", "echo macos"])?;
+    wait_for_task_condition(shared, 2, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--plain", "--label", "build-*"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "linux\n\nmacos\n");
+
+    // Patterns without wildcards match any part of the label.
+    let output = run_client_command(shared, &["log", "--plain", "--label", "linux"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "linux\n\ntest\n");
+
+    let output = run_client_command(shared, &["log", "--label", "deploy-*"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "There are no finished tasks matching label 'deploy-*'\n"
+    );
+
+    Ok(())
+}
+
+/// `--max-tasks` only shows the logs of the most recently started tasks and mentions the rest.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn max_tasks() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Wait for each task, so they're started one after another.
+    for (id, word) in ["first", "second", "third"].iter().enumerate() {
+        assert_success(add_task(shared, &format!("echo {word}")).await?);
+        wait_for_task_condition(shared, id, Task::is_done).await?;
+    }
+
+    let output = run_client_command(shared, &["log", "--plain", "--all", "--max-tasks", "2"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "second\n\nthird\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("… 1 more task omitted"),
+        "Missing omitted tasks:\n{stderr}"
+    );
+
+    // Nothing is mentioned, if all tasks are shown.
+    let output = run_client_command(shared, &["log", "--plain", "--max-tasks", "3"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "first\n\nsecond\n\nthird\n"
+    );
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("omitted"));
+
+    Ok(())
+}
+
+/// Calling `log` with the `--color=always` flag, colors the output as expected.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn colored() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Add a task and wait until it finishes.
+    assert_success(add_task(shared, "echo test").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["--color", "always", "log"])?;
+
+    let context = get_task_context(&daemon.settings).await?;
+    assert_template_matches("log__colored", output, context)?;
+
+    Ok(())
+}
+
+/// `NO_COLOR` disables all styling, even with `--color=always`, unless `CLICOLOR_FORCE` is set.
+#[rstest]
+#[case(&["--color", "always"], &[("NO_COLOR", "1")], false)]
+#[case(&["--color", "always"], &[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")], true)]
+#[case(&[], &[("CLICOLOR_FORCE", "1")], true)]
+#[case(&[], &[("CLICOLOR_FORCE", "0")], false)]
+#[case(&["--color", "never"], &[("CLICOLOR_FORCE", "1")], false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn color_env_variables(
+    #[case] color_args: &[&str],
+    #[case] envs: &[(&str, &str)],
+    #[case] styled: bool,
+) -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo test").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let mut args = color_args.to_vec();
+    args.extend(["log", "--filter", "test"]);
+    let output = run_client_command_with_env(shared, &args, envs.iter().copied().collect())?;
+
+    // Both the task information and the highlighted output are checked.
+    for output in [output.stdout, output.stderr] {
+        let output = String::from_utf8_lossy(&output);
+        assert_eq!(
+            output.contains('\x1b'),
+            styled,
+            "Unexpected styling for {args:?} with {envs:?}:\n{output}"
+        );
+    }
+
+    Ok(())
+}
+
+/// The task ids in the headers are colored by task, if `client.colorize_task_ids` is enabled.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn colorized_task_ids(#[case] colorize_task_ids: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.colorize_task_ids = colorize_task_ids;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "echo first").await?);
+    assert_success(add_task(shared, "echo second").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["--color", "always", "log"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    // Get the escape sequences right in front of the task ids.
+    let id_style = |task_id: usize| -> Result<String> {
+        let end = stderr
+            .find(&format!("Task {task_id}: "))
+            .ok_or_else(|| eyre!("Missing header of task {task_id}:\n{stderr}"))?;
+        let start = stderr[..end].rfind('│').unwrap_or_default();
+        Ok(stderr[start..end].to_string())
+    };
+    assert_eq!(
+        id_style(0)? != id_style(1)?,
+        colorize_task_ids,
+        "Unexpected task id styles:\n{stderr}"
+    );
+
+    Ok(())
+}
+
+/// `--separator` replaces the empty line between tasks and `--summary` prints the amount of
+/// shown tasks to stderr.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn separator_and_summary() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first").await?);
+    assert_success(add_task(shared, "echo second").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--separator", "==="])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "first\n===\nsecond\n"
+    );
+
+    let output = run_client_command(shared, &["log", "--summary"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with("Showing logs for 2 tasks (group 'default')\n"),
+        "Unexpected summary:\n{stderr}"
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "first\n\nsecond\n");
+
+    let output = run_client_command(shared, &["log", "0", "--summary"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with("Showing logs for 1 task\n"),
+        "Unexpected summary:\n{stderr}"
+    );
+
+    let output = run_client_command(shared, &["log", "--all", "--summary"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with("Showing logs for 2 tasks (all groups)\n"),
+        "Unexpected summary:\n{stderr}"
+    );
+
+    Ok(())
+}
+
+/// Only the output is printed with `--no-task-info`, which is optionally separated.
+#[rstest]
+#[case(&[], "first\nsecond\n")]
+#[case(&["--separator", "---"], "first\n---\nsecond\n")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn no_task_info(#[case] args: &[&str], #[case] expected: &str) -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first").await?);
+    assert_success(add_task(shared, "echo second").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    let mut command = vec!["log", "--no-task-info"];
+    command.extend(args);
+    let output = run_client_command(shared, &command)?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), expected);
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+
+    // Hints are still shown on stderr.
+    let output = run_client_command(shared, &["log", "--no-task-info", "--group", "test_2"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("There are no finished tasks"),
+        "Missing hint on stderr"
+    );
+
+    Ok(())
+}
+
+/// The error of tasks that failed to spawn is shown in the task information.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failed_to_spawn() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.shell_command =
+        Some(vec!["thisshellshouldreallynotexist.hopefully".to_string()]);
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo test").await?);
+    wait_for_task_condition(shared, 0, |task| task.failed()).await?;
+
+    let output = run_client_command(shared, &["log", "0"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("failed to spawn: No such file or directory"),
+        "Missing spawn error:\n{stderr}"
+    );
+
+    Ok(())
+}
+
+/// The group of tasks is shown in the task information.
+/// The `default` group is only shown, if `client.show_default_group` is enabled.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn show_group(#[case] show_default_group: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.show_default_group = show_default_group;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "echo default").await?);
+    assert_success(add_task_to_group(shared, "echo test", "test_2").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "0"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(
+        stderr.contains("Group: default"),
+        show_default_group,
+        "Unexpected default group info:\n{stderr}"
+    );
+
+    let output = run_client_command(shared, &["log", "1"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Group: test_2"),
+        "Missing group info:\n{stderr}"
+    );
+
+    Ok(())
+}
+
+/// Relative times and the duration of tasks are only shown with `client.show_relative_times`.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn relative_times(#[case] show_relative_times: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.show_relative_times = show_relative_times;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "echo done").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    assert_success(add_task(shared, "sleep 60").await?);
+    wait_for_task_condition(shared, 1, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["log", "0"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.contains("s ago)"), show_relative_times, "{stderr}");
+    assert_eq!(
+        stderr.contains("Duration: 0s"),
+        show_relative_times,
+        "{stderr}"
+    );
+
+    let output = run_client_command(shared, &["log", "1"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.contains("s so far"), show_relative_times, "{stderr}");
+
+    Ok(())
+}
+
+/// This is the output struct used for task logs.
+/// Since the Pueue client isn't exposed as a library, we have to declare our own for testing
+/// purposes. The counter part can be found in `client/display/log/json.rs`.
+#[derive(Debug, Deserialize)]
+pub struct TaskLog {
+    pub schema: u32,
+    pub task: Task,
+    pub output: String,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Calling `pueue log --json` prints the expected json output to stdout.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn json() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Add a task and wait until it finishes.
+    assert_success(add_task(shared, "echo test").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--json"])?;
+
+    // Deserialize the json back to the original task BTreeMap.
+    let json = String::from_utf8_lossy(&output.stdout);
+    let mut task_logs: BTreeMap<usize, TaskLog> = serde_json::from_str(&json)
+        .context(format!("Failed to deserialize json tasks: \n{json}"))?;
+
+    // Get the actual BTreeMap from the daemon
+    let mut state = get_state(shared).await?;
     let original_task = state.tasks.get_mut(&0).unwrap();
     // Clean the environment variables, as they aren't transmitted when calling `log`.
     original_task.envs = HashMap::new();
 
-    let task_log = task_logs.get_mut(&0).expect("Expected one task log");
+    let task_log = task_logs.get_mut(&0).expect("Expected one task log");
+    assert_eq!(task_log.schema, 1, "Unexpected json schema version");
+    assert_eq!(
+        original_task, &task_log.task,
+        "Deserialized task and original task aren't equal"
+    );
+
+    // Append a newline to the deserialized task's output, which is automatically done when working
+    // with the shell.
+    assert_eq!("test", task_log.output);
+
+    Ok(())
+}
+
+/// `--json-pretty` indents the json, which still contains the same content.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn json_pretty(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "printf 'first\\nsecond\\n'").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    assert_success(add_task(shared, "echo test").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--json"])?;
+    let compact: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+
+    let output = run_client_command(shared, &["log", "--json", "--json-pretty"])?;
+    let json = String::from_utf8_lossy(&output.stdout);
+    let pretty: serde_json::Value = serde_json::from_str(&json)
+        .context(format!("Failed to deserialize pretty json: \n{json}"))?;
+    assert_eq!(compact, pretty);
+    assert!(
+        json.starts_with("{\n  \"0\": {\n    \"schema\": 1,\n    \"task\": {\n      \"id\": 0,"),
+        "Unexpected indentation:\n{json}"
+    );
+    assert!(
+        json.contains("\n    \"output\": \"first\\nsecond")
+            && json.contains("\"\n  },\n  \"1\": {"),
+        "Unexpected indentation:\n{json}"
+    );
+
+    Ok(())
+}
+
+/// `--json-group-by` nests the tasks of `--json` by their group or label.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn json_group_by(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    add_group_with_slots(shared, "test", 1).await?;
+    run_client_command(shared, &["add", "--label", "build", "echo one"])?;
+    assert_success(add_task_to_group(shared, "echo two", "test").await?);
+    run_client_command(
+        shared,
+        &["add", "--group", "test", "--label", "build", "echo three"],
+    )?;
+    for task_id in 0..3 {
+        wait_for_task_condition(shared, task_id, Task::is_done).await?;
+    }
+
+    // Remote output keeps its trailing newline.
+    let output_of = |task: &serde_json::Value| {
+        task["output"]
+            .as_str()
+            .map(|output| output.trim_end().to_string())
+    };
+
+    let args = ["log", "--all", "--json", "--json-group-by", "group"];
+    let output = run_client_command(shared, &args)?;
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(output_of(&json["default"]["0"]), Some("one".into()));
+    assert_eq!(output_of(&json["test"]["1"]), Some("two".into()));
+    assert_eq!(output_of(&json["test"]["2"]), Some("three".into()));
+    assert_eq!(json.as_object().map(|groups| groups.len()), Some(2));
+
+    // The indented json has the same shape.
+    let output = run_client_command(shared, &[&args[..], &["--json-pretty"]].concat())?;
+    let pretty: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(json, pretty);
+
+    // Tasks without a label are nested under an empty label.
+    let args = ["log", "--all", "--json", "--json-group-by", "label"];
+    let output = run_client_command(shared, &args)?;
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(output_of(&json["build"]["0"]), Some("one".into()));
+    assert_eq!(output_of(&json["build"]["2"]), Some("three".into()));
+    assert_eq!(output_of(&json[""]["1"]), Some("two".into()));
+
+    Ok(())
+}
+
+/// `--annotate` prepends a line of task metadata to the output, which is also written into
+/// `--output` files.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn annotate(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    run_client_command(shared, &["add", "--label", "nightly build", "echo test"])?;
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--annotate", "--no-task-info"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with(
+            "#pueue task=0 group=default label=\"nightly build\" status=success exit=0 start="
+        ) && stdout.contains(" end=")
+            && stdout.ends_with("\ntest\n"),
+        "Unexpected annotation:\n{stdout}"
+    );
+
+    // The prefix is configurable and the annotation ends up in output files as well.
+    let path = daemon.tempdir.path().join("task.log");
+    let path_arg = path.to_string_lossy().to_string();
+    run_client_command(
+        shared,
+        &[
+            "log",
+            "0",
+            "--output",
+            &path_arg,
+            "--annotate",
+            "--annotate-prefix",
+            "## meta",
+        ],
+    )?;
+    let content = read_to_string(&path)?;
+    assert!(
+        content.starts_with("## meta task=0 ") && content.ends_with("\ntest\n"),
+        "Unexpected annotation in output file:\n{content}"
+    );
+
+    Ok(())
+}
+
+/// Binary output is dumped when piping, unless `--binary` hides it behind a notice.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn binary_output(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "printf 'binary\\0output\\n'").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--no-task-info"])?;
+    assert_eq!(output.stdout, b"binary\0output\n");
+
+    let output = run_client_command(shared, &["log", "--no-task-info", "--binary"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Binary output; use --raw or --text to display\n"
+    );
+
+    // Text output isn't affected.
+    assert_success(add_task(shared, "echo text").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+    let output = run_client_command(shared, &["log", "1", "--no-task-info", "--binary"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "text\n");
+
+    Ok(())
+}
+
+/// `--socket` connects to another daemon for a single invocation.
+/// Its logs are sent by the daemon, as they aren't in the configured pueue directory.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn socket_override() -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = daemon.settings.shared.clone();
+
+    assert_success(add_task(&shared, "echo test").await?);
+    wait_for_task_condition(&shared, 0, Task::is_done).await?;
+
+    // Point the configuration to a daemon that doesn't exist, with an empty pueue directory.
+    let other_directory = daemon.tempdir.path().join("other");
+    create_dir(&other_directory)?;
+    daemon.settings.client.read_local_logs = true;
+    daemon.settings.shared.unix_socket_path = Some(daemon.tempdir.path().join("missing.socket"));
+    daemon.settings.shared.pueue_directory = Some(other_directory);
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    let output = run_client_command(&shared, &["log", "0"])?;
+    assert!(!output.status.success(), "Connected to a missing daemon");
+
+    let socket = shared.unix_socket_path().to_string_lossy().to_string();
+    let output = run_client_command(
+        &shared,
+        &["log", "0", "--no-task-info", "--socket", &socket],
+    )?;
+    assert!(
+        output.status.success(),
+        "Failed to connect via --socket: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "test\n");
+
+    Ok(())
+}
+
+/// Selecting tasks that don't exist doesn't break the json output of the tasks that do.
+#[rstest]
+#[case("--json")]
+#[case("--json-lines")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn json_missing_task(#[case] format: &str) -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo test").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", format, "0", "5"])?;
+    assert!(output.status.success(), "The log command failed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let outputs: Vec<String> = if format == "--json" {
+        let task_logs: BTreeMap<usize, TaskLog> = serde_json::from_str(&stdout)
+            .context(format!("Failed to deserialize json tasks: \n{stdout}"))?;
+        assert_eq!(task_logs.keys().collect::<Vec<_>>(), vec![&0]);
+        task_logs.into_values().map(|log| log.output).collect()
+    } else {
+        stdout
+            .lines()
+            .map(serde_json::from_str::<TaskLogRecord>)
+            .map(|record| record.map(|record| record.output))
+            .collect::<std::result::Result<_, _>>()
+            .context(format!("Failed to deserialize json records: \n{stdout}"))?
+    };
+    assert_eq!(outputs.len(), 1, "Unexpected output:\n{stdout}");
+    assert_eq!(outputs[0].trim_end(), "test");
+
+    Ok(())
+}
+
+/// Failures to read the output are reported in a separate `error` field, instead of being
+/// mixed into the output.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn json_read_error() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first").await?);
+    assert_success(add_task(shared, "echo second").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+    std::fs::remove_file(get_log_path(0, &shared.pueue_directory()))?;
+
+    let output = run_client_command(shared, &["log", "--json"])?;
+    let json = String::from_utf8_lossy(&output.stdout);
+    let task_logs: BTreeMap<usize, TaskLog> = serde_json::from_str(&json)
+        .context(format!("Failed to deserialize json tasks: \n{json}"))?;
+    assert_eq!(task_logs[&0].output, "");
+    assert_eq!(
+        task_logs[&0].error.as_deref(),
+        Some("The log of task 0 doesn't exist.")
+    );
+    assert_eq!(task_logs[&1].output.trim_end(), "second");
+    assert_eq!(task_logs[&1].error, None);
+
+    let output = run_client_command(shared, &["log", "--json-lines"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let records = stdout
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<std::result::Result<Vec<TaskLogRecord>, _>>()
+        .context(format!("Failed to deserialize json records: \n{stdout}"))?;
+    assert_eq!(records[0].output, "");
+    assert!(records[0].error.is_some(), "Expected an error:\n{stdout}");
+    assert_eq!(records[1].error, None);
+
+    Ok(())
+}
+
+/// If the daemon can't read the output of a task, that task's error is reported, while the output
+/// of the other tasks is still received.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn remote_read_error() -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = false;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "echo first").await?);
+    assert_success(add_task(shared, "echo second").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+    std::fs::remove_file(get_log_path(0, &shared.pueue_directory()))?;
+
+    let output = run_client_command(shared, &["log"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "Log failed: {stderr}");
+    assert!(
+        stderr.contains("Task 0: Failed reading process output file"),
+        "Expected read error of task 0: {stderr}"
+    );
+    assert!(
+        stdout.contains("second"),
+        "Expected output of task 1: {stdout}"
+    );
+
+    let output = run_client_command(shared, &["log", "--json"])?;
+    let json = String::from_utf8_lossy(&output.stdout);
+    let task_logs: BTreeMap<usize, TaskLog> = serde_json::from_str(&json)
+        .context(format!("Failed to deserialize json tasks: \n{json}"))?;
+    assert_eq!(task_logs[&0].output, "");
+    assert!(task_logs[&0].error.is_some(), "Expected an error:\n{json}");
+    assert_eq!(task_logs[&1].output.trim_end(), "second");
+    assert_eq!(task_logs[&1].error, None);
+
+    Ok(())
+}
+
+/// The output of multiple tasks is properly escaped in the json output, including characters that
+/// need escaping and invalid UTF-8.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn json_escaped_output(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    // Add two tasks and wait until they finish.
+    assert_success(add_task(shared, r#"printf 'say "hi"\t\\\377\n'"#).await?);
+    assert_success(add_task(shared, "echo second").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--json"])?;
+    let json = String::from_utf8(output.stdout)?;
+    let task_logs: BTreeMap<usize, TaskLog> = serde_json::from_str(&json)
+        .context(format!("Failed to deserialize json tasks: \n{json}"))?;
+
+    assert_eq!(task_logs.len(), 2, "Expected two task logs:\n{json}");
+    assert_eq!(task_logs[&0].output.trim_end(), "say \"hi\"\t\\\u{FFFD}");
+    assert_eq!(task_logs[&1].output.trim_end(), "second");
+
+    Ok(())
+}
+
+/// `--with-env` includes the task's environment in the json output and redacts secrets,
+/// unless `--with-env-raw` is provided.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn json_with_env() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Add a task with some environment variables and wait until it finishes.
+    let envs = HashMap::from([("PUEUE_TEST_VALUE", "visible"), ("API_TOKEN", "hunter2")]);
+    run_client_command_with_env(shared, &["add", "echo test"], envs)?;
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    // Get the environment of the task from the json output.
+    let task_envs = |args: &[&str]| -> Result<HashMap<String, String>> {
+        let output = run_client_command(shared, args)?;
+        let json = String::from_utf8_lossy(&output.stdout);
+        let mut task_logs: BTreeMap<usize, TaskLog> = serde_json::from_str(&json)
+            .context(format!("Failed to deserialize json tasks: \n{json}"))?;
+        Ok(task_logs.remove(&0).unwrap().task.envs)
+    };
+
+    assert!(task_envs(&["log", "--json"])?.is_empty());
+
+    let envs = task_envs(&["log", "--json", "--with-env"])?;
+    assert_eq!(envs["PUEUE_TEST_VALUE"], "visible");
+    assert_eq!(envs["API_TOKEN"], "[REDACTED]");
+
+    let envs = task_envs(&["log", "--json", "--with-env", "--with-env-raw"])?;
+    assert_eq!(envs["API_TOKEN"], "hunter2");
+
+    // The environment is only part of the json output.
+    let output = run_client_command(shared, &["log", "--with-env"])?;
+    assert!(
+        !output.status.success(),
+        "--with-env got accepted without --json"
+    );
+
+    Ok(())
+}
+
+/// This is the output struct used for each line of `--json-lines`.
+/// The counter part can be found in `client/display/log/json.rs`.
+#[derive(Debug, Deserialize)]
+pub struct TaskLogRecord {
+    pub schema: u32,
+    pub id: usize,
+    pub task: Task,
+    pub output: String,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Calling `pueue log --json-lines` prints a separate json record for each task.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn json_lines(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    // Add two tasks and wait until they finish.
+    assert_success(add_task(shared, "echo first").await?);
+    assert_success(add_task(shared, "echo second").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--json-lines"])?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let records = stdout
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<std::result::Result<Vec<TaskLogRecord>, _>>()
+        .context(format!("Failed to deserialize json records: \n{stdout}"))?;
+
+    assert_eq!(records.len(), 2, "Expected one record per task");
+    for (record, expected) in records.iter().zip(["first", "second"]) {
+        assert_eq!(record.schema, 1, "Unexpected json schema version");
+        assert_eq!(record.id, record.task.id);
+        assert_eq!(record.output.trim_end(), expected);
+    }
+    assert_eq!(records[1].id, 1);
+
+    Ok(())
+}
+
+/// If the daemon records log timestamps, `--timestamps` shows the time at which each line has
+/// been written instead of the time it's being read.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn recorded_timestamps() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.record_log_timestamps = true;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    // Add a task that writes two lines a few seconds apart.
+    assert_success(add_task(shared, "echo first && sleep 2 && echo second").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--timestamps"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        !stderr.contains("No log timestamps"),
+        "Got unexpected warning: {stderr}"
+    );
+
+    // Extract the timestamps of both lines.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let times: Vec<NaiveDateTime> = stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix('[')?.split_once("] "))
+        .map(|(time, _)| NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S%.3f"))
+        .collect::<std::result::Result<_, _>>()?;
+    assert_eq!(times.len(), 2, "Expected two timestamped lines:\n{stdout}");
+
+    let difference = times[1] - times[0];
+    assert!(
+        difference.num_milliseconds() >= 1500,
+        "Expected lines to be written two seconds apart:\n{stdout}"
+    );
+
+    Ok(())
+}
+
+/// The timestamp index isn't sent by the daemon, which is pointed out instead of asking to record
+/// timestamps, which the daemon already does.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn remote_timestamps_warning() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.record_log_timestamps = true;
+    settings.client.read_local_logs = false;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--timestamps"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // The task information is printed to stderr as well.
+    let warnings: Vec<&str> = stderr
+        .lines()
+        .filter(|line| line.starts_with("Pueue:"))
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "Pueue: Log timestamps aren't sent by the daemon, showing the time of reading \
+            instead. Enable `client.read_local_logs` to show the recorded timestamps."
+        ],
+    );
+
+    Ok(())
+}
+
+/// If the daemon records log timestamps, `--since` and `--until` only show lines that have been
+/// written within the given time window.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn time_window_lines() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.record_log_timestamps = true;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    // Add a task that writes two lines a few seconds apart.
+    assert_success(add_task(shared, "echo first && sleep 4 && echo second").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    // Only look at the lines of the task's output.
+    let output_lines = |args: &[&str]| -> Result<Vec<String>> {
+        let output = run_client_command(shared, args)?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| ["first", "second"].contains(line))
+            .map(ToString::to_string)
+            .collect())
+    };
+
+    assert_eq!(output_lines(&["log", "--since", "2s"])?, vec!["second"]);
+
+    let until = (Local::now() - TimeDelta::seconds(2)).to_rfc3339();
+    assert_eq!(output_lines(&["log", "--until", &until])?, vec!["first"]);
+
+    Ok(())
+}
+
+/// Without a timestamp index, `--since` and `--until` only hide tasks that didn't run during
+/// the given time window.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn time_window_tasks(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    // Add a task and wait until it finishes.
+    assert_success(add_task(shared, "echo test").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--since", "1h"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.lines().any(|line| line == "test"),
+        "Expected output:\n{stdout}"
+    );
+
+    let output = run_client_command(shared, &["log", "--until", "1h"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.is_empty(), "Expected no output:\n{stdout}");
+
+    let output = run_client_command(shared, &["log", "--since", "yesterday"])?;
+    assert!(!output.status.success(), "Invalid time got accepted");
+
+    Ok(())
+}
+
+/// `--timestamp-format` is applied to the timestamps and invalid formats are rejected.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn timestamp_format() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Add a task and wait until it finishes.
+    assert_success(add_task(shared, "echo test").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(
+        shared,
+        &["log", "--timestamps", "--timestamp-format", "unix"],
+    )?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout
+        .lines()
+        .find(|line| line.ends_with("] test"))
+        .ok_or_else(|| eyre!("Couldn't find timestamped line:\n{stdout}"))?;
+    let timestamp = line.trim_start_matches('[').trim_end_matches("] test");
+    timestamp
+        .parse::<f64>()
+        .context(format!("Expected a unix timestamp, got: {timestamp}"))?;
+
+    let output = run_client_command(shared, &["log", "--timestamps", "--timestamp-format", "%Q"])?;
+    assert!(!output.status.success(), "Invalid format got accepted");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("invalid strftime format string"),
+        "Unexpected error: {stderr}"
+    );
+
+    Ok(())
+}
+
+/// `--timestamps-utc` shows timestamps in UTC, both in the normal and the json output.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn timestamps_utc() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Add a task and wait until it finishes.
+    assert_success(add_task(shared, "echo test").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(
+        shared,
+        &[
+            "log",
+            "--timestamps",
+            "--timestamps-utc",
+            "--timestamp-format",
+            "%z",
+        ],
+    )?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("[+0000] test"),
+        "Expected UTC offset:\n{stdout}"
+    );
+
+    let output = run_client_command(
+        shared,
+        &[
+            "log",
+            "--json",
+            "--timestamps",
+            "--timestamps-utc",
+            "--timestamp-format",
+            "%z",
+        ],
+    )?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("[+0000] test"),
+        "Expected UTC offset:\n{stdout}"
+    );
+
+    Ok(())
+}
+
+/// `--filter` only shows matching lines, which can be inverted and matched case-insensitively.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    // Add a task and wait until it finishes.
+    assert_success(add_task(shared, "echo apple && echo Banana && echo cherry").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    // Only look at the lines of the task's output.
+    let output_lines = |args: &[&str]| -> Result<Vec<String>> {
+        let output = run_client_command(shared, args)?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| ["apple", "Banana", "cherry"].contains(line))
+            .map(ToString::to_string)
+            .collect())
+    };
+
+    assert_eq!(output_lines(&["log", "--filter", "an"])?, vec!["Banana"]);
+    assert_eq!(
+        output_lines(&["log", "--filter", "^b", "--ignore-case"])?,
+        vec!["Banana"]
+    );
+    assert_eq!(
+        output_lines(&["log", "--filter", "an", "--invert-match"])?,
+        vec!["apple", "cherry"]
+    );
+
+    let output = run_client_command(shared, &["log", "--filter", "("])?;
+    assert!(!output.status.success(), "Invalid regex got accepted");
+
+    Ok(())
+}
+
+/// Matches of `--filter` are highlighted if styling is enabled.
+/// Adjacent matches are merged into a single highlighted span.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_highlight(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    // Add a task and wait until it finishes.
+    assert_success(add_task(shared, "echo Banana").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["--color", "always", "log", "--filter", "an"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = format!("B{}a\n", "anan".red().bold());
+    assert!(stdout.ends_with(&expected), "Unexpected output: {stdout:?}");
+
+    // No highlighting without styling.
+    let output = run_client_command(shared, &["--color", "never", "log", "--filter", "an"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.lines().last() == Some("Banana"),
+        "Unexpected output: {stdout:?}"
+    );
+
+    Ok(())
+}
+
+/// `--context` shows the lines around matches of `--filter` and `--search`, separating groups
+/// that aren't adjacent by `--`.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_context(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(
+        add_task(
+            shared,
+            "printf 'a\\nb\\nhit 1\\nc\\nd\\ne\\nf\\nhit 2\\ng\\n'",
+        )
+        .await?,
+    );
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let stdout = |args: &[&str]| -> Result<String> {
+        let mut command = vec!["log", "--no-task-info", "--full"];
+        command.extend(args);
+        let output = run_client_command(shared, &command)?;
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    assert_eq!(
+        stdout(&["--filter", "hit", "--context", "1"])?,
+        "b\nhit 1\nc\n--\nf\nhit 2\ng\n"
+    );
+    // The lines after matches can be set on their own.
+    assert_eq!(
+        stdout(&["--filter", "hit", "-C", "1", "-A", "0"])?,
+        "b\nhit 1\n--\nf\nhit 2\n"
+    );
+    // Overlapping groups are merged.
+    assert_eq!(
+        stdout(&["--filter", "hit", "-B", "2", "-A", "2"])?,
+        "a\nb\nhit 1\nc\nd\ne\nf\nhit 2\ng\n"
+    );
+
+    let output = run_client_command(shared, &["log", "--search", "hit 2", "-B", "1"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "f\nhit 2\n");
+
+    // Context requires a regular expression.
+    let output = run_client_command(shared, &["log", "--context", "1"])?;
+    assert!(
+        !output.status.success(),
+        "Context without filter got accepted"
+    );
+
+    Ok(())
+}
+
+/// If the daemon keeps separate log streams, `--stdout-only` and `--stderr-only` only show the
+/// output of the respective stream, both for local and remote logs.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn separate_streams(#[case] read_local_logs: bool) -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.separate_log_streams = true;
+    settings.client.read_local_logs = read_local_logs;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo hello && echo oops >&2").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--stdout-only"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("hello"),
+        "Expected stdout output:\n{stdout}"
+    );
+    assert!(
+        !stdout.contains("oops"),
+        "Got unexpected stderr output:\n{stdout}"
+    );
+
+    let output = run_client_command(shared, &["log", "--stderr-only"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("oops"), "Expected stderr output:\n{stdout}");
+    assert!(
+        !stdout.contains("hello"),
+        "Got unexpected stdout output:\n{stdout}"
+    );
+
+    Ok(())
+}
+
+/// The daemon compresses the logs of finished tasks, if `daemon.compress_task_logs` is enabled.
+/// They're transparently decompressed, both for local and remote logs.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn compressed_logs(#[case] read_local_logs: bool) -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.compress_task_logs = true;
+    settings.client.read_local_logs = read_local_logs;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo hello && echo world").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let pueue_directory = shared.pueue_directory();
+    assert!(get_compressed_log_path(0, &pueue_directory).exists());
+    assert!(!get_log_path(0, &pueue_directory).exists());
+
+    let output = run_client_command(shared, &["log", "--no-task-info", "0"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\nworld\n");
+
+    // The last lines can't be seeked in the compressed file and are taken from the full output.
+    let output = run_client_command(shared, &["log", "--no-task-info", "--lines", "1", "0"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "world\n");
+
+    let output = run_client_command(shared, &["follow", "0"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\nworld\n");
+
+    Ok(())
+}
+
+/// Remote logs can be sent with zstd compression, which is requested via `shared.log_compression`.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn zstd_compression() -> Result<()> {
+    let mut daemon = daemon().await?;
+    daemon.settings.client.read_local_logs = false;
+    daemon.settings.shared.log_compression = LogCompression::Zstd;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo hello && echo world").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--no-task-info", "0"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\nworld\n");
+
+    let output = run_client_command(shared, &["log", "--no-task-info", "--lines", "1", "0"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "world\n");
+
+    Ok(())
+}
+
+/// `--interleaved` prefixes each line of a local log with the stream it has been written to.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn interleaved_streams() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.separate_log_streams = true;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    // Wait a bit between both lines, so they can't end up in the log at the same time.
+    assert_success(add_task(shared, "echo hello && sleep 0.5 && echo oops >&2").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--interleaved"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
     assert_eq!(
-        original_task, &task_log.task,
-        "Deserialized task and original task aren't equal"
+        lines,
+        vec!["O: hello", "E: oops"],
+        "Unexpected output:\n{stdout}"
     );
 
-    // Append a newline to the deserialized task's output, which is automatically done when working
-    // with the shell.
-    assert_eq!("test", task_log.output);
+    Ok(())
+}
+
+/// Interleaved lines of stderr are highlighted, if colors are enabled.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn interleaved_stderr_color() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.separate_log_streams = true;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo hello && sleep 0.5 && echo oops >&2").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["--color", "always", "log", "--interleaved"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = format!("O: hello\n{}\n", "E: oops".red());
+    assert!(stdout.ends_with(&expected), "Unexpected output:\n{stdout}");
+
+    // Nothing is highlighted without colors.
+    let output = run_client_command(shared, &["--color", "never", "log", "--interleaved"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.ends_with("O: hello\nE: oops\n"),
+        "Unexpected output:\n{stdout}"
+    );
+
+    Ok(())
+}
+
+/// Lines are highlighted by their log level via `--color-levels`, if colors are enabled.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn color_levels(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(
+        add_task(
+            shared,
+            "printf 'ERROR: failed\\nno WARN here\\n2024-05-01 12:00:00 DEBUG value\\n'",
+        )
+        .await?,
+    );
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["--color", "always", "log", "--color-levels"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = format!(
+        "{}\nno WARN here\n{}\n",
+        "ERROR: failed".red(),
+        "2024-05-01 12:00:00 DEBUG value".dim()
+    );
+    assert!(stdout.ends_with(&expected), "Unexpected output:\n{stdout}");
+
+    // Nothing is highlighted without colors.
+    let output = run_client_command(shared, &["--color", "never", "log", "--color-levels"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.ends_with("ERROR: failed\nno WARN here\n2024-05-01 12:00:00 DEBUG value\n"),
+        "Unexpected output:\n{stdout}"
+    );
+
+    Ok(())
+}
+
+/// `--ansi-safe-truncate` resets the terminal styles where the output has been cut off.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn ansi_safe_truncate(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "printf '\\033[31mred\\nstill red\\nlast\\n'").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--lines", "2", "--ansi-safe-truncate"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.ends_with("\x1b[0mstill red\nlast\n"),
+        "Unexpected output:\n{stdout:?}"
+    );
+
+    // The styles are reset in front of the omission marker as well.
+    let output = run_client_command(
+        shared,
+        &["log", "--head", "1", "--lines", "1", "--ansi-safe-truncate"],
+    )?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.ends_with("\x1b[31mred\n\x1b[0m...\nlast\n"),
+        "Unexpected output:\n{stdout:?}"
+    );
+
+    // Nothing is reset, if the full output is shown.
+    let output = run_client_command(shared, &["log", "--full", "--ansi-safe-truncate"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        !stdout.contains("\x1b[0m"),
+        "Unexpected output:\n{stdout:?}"
+    );
+
+    Ok(())
+}
+
+/// Invalid UTF-8 in the output is replaced, instead of aborting to read the lines of the log.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn invalid_utf8(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    // Add a task that writes an invalid byte between two lines of text.
+    assert_success(add_task(shared, r"printf 'a\377b\nplain\n'").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--timestamps"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(
+        stdout.contains("] a\u{FFFD}b\n"),
+        "Invalid byte hasn't been replaced:\n{stdout}"
+    );
+    assert!(
+        stdout.contains("] plain\n"),
+        "Lines after the invalid byte are missing:\n{stdout}"
+    );
+
+    Ok(())
+}
+
+/// `--squash-repeats` collapses consecutive identical lines, which also works with line numbers.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn squash_repeats(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(
+        add_task(
+            shared,
+            "for i in 1 2 3; do echo same; done; echo other; echo same",
+        )
+        .await?,
+    );
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let path = daemon.tempdir.path().join("task.log");
+    let path_arg = path.to_string_lossy().to_string();
+
+    run_client_command(
+        shared,
+        &["log", "0", "--output", &path_arg, "--squash-repeats"],
+    )?;
+    assert_eq!(
+        read_to_string(&path)?,
+        "same\n(repeated 3 times)\nother\nsame\n"
+    );
+
+    // Squashed lines are still numbered by their position in the full output.
+    let args = [
+        "log",
+        "0",
+        "--output",
+        &path_arg,
+        "--squash-repeats",
+        "--number",
+    ];
+    run_client_command(shared, &args)?;
+    assert_eq!(
+        read_to_string(&path)?,
+        "     1\tsame\n(repeated 3 times)\n     4\tother\n     5\tsame\n"
+    );
+
+    Ok(())
+}
+
+/// Searching shows the matching lines of all matching tasks and counts the matching tasks.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn search(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Force the client to read remote logs via config file.
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "printf 'ok\\nError: first\\nerror: second\\n'").await?);
+    assert_success(add_task(shared, "echo fine").await?);
+    assert_success(add_task(shared, "echo 'Error: third'").await?);
+    for id in 0..3 {
+        wait_for_task_condition(shared, id, Task::is_done).await?;
+    }
+
+    let output = run_client_command(shared, &["log", "--search", "^Error"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Error: first\n\nError: third\n"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Task 0"), "Missing task 0:\n{stderr}");
+    assert!(
+        !stderr.contains("Task 1"),
+        "Task 1 doesn't match:\n{stderr}"
+    );
+    assert!(stderr.contains("Task 2"), "Missing task 2:\n{stderr}");
+    assert!(
+        stderr.ends_with("Matched in 2 of 3 tasks.\n"),
+        "Missing summary:\n{stderr}"
+    );
+
+    let output = run_client_command(
+        shared,
+        &["log", "--search", "^error", "--ignore-case", "--count"],
+    )?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Task 0: 2 matches\nTask 2: 1 match\n"
+    );
+
+    Ok(())
+}
+
+/// Tasks that haven't been started yet are pointed out, instead of just showing no output.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn waiting_tasks() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    for _ in 0..2 {
+        let output = run_client_command(shared, &["add", "--stashed", "echo hello"])?;
+        assert!(output.status.success(), "Failed to add stashed task");
+    }
+
+    let output = run_client_command(shared, &["log", "0"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Task 0 is still stashed; use `pueue follow 0`.\n"
+    );
+
+    let output = run_client_command(shared, &["log"])?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "There are no finished tasks for group 'default' yet. Tasks 0, 1 haven't been started; \
+        use `pueue follow 0` to follow the first one.\n"
+    );
+
+    let output = run_client_command(shared, &["log", "--all"])?;
+    assert!(
+        String::from_utf8_lossy(&output.stderr).starts_with("There are no finished tasks yet."),
+        "Unexpected output for all tasks"
+    );
+
+    Ok(())
+}
+
+/// `--since-last` only prints the output that hasn't been printed by a previous call.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn since_last() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["log", "--plain", "--since-last", "0"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "first\n");
+
+    // Everything has been printed already.
+    let output = run_client_command(shared, &["log", "--plain", "--since-last", "0"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("(no new output)"),
+        "Missing note about no new output:\n{stderr}"
+    );
+
+    // Tasks that haven't been printed yet are shown in full.
+    assert_success(add_task(shared, "echo second").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+    let output = run_client_command(shared, &["log", "--plain", "--since-last"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "\nsecond\n");
+
+    Ok(())
+}
+
+/// `--include-status` shows the task information of tasks that haven't been started yet.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn include_status() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let output = run_client_command(shared, &["add", "--stashed", "echo hello"])?;
+    assert!(output.status.success(), "Failed to add stashed task");
+
+    // Tasks without output aren't shown by default.
+    let output = run_client_command(shared, &["log", "0"])?;
+    assert!(output.stdout.is_empty(), "Stashed task has been shown");
+
+    let output = run_client_command(
+        shared,
+        &["log", "--plain", "--include-status", "stashed", "0"],
+    )?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "(no output / not started)\n"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Task 0 (Stashed):"),
+        "Missing task information:\n{stderr}"
+    );
+
+    // Other statuses are still left out.
+    let output = run_client_command(shared, &["log", "--include-status", "queued,locked", "0"])?;
+    assert!(output.stdout.is_empty(), "Stashed task has been shown");
+
+    Ok(())
+}
+
+/// `--watch` needs a terminal to draw on and refuses to run otherwise.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn watch_requires_terminal() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let output = run_client_command(shared, &["log", "--watch"])?;
+    assert!(
+        !output.status.success(),
+        "Watching without a terminal succeeded"
+    );
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("requires stdout to be a terminal"),
+        "Unexpected error: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = run_client_command(shared, &["log", "--interval", "500"])?;
+    assert!(
+        !output.status.success(),
+        "--interval got accepted without --watch"
+    );
+
+    Ok(())
+}
+
+/// `--file` prints an arbitrary log file, without the need for a running daemon.
+#[test]
+fn log_file() -> Result<()> {
+    let (settings, tempdir) = daemon_base_setup()?;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    let path = tempdir.path().join("copied.log");
+    std::fs::write(&path, "first\nsecond\nthird\n")?;
+    let path = path.to_string_lossy();
+
+    let output = run_client_command(&settings.shared, &["log", "--file", &path, "--lines", "2"])?;
+    assert!(output.status.success(), "Printing the log file failed");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "second\nthird\n");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("copied.log: (last 2 of 3 lines)"),
+        "Expected line info:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = run_client_command(
+        &settings.shared,
+        &["log", "--file", &path, "--number", "--no-task-info"],
+    )?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "     1\tfirst\n     2\tsecond\n     3\tthird\n"
+    );
+
+    Ok(())
+}
+
+/// Printed tasks are always separated by a single empty line, regardless of their status.
+/// Tasks without any log, such as stashed ones, don't affect the spacing.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn separate_tasks() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    let output = run_client_command(shared, &["add", "--stashed", "echo stashed"])?;
+    assert!(output.status.success(), "Failed to add stashed task");
+    assert_success(add_task(shared, "echo second && false").await?);
+    wait_for_task_condition(shared, 2, Task::is_done).await?;
+    let output = run_client_command(shared, &["add", "--stashed", "echo stashed"])?;
+    assert!(output.status.success(), "Failed to add stashed task");
+
+    let output = run_client_command(shared, &["log"])?;
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "first\n\nsecond\n");
+
+    Ok(())
+}
+
+/// Daemons send the output of all tasks either along with each task or in chunks.
+/// If the output of a task is missing regardless, this is reported instead of waiting for
+/// chunks that'll never arrive.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn missing_remote_output() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.client.read_local_logs = false;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    init_shared_secret(&settings.shared.shared_secret_path())?;
+    let listener = get_listener(&settings.shared).await?;
+
+    let mut output = Vec::new();
+    let mut compressor = LogCompression::Snap.compressor(&mut output)?;
+    compressor.write_all(b"first\n")?;
+    drop(compressor);
+
+    let now = Local::now();
+    let mut logs = BTreeMap::new();
+    for (id, output) in [(0, Some(output)), (1, None)] {
+        let mut task = Task::new(
+            "echo".into(),
+            settings.shared.pueue_directory(),
+            HashMap::new(),
+            PUEUE_DEFAULT_GROUP.into(),
+            TaskStatus::Done {
+                enqueued_at: now,
+                start: now,
+                end: now,
+                result: TaskResult::Success,
+            },
+            Vec::new(),
+            0,
+            None,
+        );
+        task.id = id;
+        logs.insert(
+            id,
+            TaskLogResponse {
+                task,
+                output_complete: true,
+                output,
+                head_size: None,
+                stream: None,
+                first_line: None,
+                total_lines: None,
+                total_bytes: None,
+                compression: LogCompression::Snap,
+            },
+        );
+    }
+
+    // A fake daemon, which sends the output of the first task only.
+    tokio::spawn(async move {
+        loop {
+            let mut stream = listener.accept().await.unwrap();
+            let logs = logs.clone();
+            tokio::spawn(async move {
+                receive_bytes(&mut stream).await?;
+                send_bytes(PROTOCOL_VERSION.as_bytes(), &mut stream).await?;
+                while let Ok(request) = receive_request(&mut stream).await {
+                    match request {
+                        Request::Log(_) => send_response(logs.clone(), &mut stream).await?,
+                        _ => send_response(Response::Failure("".into()), &mut stream).await?,
+                    }
+                }
+                Ok::<(), pueue_lib::error::Error>(())
+            });
+        }
+    });
+
+    let output = run_client_command(&settings.shared, &["log", "--no-task-info"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with("first\n"),
+        "Expected output of task 0:\n{stdout}"
+    );
+    assert!(
+        !stdout.contains("Logs of task 1"),
+        "Expected error to be kept out of the output:\n{stdout}"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "Logs of task 1 requested from pueue daemon, but none received. \
+            Please report this bug."
+        ),
+        "Expected error to be logged:\n{stderr}"
+    );
 
     Ok(())
 }
@@ -1,9 +1,10 @@
 use std::{
     fs::{File, read_to_string},
+    io::Read,
     path::Path,
 };
 
-use pueue_lib::{Task, message::*};
+use pueue_lib::{Task, log::LogCompression, message::*};
 use tempfile::TempDir;
 
 use crate::{helper::*, internal_prelude::*};
@@ -95,9 +96,8 @@ async fn test_partial_log() -> Result<()> {
 
     // Request a partial log for task 0
     let log_message = LogRequest {
-        tasks: TaskSelection::TaskIds(vec![0]),
-        send_logs: true,
         lines: Some(5),
+        ..LogRequest::new(TaskSelection::TaskIds(vec![0]))
     };
     let response = send_request(shared, Request::Log(log_message)).await?;
     let logs = match response {
@@ -119,6 +119,39 @@ async fn test_partial_log() -> Result<()> {
     Ok(())
 }
 
+/// The output is compressed with the codec that has been requested by the client.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_zstd_compression() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo hello").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let log_message = LogRequest {
+        compression: LogCompression::Zstd,
+        ..LogRequest::new(TaskSelection::TaskIds(vec![0]))
+    };
+    let response = send_request(shared, Request::Log(log_message)).await?;
+    let Response::Log(logs) = response else {
+        bail!("Received non Log Response: {:#?}", response);
+    };
+
+    let log = logs.get(&0).unwrap();
+    assert_eq!(log.compression, LogCompression::Zstd);
+    let output = log
+        .output
+        .clone()
+        .ok_or(eyre!("Didn't find output on TaskLogResponse"))?;
+    let mut decompressed = String::new();
+    LogCompression::Zstd
+        .decompressor(output.as_slice())?
+        .read_to_string(&mut decompressed)?;
+    assert_eq!(decompressed, "hello\n");
+
+    Ok(())
+}
+
 /// Ensure that stdout and stderr are properly ordered in log output.
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_correct_log_order() -> Result<()> {
@@ -131,11 +164,7 @@ async fn test_correct_log_order() -> Result<()> {
     wait_for_task_condition(shared, 0, Task::is_done).await?;
 
     // Request all log lines
-    let log_message = LogRequest {
-        tasks: TaskSelection::TaskIds(vec![0]),
-        send_logs: true,
-        lines: None,
-    };
+    let log_message = LogRequest::new(TaskSelection::TaskIds(vec![0]));
     let response = send_request(shared, Request::Log(log_message)).await?;
     let logs = match response {
         Response::Log(logs) => logs,
@@ -174,11 +203,7 @@ async fn logs_of_group() -> Result<()> {
     wait_for_task_condition(shared, 1, Task::is_done).await?;
 
     // Request the task's logs.
-    let message = LogRequest {
-        tasks: TaskSelection::Group("test_2".to_string()),
-        send_logs: true,
-        lines: None,
-    };
+    let message = LogRequest::new(TaskSelection::Group("test_2".to_string()));
     let response = send_request(shared, message).await?;
     let logs = match response {
         Response::Log(logs) => logs,
@@ -208,11 +233,7 @@ async fn logs_for_all() -> Result<()> {
     wait_for_task_condition(shared, 1, Task::is_done).await?;
 
     // Request the task's logs.
-    let message = LogRequest {
-        tasks: TaskSelection::All,
-        send_logs: true,
-        lines: None,
-    };
+    let message = LogRequest::new(TaskSelection::All);
     let response = send_request(shared, message).await?;
     let logs = match response {
         Response::Log(logs) => logs,
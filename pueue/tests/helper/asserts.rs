@@ -103,11 +103,7 @@ pub async fn assert_worker_envs(
     // Get the log output for the task.
     let response = send_request(
         shared,
-        LogRequest {
-            tasks: TaskSelection::TaskIds(vec![task_id]),
-            send_logs: true,
-            lines: None,
-        },
+        LogRequest::new(TaskSelection::TaskIds(vec![task_id])),
     )
     .await?;
 
@@ -21,9 +21,8 @@ pub fn decompress_log(bytes: Vec<u8>) -> Result<String> {
 /// `lines: None` requests all log lines.
 pub async fn get_task_log(shared: &Shared, task_id: usize, lines: Option<usize>) -> Result<String> {
     let message = LogRequest {
-        tasks: TaskSelection::TaskIds(vec![task_id]),
-        send_logs: true,
         lines,
+        ..LogRequest::new(TaskSelection::TaskIds(vec![task_id]))
     };
     let response = send_request(shared, message).await?;
 
@@ -1,14 +1,19 @@
 //! Helper classes to read and write log files of Pueue's tasks.
 use std::{
-    fs::{File, remove_file},
-    io::{self, Read, SeekFrom, prelude::*},
+    borrow::Cow,
+    fs::{File, OpenOptions, remove_file, rename},
+    io::{self, BufReader, Read, SeekFrom, prelude::*},
+    ops::Range,
     path::{Path, PathBuf},
 };
 
+use chrono::{DateTime, Local};
 use rev_buf_reader::RevBufReader;
-use snap::write::FrameEncoder;
+use serde::{Deserialize, Serialize};
+use snap::{read::FrameDecoder, write::FrameEncoder};
+use strum::{Display, EnumString};
 
-use crate::{error::Error, internal_prelude::*};
+use crate::{error::Error, internal_prelude::*, message::TaskLogResponse};
 
 /// Get the path to the log file of a task.
 pub fn get_log_path(task_id: usize, pueue_dir: &Path) -> PathBuf {
@@ -16,9 +21,133 @@ pub fn get_log_path(task_id: usize, pueue_dir: &Path) -> PathBuf {
     task_log_dir.join(format!("{task_id}.log"))
 }
 
+/// The codec that's used to compress log output, which is sent by the daemon.
+///
+/// [LogCompression::Snap] is the default, as it's the only codec that older clients and daemons
+/// know about. [LogCompression::Zstd] usually results in a much better compression ratio, which
+/// is useful if the daemon is reached via a slow connection.
+#[derive(
+    PartialEq, Eq, Clone, Copy, Debug, Default, Display, EnumString, Deserialize, Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum LogCompression {
+    #[default]
+    Snap,
+    Zstd,
+}
+
+impl LogCompression {
+    /// Wrap a writer, so everything that's written to it is compressed with this codec.
+    /// The compressed output is finished, once the returned writer is dropped.
+    pub fn compressor<'a>(self, writer: impl Write + 'a) -> io::Result<Box<dyn Write + 'a>> {
+        self.compressor_with_level(writer, None)
+    }
+
+    /// Just like [LogCompression::compressor], but with a specific compression `level`.
+    ///
+    /// The level is ignored by codecs that don't support levels, such as snap. Zstd levels that
+    /// are out of range are clamped. `None` uses the codec's default level.
+    /// The level doesn't need to be known to decompress the output.
+    pub fn compressor_with_level<'a>(
+        self,
+        writer: impl Write + 'a,
+        level: Option<i32>,
+    ) -> io::Result<Box<dyn Write + 'a>> {
+        Ok(match self {
+            LogCompression::Snap => Box::new(FrameEncoder::new(writer)),
+            LogCompression::Zstd => {
+                let range = zstd::compression_level_range();
+                let level = level.map_or(0, |level| level.clamp(*range.start(), *range.end()));
+                Box::new(zstd::Encoder::new(writer, level)?.auto_finish())
+            }
+        })
+    }
+
+    /// Wrap a reader of output that has been compressed with this codec.
+    pub fn decompressor<'a>(self, reader: impl Read + 'a) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            LogCompression::Snap => Box::new(FrameDecoder::new(reader)),
+            LogCompression::Zstd => Box::new(zstd::Decoder::new(reader)?),
+        })
+    }
+}
+
+/// Get the path to the [snap] compressed log file of a task.
+///
+/// This file only exists if the daemon has been configured to compress the logs of finished
+/// tasks. It replaces the log file at [get_log_path].
+pub fn get_compressed_log_path(task_id: usize, pueue_dir: &Path) -> PathBuf {
+    let task_log_dir = pueue_dir.join("task_logs");
+    task_log_dir.join(format!("{task_id}.log.sz"))
+}
+
+/// Check whether the log file of a task exists, be it compressed or not.
+pub fn log_file_exists(task_id: usize, pueue_dir: &Path) -> bool {
+    get_log_path(task_id, pueue_dir).exists()
+        || get_compressed_log_path(task_id, pueue_dir).exists()
+}
+
+/// Get the path to the timestamp index of a task's log file.
+///
+/// This file only exists if the daemon has been configured to record log timestamps.
+pub fn get_log_timestamp_path(task_id: usize, pueue_dir: &Path) -> PathBuf {
+    let task_log_dir = pueue_dir.join("task_logs");
+    task_log_dir.join(format!("{task_id}.time"))
+}
+
+/// One of the two output streams of a task.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Display, EnumString, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Get the path to the log file that only contains a single output stream of a task.
+///
+/// This file only exists if the daemon has been configured to keep separate log streams.
+pub fn get_stream_log_path(task_id: usize, stream: OutputStream, pueue_dir: &Path) -> PathBuf {
+    let task_log_dir = pueue_dir.join("task_logs");
+    task_log_dir.join(format!("{task_id}.{stream}"))
+}
+
+/// Get the path to the stream index of a task's log file.
+///
+/// This file only exists if the daemon has been configured to keep separate log streams.
+pub fn get_log_stream_index_path(task_id: usize, pueue_dir: &Path) -> PathBuf {
+    let task_log_dir = pueue_dir.join("task_logs");
+    task_log_dir.join(format!("{task_id}.streams"))
+}
+
+/// Get the path to the log file of a task that contains the given output stream.
+/// `None` selects the log file that contains both streams.
+///
+/// If there's no separate log file for the stream, e.g. because the task has been started
+/// before separate log streams were enabled, this falls back to the log file with both streams.
+/// The returned stream is the one that's actually contained in the file.
+pub fn resolve_log_path(
+    task_id: usize,
+    stream: Option<OutputStream>,
+    pueue_dir: &Path,
+) -> (PathBuf, Option<OutputStream>) {
+    if let Some(stream) = stream {
+        let path = get_stream_log_path(task_id, stream, pueue_dir);
+        if path.exists() {
+            return (path, Some(stream));
+        }
+    }
+
+    (get_log_path(task_id, pueue_dir), None)
+}
+
 /// Create and return the two file handles for the `(stdout, stderr)` log file of a task.
 /// These are two handles to the same file.
 pub fn create_log_file_handles(task_id: usize, pueue_dir: &Path) -> Result<(File, File), Error> {
+    // The indices and separate streams of a previous run no longer match the new log file.
+    remove_log_side_files(task_id, pueue_dir)?;
+
     let log_path = get_log_path(task_id, pueue_dir);
     let stdout_handle = File::create(&log_path)
         .map_err(|err| Error::IoPathError(log_path, "getting stdout handle", err))?;
@@ -29,15 +158,152 @@ pub fn create_log_file_handles(task_id: usize, pueue_dir: &Path) -> Result<(File
     Ok((stdout_handle, stderr_handle))
 }
 
+/// Create the log files of a task, which keep its output streams in separate files.
+///
+/// The output of both streams still has to be written into the log file at [get_log_path],
+/// which contains the output of both streams, as well as its stream index.
+/// Returns the handles to the `(log file, stream index, stdout file, stderr file)`.
+pub fn create_stream_log_file_handles(
+    task_id: usize,
+    pueue_dir: &Path,
+) -> Result<(File, File, File, File), Error> {
+    remove_log_side_files(task_id, pueue_dir)?;
+
+    let create = |path: PathBuf, action: &'static str| {
+        File::create(&path).map_err(|err| Error::IoPathError(path, action, err))
+    };
+    Ok((
+        create(get_log_path(task_id, pueue_dir), "getting log file handle")?,
+        create(
+            get_log_stream_index_path(task_id, pueue_dir),
+            "creating stream index",
+        )?,
+        create(
+            get_stream_log_path(task_id, OutputStream::Stdout, pueue_dir),
+            "getting stdout handle",
+        )?,
+        create(
+            get_stream_log_path(task_id, OutputStream::Stderr, pueue_dir),
+            "getting stderr handle",
+        )?,
+    ))
+}
+
+/// Remove all files that accompany the log file of a task.
+fn remove_log_side_files(task_id: usize, pueue_dir: &Path) -> Result<(), Error> {
+    let paths = [
+        (
+            get_compressed_log_path(task_id, pueue_dir),
+            "removing compressed log",
+        ),
+        (
+            get_log_timestamp_path(task_id, pueue_dir),
+            "removing timestamp index",
+        ),
+        (
+            get_log_stream_index_path(task_id, pueue_dir),
+            "removing stream index",
+        ),
+        (
+            get_stream_log_path(task_id, OutputStream::Stdout, pueue_dir),
+            "removing stdout log",
+        ),
+        (
+            get_stream_log_path(task_id, OutputStream::Stderr, pueue_dir),
+            "removing stderr log",
+        ),
+    ];
+    for (path, action) in paths {
+        if path.exists() {
+            remove_file(&path).map_err(|err| Error::IoPathError(path, action, err))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Return the file handle for the log file of a task that contains the given output stream.
+/// Just like [resolve_log_path], this falls back to the log file with both streams.
+pub fn get_stream_log_file_handle(
+    task_id: usize,
+    stream: Option<OutputStream>,
+    pueue_dir: &Path,
+) -> Result<(File, Option<OutputStream>), Error> {
+    let (path, stream) = resolve_log_path(task_id, stream, pueue_dir);
+    let handle = match stream {
+        Some(_) => File::open(&path)
+            .map_err(|err| Error::IoPathError(path, "getting log file handle", err))?,
+        None => get_log_file_handle(task_id, pueue_dir)?,
+    };
+
+    Ok((handle, stream))
+}
+
 /// Return the file handle for the log file of a task.
+///
+/// If the log file has been compressed, it's decompressed into a temporary file, whose handle
+/// is returned instead. That way, it can be read and seeked just like the original log file.
 pub fn get_log_file_handle(task_id: usize, pueue_dir: &Path) -> Result<File, Error> {
     let path = get_log_path(task_id, pueue_dir);
-    let handle = File::open(&path)
-        .map_err(|err| Error::IoPathError(path, "getting log file handle", err))?;
+    match File::open(&path) {
+        Ok(handle) => Ok(handle),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            let compressed_path = get_compressed_log_path(task_id, pueue_dir);
+            if !compressed_path.exists() {
+                return Err(Error::IoPathError(path, "getting log file handle", err));
+            }
+            decompress_log_file(compressed_path)
+        }
+        Err(err) => Err(Error::IoPathError(path, "getting log file handle", err)),
+    }
+}
+
+/// Decompress a compressed log file into a temporary file and return its handle.
+fn decompress_log_file(path: PathBuf) -> Result<File, Error> {
+    let compressed = File::open(&path)
+        .map_err(|err| Error::IoPathError(path.clone(), "getting compressed log file", err))?;
+    let mut handle = tempfile::tempfile()
+        .map_err(|err| Error::IoError("creating temporary log file".to_string(), err))?;
+
+    io::copy(
+        &mut FrameDecoder::new(BufReader::new(compressed)),
+        &mut handle,
+    )
+    .map_err(|err| Error::IoPathError(path, "decompressing log file", err))?;
+    handle
+        .rewind()
+        .map_err(|err| Error::IoError("rewinding temporary log file".to_string(), err))?;
 
     Ok(handle)
 }
 
+/// Compress the log file of a finished task with [snap] and remove the uncompressed file.
+///
+/// The compressed file is only moved into place once it has been fully written, so readers
+/// always see either of both files.
+pub fn compress_task_log(task_id: usize, pueue_dir: &Path) -> Result<(), Error> {
+    let path = get_log_path(task_id, pueue_dir);
+    let compressed_path = get_compressed_log_path(task_id, pueue_dir);
+    let partial_path = compressed_path.with_extension("sz.partial");
+
+    let mut file = File::open(&path)
+        .map_err(|err| Error::IoPathError(path.clone(), "getting log file handle", err))?;
+    let partial = File::create(&partial_path).map_err(|err| {
+        Error::IoPathError(partial_path.clone(), "creating compressed log file", err)
+    })?;
+    let mut compressor = FrameEncoder::new(partial);
+    io::copy(&mut file, &mut compressor)
+        .and_then(|_| compressor.flush())
+        .map_err(|err| Error::IoPathError(partial_path.clone(), "compressing log file", err))?;
+    drop(compressor);
+
+    rename(&partial_path, &compressed_path)
+        .map_err(|err| Error::IoPathError(compressed_path, "moving compressed log file", err))?;
+    remove_file(&path).map_err(|err| Error::IoPathError(path, "removing log file", err))?;
+
+    Ok(())
+}
+
 /// Return the file handle for the log file of a task.
 pub fn get_writable_log_file_handle(task_id: usize, pueue_dir: &Path) -> Result<File, Error> {
     let path = get_log_path(task_id, pueue_dir);
@@ -57,6 +323,147 @@ pub fn clean_log_handles(task_id: usize, pueue_dir: &Path) {
             error!("Failed to remove stdout file for task {task_id} with error {err:?}");
         };
     }
+
+    if let Err(err) = remove_log_side_files(task_id, pueue_dir) {
+        error!("Failed to remove log files for task {task_id} with error {err:?}");
+    }
+}
+
+/// Append an entry to the timestamp index of a task's log file.
+///
+/// Each entry states that all log output up to the byte `offset` has been written at or
+/// before the given `time`.
+pub fn append_log_timestamp(
+    task_id: usize,
+    pueue_dir: &Path,
+    offset: u64,
+    time: DateTime<Local>,
+) -> Result<(), Error> {
+    let path = get_log_timestamp_path(task_id, pueue_dir);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|err| Error::IoPathError(path.clone(), "opening timestamp index", err))?;
+
+    writeln!(file, "{offset} {}", time.to_rfc3339())
+        .map_err(|err| Error::IoPathError(path, "writing timestamp index", err))?;
+
+    Ok(())
+}
+
+/// The timestamp index of a task's log file.
+///
+/// The daemon periodically records how far the log file has grown, which allows us to
+/// determine when a specific line of output has roughly been written.
+#[derive(Clone, Debug, Default)]
+pub struct LogTimestamps {
+    /// `(end offset, time)` pairs, sorted by offset.
+    entries: Vec<(u64, DateTime<Local>)>,
+}
+
+impl LogTimestamps {
+    /// Read the timestamp index of a task.
+    /// Returns `Ok(None)` if no index has been recorded for this task.
+    pub fn read(task_id: usize, pueue_dir: &Path) -> Result<Option<Self>, Error> {
+        let path = get_log_timestamp_path(task_id, pueue_dir);
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::IoPathError(path, "opening timestamp index", err)),
+        };
+
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line
+                .map_err(|err| Error::IoPathError(path.clone(), "reading timestamp index", err))?;
+
+            // Silently skip malformed entries, e.g. a partially written last line.
+            let Some((offset, time)) = line.split_once(' ') else {
+                continue;
+            };
+            let (Ok(offset), Ok(time)) = (offset.parse(), DateTime::parse_from_rfc3339(time))
+            else {
+                continue;
+            };
+            entries.push((offset, time.with_timezone(&Local)));
+        }
+
+        Ok(Some(LogTimestamps { entries }))
+    }
+
+    /// Get the time at which the byte at `offset` has been written.
+    ///
+    /// Returns `None` if the output at this offset hasn't been indexed yet.
+    pub fn at_offset(&self, offset: u64) -> Option<DateTime<Local>> {
+        let index = self
+            .entries
+            .partition_point(|(end_offset, _)| *end_offset <= offset);
+
+        self.entries.get(index).map(|(_, time)| *time)
+    }
+}
+
+/// Append an entry to the stream index of a task's log file.
+///
+/// Each entry states that the log output starting at byte `offset` belongs to `stream`,
+/// until the next entry.
+pub fn append_log_stream(index: &mut File, offset: u64, stream: OutputStream) -> io::Result<()> {
+    writeln!(index, "{offset} {stream}")
+}
+
+/// The stream index of a task's log file.
+///
+/// If the daemon keeps separate log streams, it records which parts of the log file belong to
+/// which output stream. This allows us to tell stdout and stderr apart in the combined output.
+#[derive(Clone, Debug, Default)]
+pub struct LogStreams {
+    /// `(start offset, stream)` pairs, sorted by offset.
+    entries: Vec<(u64, OutputStream)>,
+}
+
+impl LogStreams {
+    /// Read the stream index of a task.
+    /// Returns `Ok(None)` if no index has been recorded for this task.
+    pub fn read(task_id: usize, pueue_dir: &Path) -> Result<Option<Self>, Error> {
+        let path = get_log_stream_index_path(task_id, pueue_dir);
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::IoPathError(path, "opening stream index", err)),
+        };
+
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line =
+                line.map_err(|err| Error::IoPathError(path.clone(), "reading stream index", err))?;
+
+            // Silently skip malformed entries, e.g. a partially written last line.
+            let Some((offset, stream)) = line.split_once(' ') else {
+                continue;
+            };
+            let (Ok(offset), Ok(stream)) = (offset.parse(), stream.parse()) else {
+                continue;
+            };
+            entries.push((offset, stream));
+        }
+
+        Ok(Some(LogStreams { entries }))
+    }
+
+    /// Get the stream to which the byte at `offset` belongs.
+    ///
+    /// Returns `None` if the output at this offset hasn't been indexed.
+    pub fn at_offset(&self, offset: u64) -> Option<OutputStream> {
+        let index = self
+            .entries
+            .partition_point(|(start_offset, _)| *start_offset <= offset);
+
+        index
+            .checked_sub(1)
+            .and_then(|index| self.entries.get(index))
+            .map(|(_, stream)| *stream)
+    }
 }
 
 /// Return the output of a task. \
@@ -71,7 +478,20 @@ pub fn read_and_compress_log_file(
     lines: Option<usize>,
 ) -> Result<(Vec<u8>, bool), Error> {
     let mut file = get_log_file_handle(task_id, pueue_dir)?;
+    compress_log_file(&mut file, lines, LogCompression::Snap, None)
+}
 
+/// Compress the output of an already opened log file, just like [read_and_compress_log_file].
+/// This is used to read the log file of a single output stream.
+///
+/// The output is compressed with the given codec and level, see
+/// [LogCompression::compressor_with_level].
+pub fn compress_log_file(
+    file: &mut File,
+    lines: Option<usize>,
+    compression: LogCompression,
+    level: Option<i32>,
+) -> Result<(Vec<u8>, bool), Error> {
     let mut content = Vec::new();
 
     // Indicates whether the full log output is shown or just the last part of it.
@@ -81,19 +501,69 @@ pub fn read_and_compress_log_file(
 
     // If requested, move the cursor to the last few lines of the file.
     if let Some(lines) = lines {
-        output_complete = seek_to_last_lines(&mut file, lines)?;
+        output_complete = seek_to_last_lines(file, lines)?;
     }
 
-    // Pipe the remaining log output file it into the snappy compressor
+    // Pipe the remaining log output file it into the compressor
     {
-        let mut compressor = FrameEncoder::new(&mut content);
-        io::copy(&mut file, &mut compressor)
+        let mut compressor = compression
+            .compressor_with_level(&mut content, level)
+            .map_err(|err| Error::IoError("creating log compressor".to_string(), err))?;
+        io::copy(file, &mut compressor)
             .map_err(|err| Error::IoError("compressing log output".to_string(), err))?;
     }
 
     Ok((content, output_complete))
 }
 
+/// Return the first `head` and the last `tail` lines of a task's output. \
+/// The output is compressed using [snap], just like in [read_and_compress_log_file].
+/// Return type is `(Vec<u8>, bool, u64)`
+/// - `Vec<u8>` the compressed task output.
+/// - `bool` Whether the full task's output has been read.
+/// - `u64` The uncompressed size of the head. If lines have been omitted, the tail follows
+///   directly after the head.
+pub fn read_and_compress_log_file_head(
+    task_id: usize,
+    pueue_dir: &Path,
+    head: usize,
+    tail: Option<usize>,
+) -> Result<(Vec<u8>, bool, u64), Error> {
+    let mut file = get_log_file_handle(task_id, pueue_dir)?;
+    compress_log_file_head(&mut file, head, tail, LogCompression::Snap, None)
+}
+
+/// Compress the first and last lines of an already opened log file with the given codec and
+/// level, just like [read_and_compress_log_file_head].
+pub fn compress_log_file_head(
+    file: &mut File,
+    head: usize,
+    tail: Option<usize>,
+    compression: LogCompression,
+    level: Option<i32>,
+) -> Result<(Vec<u8>, bool, u64), Error> {
+    let (ranges, output_complete) = head_and_tail_ranges(file, Some(head), tail)?;
+
+    let mut content = Vec::new();
+    {
+        let mut compressor = compression
+            .compressor_with_level(&mut content, level)
+            .map_err(|err| Error::IoError("creating log compressor".to_string(), err))?;
+        for range in ranges.iter() {
+            file.seek(SeekFrom::Start(range.start))
+                .map_err(|err| Error::IoError("seeking to log range".to_string(), err))?;
+            io::copy(
+                &mut (&mut *file).take(range.end - range.start),
+                &mut compressor,
+            )
+            .map_err(|err| Error::IoError("compressing log output".to_string(), err))?;
+        }
+    }
+
+    let head_size = ranges.first().map_or(0, |range| range.end - range.start);
+    Ok((content, output_complete, head_size))
+}
+
 /// Return the last lines of of a task's output. \
 /// This output is uncompressed and may take a lot of memory, which is why we only read
 /// the last few lines.
@@ -127,12 +597,181 @@ pub fn read_last_lines(file: &mut File, amount: usize) -> String {
     lines.into_iter().rev().collect::<Vec<String>>().join("\n")
 }
 
+/// Determine the byte ranges of a file that contain its first `head` and last `tail` lines.
+/// If neither is set, the whole file is covered.
+///
+/// If lines are omitted between the head and the tail, two ranges are returned.
+/// Otherwise, a single range is returned.
+/// The `bool` return value indicates whether the ranges cover the whole file.
+#[allow(clippy::single_range_in_vec_init)]
+pub fn head_and_tail_ranges(
+    file: &mut File,
+    head: Option<usize>,
+    tail: Option<usize>,
+) -> Result<(Vec<Range<u64>>, bool), Error> {
+    // The file size might change while we're reading the file. Hence we have to save it now.
+    let file_end = file
+        .seek(SeekFrom::End(0))
+        .map_err(|err| Error::IoError("seeking to end of file".to_string(), err))?;
+    let head_end = match head {
+        Some(head) => end_of_first_lines(file, head)?.min(file_end),
+        None => 0,
+    };
+
+    let Some(tail) = tail else {
+        return match head {
+            Some(_) => Ok((vec![0..head_end], head_end == file_end)),
+            None => Ok((vec![0..file_end], true)),
+        };
+    };
+
+    file.seek(SeekFrom::End(0))
+        .map_err(|err| Error::IoError("seeking to end of file".to_string(), err))?;
+    let tail_start = if seek_to_last_lines(file, tail)? {
+        0
+    } else {
+        file.stream_position()
+            .map_err(|err| Error::IoError("getting tail position".to_string(), err))?
+    };
+
+    // Head and tail overlap, there's nothing to omit.
+    if tail_start <= head_end {
+        return Ok((vec![0..file_end], true));
+    }
+
+    match head {
+        Some(_) => Ok((vec![0..head_end, tail_start..file_end], false)),
+        None => Ok((vec![tail_start..file_end], false)),
+    }
+}
+
+/// Return the byte offset directly behind the first `amount` lines of a file.
+///
+/// This is the counterpart to [seek_to_last_lines], which reads from the start of the file.
+/// If the file has fewer lines than that, the offset of the end of the file is returned.
+pub fn end_of_first_lines(file: &mut File, amount: usize) -> Result<u64, Error> {
+    file.seek(SeekFrom::Start(0))
+        .map_err(|err| Error::IoError("seeking to start of file".to_string(), err))?;
+
+    let mut offset: u64 = 0;
+    let mut found_lines = 0;
+    let mut buffer = vec![0; 4096];
+    while found_lines < amount {
+        let read_bytes = file
+            .read(&mut buffer)
+            .map_err(|err| Error::IoError("reading next log chunk".to_string(), err))?;
+
+        // We hit the end of the file and read fewer lines then specified.
+        if read_bytes == 0 {
+            break;
+        }
+
+        for byte in buffer[0..read_bytes].iter() {
+            offset += 1;
+            if *byte != b'\n' {
+                continue;
+            }
+
+            found_lines += 1;
+            if found_lines == amount {
+                break;
+            }
+        }
+    }
+
+    Ok(offset)
+}
+
+/// Count the lines that end before the given byte offset of a file.
+///
+/// This is the amount of lines that precede the line at `offset`, if a line starts there.
+pub fn count_lines(file: &mut File, offset: u64) -> Result<usize, Error> {
+    file.seek(SeekFrom::Start(0))
+        .map_err(|err| Error::IoError("seeking to start of file".to_string(), err))?;
+
+    let mut reader = file.take(offset);
+    let mut lines = 0;
+    let mut buffer = vec![0; 4096];
+    loop {
+        let read_bytes = reader
+            .read(&mut buffer)
+            .map_err(|err| Error::IoError("reading next log chunk".to_string(), err))?;
+        if read_bytes == 0 {
+            break;
+        }
+
+        lines += buffer[0..read_bytes]
+            .iter()
+            .filter(|byte| **byte == b'\n')
+            .count();
+    }
+
+    Ok(lines)
+}
+
+/// Count all lines of a file, including a last line that doesn't end with a newline.
+pub fn count_total_lines(file: &mut File) -> Result<usize, Error> {
+    file.seek(SeekFrom::Start(0))
+        .map_err(|err| Error::IoError("seeking to start of file".to_string(), err))?;
+
+    let mut lines = 0;
+    let mut last_byte = None;
+    let mut buffer = vec![0; 4096];
+    loop {
+        let read_bytes = file
+            .read(&mut buffer)
+            .map_err(|err| Error::IoError("reading next log chunk".to_string(), err))?;
+        if read_bytes == 0 {
+            break;
+        }
+
+        lines += buffer[0..read_bytes]
+            .iter()
+            .filter(|byte| **byte == b'\n')
+            .count();
+        last_byte = Some(buffer[read_bytes - 1]);
+    }
+
+    // The last line isn't terminated yet, e.g. because the task is still writing it.
+    if last_byte.is_some_and(|byte| byte != b'\n') {
+        lines += 1;
+    }
+
+    Ok(lines)
+}
+
+/// Get the amount of lines and the size in bytes of a task's output, without reading it into
+/// memory. Compressed logs are measured by their uncompressed output.
+pub fn get_log_size(
+    task_id: usize,
+    stream: Option<OutputStream>,
+    pueue_dir: &Path,
+) -> Result<(usize, u64), Error> {
+    let (mut file, _) = get_stream_log_file_handle(task_id, stream, pueue_dir)?;
+    let bytes = file
+        .metadata()
+        .map_err(|err| Error::IoError("reading log file metadata".to_string(), err))?
+        .len();
+    let lines = count_total_lines(&mut file)?;
+
+    Ok((lines, bytes))
+}
+
 /// Seek the cursor of the current file to the beginning of the line that's located `amount`
 /// newlines from the back of the file.
 ///
 /// The `bool` return value indicates whether we sought to the start of the file (there were less
 /// lines than the limit). `true` means that the handle is now at the very start of the file.
 pub fn seek_to_last_lines(file: &mut File, amount: usize) -> Result<bool, Error> {
+    // No lines are requested, so we directly jump to the end of the file instead of searching
+    // for the last newline. Incomplete last lines are thereby skipped as well.
+    if amount == 0 {
+        let file_end = file
+            .seek(SeekFrom::End(0))
+            .map_err(|err| Error::IoError("seeking to end of file".to_string(), err))?;
+        return Ok(file_end == 0);
+    }
+
     let mut reader = RevBufReader::new(file);
     // The position from which the RevBufReader starts reading.
     // The file size might change while we're reading the file. Hence we have to save it now.
@@ -165,6 +804,11 @@ pub fn seek_to_last_lines(file: &mut File, amount: usize) -> Result<bool, Error>
         // forward order. Since we want to scan from the back, we have to reverse the buffer
         for byte in buffer[0..read_bytes].iter().rev() {
             total_read_bytes += 1;
+            // The last line isn't terminated, e.g. because the process crashed while writing it.
+            // It still counts as a line, just as if it ended with a newline.
+            if total_read_bytes == 1 && *byte != b'\n' {
+                found_lines += 1;
+            }
             if *byte != b'\n' {
                 continue;
             }
@@ -202,3 +846,284 @@ pub fn seek_to_last_lines(file: &mut File, amount: usize) -> Result<bool, Error>
 
     Ok(false)
 }
+
+/// Seek the cursor of the current file to the beginning of the first line that's located within
+/// the last `amount` bytes of the file.
+///
+/// Only complete lines are shown that way. However, if there's no line break within those bytes,
+/// the cursor is placed exactly `amount` bytes before the end of the file, so single lines
+/// that're longer than the limit are still partially shown.
+///
+/// The `bool` return value indicates whether we sought to the start of the file (the file is
+/// smaller than the limit). `true` means that the handle is now at the very start of the file.
+pub fn seek_to_last_bytes(file: &mut File, amount: u64) -> Result<bool, Error> {
+    // The file size might change while we're reading the file. Hence we have to save it now.
+    let file_end = file
+        .seek(SeekFrom::End(0))
+        .map_err(|err| Error::IoError("seeking to end of file".to_string(), err))?;
+
+    if file_end <= amount {
+        file.seek(SeekFrom::Start(0))
+            .map_err(|err| Error::IoError("seeking to start of file".to_string(), err))?;
+        return Ok(true);
+    }
+
+    // Also read the byte right before the limit, in case the limit is exactly at a line start.
+    let limit = file_end - amount;
+    file.seek(SeekFrom::Start(limit - 1))
+        .map_err(|err| Error::IoError("seeking to byte limit".to_string(), err))?;
+    let skipped_bytes = BufReader::new((&mut *file).take(amount + 1))
+        .skip_until(b'\n')
+        .map_err(|err| Error::IoError("reading next log chunk".to_string(), err))?;
+
+    // Fall back to the exact limit, if there's no following line within the limit.
+    let mut position = limit - 1 + skipped_bytes as u64;
+    if position >= file_end {
+        position = limit;
+    }
+
+    file.seek(SeekFrom::Start(position))
+        .map_err(|err| Error::IoError("seeking to byte limit".to_string(), err))?;
+
+    Ok(false)
+}
+
+/// Decode a single line of output and remove its line break.
+///
+/// Tasks may write arbitrary bytes, such as binary or Latin-1 output. Invalid UTF-8 is thereby
+/// replaced, instead of aborting to read the remaining output.
+pub fn decode_line(line: &[u8]) -> Cow<'_, str> {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8_lossy(line)
+}
+
+/// Lazily iterates over the lines of a task's log.
+///
+/// The log is either read from a local log file or from the compressed output that has been sent
+/// by the daemon, which is decompressed on the fly. Lines are decoded via [decode_line].
+///
+/// The reader also keeps track of its position in the log, which allows callers to map lines to
+/// [LogTimestamps] or [LogStreams]. It can be read as plain bytes as well, e.g. to copy output
+/// that doesn't need to be processed line by line.
+pub struct LogReader<'a> {
+    reader: BufReader<Box<dyn Read + 'a>>,
+    /// The position in the log of the next byte that's read.
+    offset: u64,
+    /// The position in the log of the line that has been returned last.
+    line_offset: u64,
+    line: Vec<u8>,
+}
+
+impl<'a> LogReader<'a> {
+    /// Read uncompressed output, such as a local log file or a part of it.
+    pub fn new(reader: impl Read + 'a) -> Self {
+        Self {
+            reader: BufReader::new(Box::new(reader)),
+            offset: 0,
+            line_offset: 0,
+            line: Vec::new(),
+        }
+    }
+
+    /// Read the compressed output of a [TaskLogResponse].
+    /// If the daemon didn't send any output, there aren't any lines.
+    pub fn from_response(response: &'a TaskLogResponse) -> io::Result<Self> {
+        let reader: Box<dyn Read + 'a> = match response.output.as_ref() {
+            Some(bytes) => response.compression.decompressor(bytes.as_slice())?,
+            None => Box::new(io::empty()),
+        };
+
+        Ok(Self::new(reader))
+    }
+
+    /// Set the position in the log at which the reader starts.
+    /// This is needed to map lines to their offset, if a log file isn't read from the start.
+    pub fn with_offset(mut self, offset: u64) -> Self {
+        self.offset = offset;
+        self.line_offset = offset;
+        self
+    }
+
+    /// The position in the log at which the line that has been returned last starts.
+    pub fn line_offset(&self) -> u64 {
+        self.line_offset
+    }
+}
+
+impl Iterator for LogReader<'_> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.line.clear();
+        match self.reader.read_until(b'\n', &mut self.line) {
+            Ok(0) => None,
+            Ok(read_bytes) => {
+                self.line_offset = self.offset;
+                self.offset += read_bytes as u64;
+                Some(Ok(decode_line(&self.line).into_owned()))
+            }
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+impl Read for LogReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read_bytes = self.reader.read(buf)?;
+        self.offset += read_bytes as u64;
+        Ok(read_bytes)
+    }
+}
+
+impl BufRead for LogReader<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.reader.consume(amount);
+        self.offset += amount as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Write some content into a file and read it from where [seek_to_last_lines] leaves the
+    /// cursor.
+    fn last_lines(content: &str, amount: usize) -> (String, bool) {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+
+        let at_start = seek_to_last_lines(&mut file, amount).unwrap();
+        let mut output = String::new();
+        file.read_to_string(&mut output).unwrap();
+
+        (output, at_start)
+    }
+
+    #[test]
+    fn last_lines_with_trailing_newline() {
+        assert_eq!(last_lines("a\nb\nc\n", 2), ("b\nc\n".into(), false));
+        assert_eq!(last_lines("a\nb\nc\n", 3), ("a\nb\nc\n".into(), true));
+        assert_eq!(last_lines("a\nb\nc\n", 4), ("a\nb\nc\n".into(), true));
+    }
+
+    #[test]
+    fn last_lines_without_trailing_newline() {
+        assert_eq!(last_lines("a\nb\nc", 1), ("c".into(), false));
+        assert_eq!(last_lines("a\nb\nc", 2), ("b\nc".into(), false));
+        assert_eq!(last_lines("a\nb\nc", 3), ("a\nb\nc".into(), true));
+    }
+
+    #[test]
+    fn last_lines_of_empty_and_single_line_files() {
+        assert_eq!(last_lines("", 1), ("".into(), true));
+        assert_eq!(last_lines("", 0), ("".into(), true));
+        assert_eq!(last_lines("a\n", 1), ("a\n".into(), true));
+        assert_eq!(last_lines("a", 1), ("a".into(), true));
+        // No lines are requested, which skips an unterminated line as well.
+        assert_eq!(last_lines("a", 0), ("".into(), false));
+    }
+
+    #[test]
+    fn log_reader_reads_local_file() {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(b"first\r\nsecond\n\xffthird").unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut reader = LogReader::new(file).with_offset(10);
+        assert_eq!(reader.next().unwrap().unwrap(), "first");
+        assert_eq!(reader.line_offset(), 10);
+        assert_eq!(reader.next().unwrap().unwrap(), "second");
+        assert_eq!(reader.line_offset(), 17);
+        // Invalid UTF-8 is replaced and the last line doesn't need to be terminated.
+        assert_eq!(reader.next().unwrap().unwrap(), "\u{FFFD}third");
+        assert_eq!(reader.line_offset(), 24);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn log_reader_decompresses_response() {
+        let task = crate::task::Task::new(
+            "ls".into(),
+            PathBuf::from("/"),
+            Default::default(),
+            "default".into(),
+            crate::task::TaskStatus::Queued {
+                enqueued_at: Local::now(),
+            },
+            Vec::new(),
+            0,
+            None,
+        );
+        for compression in [LogCompression::Snap, LogCompression::Zstd] {
+            let mut output = Vec::new();
+            {
+                let mut compressor = compression.compressor(&mut output).unwrap();
+                compressor.write_all(b"first\nsecond\n").unwrap();
+            }
+            let mut response = TaskLogResponse {
+                task: task.clone(),
+                output_complete: true,
+                output: Some(output),
+                head_size: None,
+                stream: None,
+                first_line: None,
+                total_lines: None,
+                total_bytes: None,
+                compression,
+            };
+
+            let lines = LogReader::from_response(&response)
+                .unwrap()
+                .collect::<io::Result<Vec<String>>>()
+                .unwrap();
+            assert_eq!(lines, vec!["first", "second"], "{compression}");
+
+            // Without any output, there aren't any lines.
+            response.output = None;
+            assert!(
+                LogReader::from_response(&response)
+                    .unwrap()
+                    .next()
+                    .is_none()
+            );
+        }
+    }
+
+    /// Output compressed with any level is decompressed without knowing the level.
+    /// Levels that are out of range are clamped and snap ignores levels altogether.
+    #[test]
+    fn compress_with_level() {
+        let content = "some repetitive log output\n".repeat(1000);
+        let compress = |compression: LogCompression, level| {
+            let mut output = Vec::new();
+            {
+                let mut compressor = compression
+                    .compressor_with_level(&mut output, level)
+                    .unwrap();
+                compressor.write_all(content.as_bytes()).unwrap();
+            }
+            let mut decompressed = String::new();
+            compression
+                .decompressor(output.as_slice())
+                .unwrap()
+                .read_to_string(&mut decompressed)
+                .unwrap();
+            assert_eq!(decompressed, content, "{compression} {level:?}");
+            output.len()
+        };
+
+        assert!(compress(LogCompression::Zstd, Some(19)) <= compress(LogCompression::Zstd, None));
+        compress(LogCompression::Zstd, Some(100));
+        assert_eq!(
+            compress(LogCompression::Snap, Some(19)),
+            compress(LogCompression::Snap, None)
+        );
+    }
+}
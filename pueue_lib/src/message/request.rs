@@ -1,10 +1,16 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
 
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString, VariantNames};
 
-use crate::message::EditableTask;
+use crate::{
+    log::{LogCompression, OutputStream},
+    message::EditableTask,
+};
 
 /// Macro to simplify creating [From] implementations for each variant-contained
 /// Request; e.g. `impl_into_request!(AddRequest, Request::Add)` to make it possible
@@ -278,14 +284,25 @@ impl_into_request!(ShutdownRequest, Request::DaemonShutdown);
 /// Request the live streaming of a set of running tasks.
 ///
 /// **WARNING**:
-/// Even though this type currently accepts a TaskSelection, only
-/// `TaskSelection::TaskIds(vec![])` and `TaskSelection::TaskIds(vec![id])` are accepted.
-/// We already use this format in preparation for <https://github.com/Nukesor/pueue/issues/614>
-/// That way we can stay forwards compatible without having to break the API.
+/// `TaskSelection::All` isn't supported and behaves like an empty `TaskSelection::TaskIds`,
+/// which follows the single running task, if there's exactly one.
+/// `TaskSelection::Group` follows all tasks of that group that're running.
+///
+/// `offsets` Continue following tasks at these positions of their log files instead of showing
+///     their last `lines`. This is used to resume following after the connection has been lost.
+/// `stream` Only follow this output stream, if the daemon keeps separate log streams.
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub struct StreamRequest {
     pub tasks: TaskSelection,
     pub lines: Option<usize>,
+    #[serde(default)]
+    pub offsets: BTreeMap<usize, u64>,
+    #[serde(default)]
+    pub stream: Option<OutputStream>,
+    /// Only send roughly the last X bytes of output that has been written before following.
+    /// If `lines` is given as well, the smaller amount of output is sent.
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
 }
 impl_into_request!(StreamRequest, Request::Stream);
 
@@ -294,14 +311,74 @@ impl_into_request!(StreamRequest, Request::Stream);
 /// `tasks` specifies the requested tasks.
 /// `send_logs` Determines whether logs should be sent at all.
 /// `lines` Determines whether only a few lines of log should be returned.
+/// `head` Only send the first `head` lines, followed by the last `tail` lines if `tail` is set.
+///     `lines` should be `None` in this case. That way, older daemons, which don't support this,
+///     send the full output, which can then be trimmed by the client.
+/// `stream` Only send the output of this stream, if the daemon keeps separate log streams.
+///     Otherwise, the output of both streams is sent.
+/// `line_numbers` Determines whether the daemon should send the number of the first line of the
+///     chunked output via [`super::TaskLogResponse::first_line`].
+/// `chunked` Determines whether logs should be sent in chunks instead of a single payload.
+///     Daemons that support this respond with a [`super::Response::Log`] without any output,
+///     followed by a series of [`super::Response::LogChunk`] and a final
+///     [`super::Response::Close`]. Older daemons ignore this flag.
+/// `count` Only send the size of each task's output via [`super::TaskLogResponse::total_lines`]
+///     and [`super::TaskLogResponse::total_bytes`], without any output.
+///     Older daemons ignore this flag and don't send the size.
+/// `task_lines` Send this amount of last lines for specific tasks, instead of `lines` or `tail`.
+///     This allows clients to show a different amount of lines of each task, e.g. a
+///     percentage of its output. Older daemons ignore this and use `lines` or `tail` instead.
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub struct LogRequest {
     pub tasks: TaskSelection,
     pub send_logs: bool,
     pub lines: Option<usize>,
+    #[serde(default)]
+    pub head: Option<usize>,
+    #[serde(default)]
+    pub tail: Option<usize>,
+    #[serde(default)]
+    pub stream: Option<OutputStream>,
+    #[serde(default)]
+    pub line_numbers: bool,
+    #[serde(default)]
+    pub chunked: bool,
+    #[serde(default)]
+    pub count: bool,
+    /// The codec the output should be compressed with.
+    /// Older clients don't send this and always get snap compressed output.
+    #[serde(default)]
+    pub compression: LogCompression,
+    /// The level the output should be compressed with, if the codec supports levels.
+    /// Older daemons ignore this and use the codec's default level.
+    #[serde(default)]
+    pub compression_level: Option<i32>,
+    #[serde(default)]
+    pub task_lines: BTreeMap<usize, usize>,
 }
 impl_into_request!(LogRequest, Request::Log);
 
+impl LogRequest {
+    /// Request the full, snap compressed logs of the given tasks in a single response.
+    /// All other options are disabled and may be set via struct update syntax.
+    pub fn new(tasks: TaskSelection) -> Self {
+        Self {
+            tasks,
+            send_logs: true,
+            lines: None,
+            head: None,
+            tail: None,
+            stream: None,
+            line_numbers: false,
+            chunked: false,
+            count: false,
+            compression: LogCompression::Snap,
+            compression_level: None,
+            task_lines: BTreeMap::new(),
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub struct ParallelRequest {
     pub parallel_tasks: usize,
@@ -1,9 +1,10 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    log::{LogCompression, OutputStream},
     message::EditableTask,
     state::{Group, State},
     task::Task,
@@ -61,6 +62,10 @@ pub enum Response {
     /// This is the response to [`super::Request::Log`]
     Log(BTreeMap<usize, TaskLogResponse>),
 
+    /// The next chunk of log output of a task.
+    /// This is sent after [`Response::Log`], if the client requested chunked logs.
+    LogChunk(LogChunkResponse),
+
     Group(GroupResponse),
 
     /// The next chunk of output, that's send to the client.
@@ -94,7 +99,37 @@ pub struct TaskLogResponse {
     pub task: Task,
     /// Indicates whether the log output has been truncated or not.
     pub output_complete: bool,
+    /// The compressed log output.
+    /// This is `None` if no logs have been requested or if they're sent via
+    /// [`Response::LogChunk`] instead.
     pub output: Option<Vec<u8>>,
+    /// The uncompressed size of the first lines, if they've been requested via
+    /// [`super::LogRequest::head`]. If lines have been omitted, the last lines directly follow
+    /// the head. Chunked output is always split at this position.
+    #[serde(default)]
+    pub head_size: Option<u64>,
+    /// The output stream that has been sent, if only a single stream has been requested via
+    /// [`super::LogRequest::stream`]. `None` if the output of both streams has been sent.
+    #[serde(default)]
+    pub stream: Option<OutputStream>,
+    /// The number of the first line of the chunked output, starting at `1`. If the first lines
+    /// have been requested as well, this is the number of the first of the last lines.
+    /// Only set if requested via [`super::LogRequest::line_numbers`].
+    #[serde(default)]
+    pub first_line: Option<usize>,
+    /// The total amount of lines of the task's output.
+    /// Only set if the output has been limited and isn't complete, or if only the size of the
+    /// output has been requested via [`super::LogRequest::count`].
+    #[serde(default)]
+    pub total_lines: Option<usize>,
+    /// The total size of the task's output in bytes.
+    /// Only set if it has been requested via [`super::LogRequest::count`].
+    #[serde(default)]
+    pub total_bytes: Option<u64>,
+    /// The codec that `output` and the chunks of this task have been compressed with.
+    /// Older daemons don't send this, as they always use snap.
+    #[serde(default)]
+    pub compression: LogCompression,
 }
 impl_into_response!(BTreeMap<usize, TaskLogResponse>, Response::Log);
 
@@ -110,6 +145,34 @@ impl std::fmt::Debug for TaskLogResponse {
     }
 }
 
+/// A chunk of log output of a single task.
+///
+/// Chunks of a task are sent in order and each chunk contains only complete lines,
+/// unless a single line is too large to fit into a chunk.
+#[derive(PartialEq, Eq, Clone, Deserialize, Serialize)]
+pub struct LogChunkResponse {
+    pub task_id: usize,
+    /// The compressed log output. Each chunk can be decompressed on its own.
+    pub output: Vec<u8>,
+    /// The output of the task couldn't be read. This is the last chunk of the task and its
+    /// `output` is empty. The output of the other tasks is still sent.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+impl_into_response!(LogChunkResponse, Response::LogChunk);
+
+/// We use a custom `Debug` implementation for [LogChunkResponse] for the same reason as for
+/// [TaskLogResponse].
+impl std::fmt::Debug for LogChunkResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogChunkResponse")
+            .field("task_id", &self.task_id)
+            .field("output", &"hidden")
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
 /// Group info send by the daemon.
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub struct GroupResponse {
@@ -123,5 +186,20 @@ impl_into_response!(GroupResponse, Response::Group);
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub struct StreamResponse {
     pub logs: BTreeMap<usize, String>,
+    /// The position in each task's log file right behind the sent output.
+    #[serde(default)]
+    pub offsets: BTreeMap<usize, u64>,
+    /// The position in each task's log file at which the sent output starts.
+    /// Clients use this to detect output that's been lost or sent twice, e.g. after a reconnect.
+    #[serde(default)]
+    pub starts: BTreeMap<usize, u64>,
+    /// The tasks that have been paused since the last response.
+    /// They're still followed and their output is sent once they continue.
+    #[serde(default)]
+    pub paused: BTreeSet<usize>,
+    /// The daemon's current time, which allows clients to detect skewed clocks.
+    /// Older daemons don't send it.
+    #[serde(default)]
+    pub daemon_time: Option<DateTime<Local>>,
 }
 impl_into_response!(StreamResponse, Response::Stream);
@@ -1,6 +1,10 @@
 //! This module contains all platform unspecific default values and helper functions for working
 //! with our setting representation.
 
+use std::collections::BTreeMap;
+
+use crate::settings::LogLevelColor;
+
 /// The `Default` impl for `bool` is `false`.
 /// This function covers the `true` case.
 pub(crate) fn default_true() -> bool {
@@ -23,6 +27,42 @@ pub(crate) fn default_status_datetime_format() -> String {
     "%Y-%m-%d\n%H:%M:%S".to_string()
 }
 
+pub(crate) fn default_log_timestamp_format() -> String {
+    "%Y-%m-%d %H:%M:%S%.3f".to_string()
+}
+
+pub(crate) fn default_log_annotation_prefix() -> String {
+    "#pueue".to_string()
+}
+
+pub(crate) fn default_log_lines() -> usize {
+    15
+}
+
+pub(crate) fn default_follow_interval_ms() -> u64 {
+    250
+}
+
+pub(crate) fn default_clock_skew_warning_secs() -> u64 {
+    5
+}
+
+pub(crate) fn default_env_secret_patterns() -> Vec<String> {
+    ["*_TOKEN", "*SECRET*", "*PASSWORD*", "*_KEY"]
+        .into_iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+pub(crate) fn default_log_level_colors() -> BTreeMap<String, LogLevelColor> {
+    BTreeMap::from([
+        ("ERROR".to_string(), LogLevelColor::Red),
+        ("WARN".to_string(), LogLevelColor::Yellow),
+        ("INFO".to_string(), LogLevelColor::None),
+        ("DEBUG".to_string(), LogLevelColor::Dim),
+    ])
+}
+
 pub(crate) fn default_callback_log_lines() -> usize {
     10
 }
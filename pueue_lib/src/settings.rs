@@ -1,6 +1,6 @@
 //! Pueue's configuration file representation.
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs::{File, create_dir_all},
     io::{BufReader, prelude::*},
     path::{Path, PathBuf},
@@ -9,7 +9,7 @@ use std::{
 use serde::{Deserialize, Serialize};
 use shellexpand::tilde;
 
-use crate::{error::Error, internal_prelude::*, setting_defaults::*};
+use crate::{error::Error, internal_prelude::*, log::LogCompression, setting_defaults::*};
 
 /// The environment variable that can be set to overwrite pueue's config path.
 pub const PUEUE_CONFIG_PATH_ENV: &str = "PUEUE_CONFIG_PATH";
@@ -81,6 +81,16 @@ pub struct Shared {
     ///
     /// The path to the file containing the shared secret used to authenticate the client.
     pub shared_secret_path: Option<PathBuf>,
+
+    /// The codec that the client asks the daemon to compress log output with.
+    /// Daemons that don't support the codec fall back to `snap`.
+    #[serde(default = "Default::default")]
+    pub log_compression: LogCompression,
+    /// The level that the client asks the daemon to compress log output with.
+    /// Only `zstd` supports levels, from `1` (fastest) to `22` (smallest output), while `snap`
+    /// ignores it. If this isn't set, the codec's default level is used.
+    #[serde(default = "Default::default")]
+    pub log_compression_level: Option<i32>,
 }
 
 /// The mode in which the client should edit tasks.
@@ -94,6 +104,33 @@ pub enum EditMode {
     Files,
 }
 
+/// How lines that have been written to stderr are highlighted, when showing interleaved streams.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Deserialize, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StderrColor {
+    /// Show stderr lines in red.
+    #[default]
+    Red,
+    /// Show stderr lines dimmed.
+    Dim,
+    /// Don't highlight stderr lines.
+    None,
+}
+
+/// The color of lines with a certain log level, when highlighting them via `--color-levels`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevelColor {
+    Red,
+    Yellow,
+    Green,
+    Blue,
+    /// Show lines dimmed.
+    Dim,
+    /// Don't highlight lines.
+    None,
+}
+
 /// All settings which are used by the client
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub struct Client {
@@ -127,6 +164,65 @@ pub struct Client {
     /// The format that will be used to display datetime formats in `pueue status`.
     #[serde(default = "default_status_datetime_format")]
     pub status_datetime_format: String,
+    /// The format of the timestamps that're prefixed to log lines via `--timestamps`.
+    /// Besides `strftime` format strings, `rfc3339` and `unix` are supported.
+    #[serde(default = "default_log_timestamp_format")]
+    pub log_timestamp_format: String,
+    /// The amount of lines that `pueue log` shows per task, unless `--lines` or `--full` is
+    /// given. `0` only shows the task information without any output.
+    #[serde(default = "default_log_lines")]
+    pub default_log_lines: usize,
+    /// The prefix of the task metadata lines that `pueue log --annotate` prepends to the output.
+    #[serde(default = "default_log_annotation_prefix")]
+    pub log_annotation_prefix: String,
+    /// The interval in milliseconds at which `pueue follow` checks local log files for new
+    /// output. Lower values show new output with less delay, but cause more wakeups and thereby
+    /// CPU usage, especially when following many tasks at once.
+    #[serde(default = "default_follow_interval_ms")]
+    pub follow_interval_ms: u64,
+    /// `pueue follow --timestamps` warns once, if the clocks of the daemon and the client differ
+    /// by more than this amount of seconds, as the shown timestamps may then be off.
+    /// `0` disables the check.
+    #[serde(default = "default_clock_skew_warning_secs")]
+    pub clock_skew_warning_secs: u64,
+    /// The maximum amount of lines per second that `pueue follow` prints of each task.
+    /// Excess lines are skipped and pointed out. `None` doesn't limit the output.
+    #[serde(default = "Default::default")]
+    pub follow_throttle: Option<u32>,
+    /// The values of environment variables whose names match any of these patterns are
+    /// redacted, when including task environments via `pueue log --json --with-env`.
+    /// `*` matches any amount of characters, names are matched case-insensitively.
+    #[serde(default = "default_env_secret_patterns")]
+    pub env_secret_patterns: Vec<String>,
+    /// How lines that have been written to stderr are highlighted, when showing the interleaved
+    /// output of both streams via `--interleaved`. Only applies if colors are enabled.
+    #[serde(default = "Default::default")]
+    pub stderr_color: StderrColor,
+    /// The colors of lines with these log levels, when highlighting them via
+    /// `pueue log --color-levels`. Log levels are matched case-sensitively at the start of a
+    /// line, optionally after a timestamp.
+    #[serde(default = "default_log_level_colors")]
+    pub log_level_colors: BTreeMap<String, LogLevelColor>,
+    /// Whether `pueue log` pipes its output through `$PAGER`, or `less` if that isn't set.
+    /// The pager is only used if stdout is a terminal.
+    #[serde(default = "Default::default")]
+    pub pager: bool,
+    /// Whether the task ids in the headers of `pueue log` are colored by task, which makes it
+    /// easier to spot where the output of a task starts.
+    #[serde(default = "Default::default")]
+    pub colorize_task_ids: bool,
+    /// Whether `pueue log` wraps long lines at the width of the terminal.
+    /// Wrapped lines are indented, so they're aligned under the content of the line.
+    #[serde(default = "Default::default")]
+    pub wrap_logs: bool,
+    /// Whether `pueue log` shows the group of tasks in the `default` group.
+    /// The group of tasks in any other group is always shown.
+    #[serde(default = "Default::default")]
+    pub show_default_group: bool,
+    /// Whether the task information of `pueue log` shows how long ago a task started and
+    /// finished, as well as how long it ran, next to the absolute times.
+    #[serde(default = "Default::default")]
+    pub show_relative_times: bool,
 }
 
 /// All settings which are used by the daemon
@@ -146,6 +242,22 @@ pub struct Daemon {
     /// time. You can expect a ~10 compression ratio.
     #[serde(default = "Default::default")]
     pub compress_state_file: bool,
+    /// If this is set to `true`, the daemon periodically records how far each task's log file
+    /// has grown. This index allows the client to show the actual time at which a line has
+    /// been written when using `--timestamps`.
+    #[serde(default = "Default::default")]
+    pub record_log_timestamps: bool,
+    /// If this is set to `true`, the stdout and stderr of tasks are additionally written into
+    /// separate log files. This allows the client to only show one of them or to tell them apart
+    /// when using `--interleaved`.
+    #[serde(default = "Default::default")]
+    pub separate_log_streams: bool,
+    /// If this is set to `true`, the log files of finished tasks are compressed with snappy.
+    /// The client transparently decompresses them, which makes reading them a bit slower.
+    ///
+    /// The log files of tasks whose streams are kept in separate files aren't compressed.
+    #[serde(default = "Default::default")]
+    pub compress_task_logs: bool,
     /// The callback that's called whenever a task finishes.
     pub callback: Option<String>,
     /// Environment variables that can be will be injected into all executed processes.
@@ -193,6 +305,8 @@ impl Default for Shared {
             daemon_cert: None,
             daemon_key: None,
             shared_secret_path: None,
+            log_compression: LogCompression::default(),
+            log_compression_level: None,
         }
     }
 }
@@ -209,6 +323,20 @@ impl Default for Client {
             max_status_lines: None,
             status_time_format: default_status_time_format(),
             status_datetime_format: default_status_datetime_format(),
+            log_timestamp_format: default_log_timestamp_format(),
+            default_log_lines: default_log_lines(),
+            log_annotation_prefix: default_log_annotation_prefix(),
+            follow_interval_ms: default_follow_interval_ms(),
+            clock_skew_warning_secs: default_clock_skew_warning_secs(),
+            follow_throttle: Default::default(),
+            env_secret_patterns: default_env_secret_patterns(),
+            stderr_color: Default::default(),
+            log_level_colors: default_log_level_colors(),
+            pager: Default::default(),
+            colorize_task_ids: Default::default(),
+            wrap_logs: Default::default(),
+            show_default_group: Default::default(),
+            show_relative_times: Default::default(),
         }
     }
 }
@@ -221,6 +349,9 @@ impl Default for Daemon {
             callback: None,
             callback_log_lines: default_callback_log_lines(),
             compress_state_file: false,
+            record_log_timestamps: false,
+            separate_log_streams: false,
+            compress_task_logs: false,
             shell_command: None,
             env_vars: HashMap::new(),
         }
@@ -8,7 +8,7 @@ use tracing_subscriber::{
 pub mod settings {
 
     use portpicker::pick_unused_port;
-    use pueue_lib::settings::*;
+    use pueue_lib::{log::LogCompression, settings::*};
     use tempfile::{Builder, TempDir};
 
     pub fn get_shared_settings(
@@ -38,6 +38,8 @@ pub mod settings {
             daemon_cert: Some(tempdir_path.join("certs").join("daemon.cert")),
             daemon_key: Some(tempdir_path.join("certs").join("daemon.key")),
             shared_secret_path: Some(tempdir_path.join("secret")),
+            log_compression: LogCompression::Snap,
+            log_compression_level: None,
         };
 
         (shared_settings, tempdir)